@@ -14,14 +14,107 @@ CREATE TABLE IF NOT EXISTS products (
     category TEXT DEFAULT 'ao-dai',
     status TEXT DEFAULT 'active',
     sort_order INTEGER DEFAULT 0,
+    compare_at_price INTEGER,
+    sale_price INTEGER,
+    sale_starts_at TEXT,
+    sale_ends_at TEXT,
+    product_type TEXT DEFAULT 'single',
+    bundle_discount_percent INTEGER,
+    stock INTEGER,
+    rental_price_per_day INTEGER, -- NULL when the product is sale-only
+    rental_deposit INTEGER DEFAULT 0,
+    rental_buffer_days INTEGER DEFAULT 1, -- cleaning time between rentals
+    rental_units TEXT DEFAULT '{}', -- pieces for rent per variant, {"S": 1, "M": 2}
     created_at TEXT NOT NULL,
     updated_at TEXT
 );
 
+-- Components of bundle products (outfit sets)
+CREATE TABLE IF NOT EXISTS bundle_items (
+    bundle_id TEXT NOT NULL,
+    component_id TEXT NOT NULL,
+    variant TEXT DEFAULT '',
+    quantity INTEGER DEFAULT 1,
+    sort_order INTEGER DEFAULT 0,
+    PRIMARY KEY (bundle_id, component_id, variant),
+    FOREIGN KEY (bundle_id) REFERENCES products(id) ON DELETE CASCADE,
+    FOREIGN KEY (component_id) REFERENCES products(id)
+);
+
+-- Customization options for bespoke (may đo) orders
+CREATE TABLE IF NOT EXISTS product_option_groups (
+    id TEXT PRIMARY KEY,
+    product_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT DEFAULT '',
+    required INTEGER DEFAULT 0,
+    max_select INTEGER DEFAULT 1,
+    sort_order INTEGER DEFAULT 0,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS product_option_choices (
+    id TEXT PRIMARY KEY,
+    group_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    price_delta INTEGER DEFAULT 0,
+    sort_order INTEGER DEFAULT 0,
+    FOREIGN KEY (group_id) REFERENCES product_option_groups(id) ON DELETE CASCADE
+);
+
+-- Admin-curated "pairs well with" links between products
+CREATE TABLE IF NOT EXISTS product_pairings (
+    product_id TEXT NOT NULL,
+    paired_product_id TEXT NOT NULL,
+    sort_order INTEGER DEFAULT 0,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (product_id, paired_product_id),
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE,
+    FOREIGN KEY (paired_product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- Promotions table (category NULL = catalog-wide)
+CREATE TABLE IF NOT EXISTS promotions (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    discount_percent INTEGER NOT NULL,
+    category TEXT,
+    starts_at TEXT,
+    ends_at TEXT,
+    active INTEGER DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- Customers, one per normalized phone number (see phone::normalize)
+CREATE TABLE IF NOT EXISTS customers (
+    id TEXT PRIMARY KEY,
+    phone TEXT UNIQUE NOT NULL,
+    name TEXT NOT NULL DEFAULT '',
+    email TEXT DEFAULT '',
+    notes TEXT DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- Saved measurements of a customer or the people they order for
+CREATE TABLE IF NOT EXISTS customer_measurements (
+    id TEXT PRIMARY KEY,
+    customer_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    measurements TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT,
+    FOREIGN KEY (customer_id) REFERENCES customers(id) ON DELETE CASCADE
+);
+
 -- Orders table
 CREATE TABLE IF NOT EXISTS orders (
     id TEXT PRIMARY KEY,
+    customer_id TEXT,
+    order_number TEXT,
     customer_name TEXT NOT NULL,
+    customer_name_folded TEXT, -- lower-case without diacritics, for sorting
     customer_phone TEXT NOT NULL,
     customer_email TEXT DEFAULT '',
     product_id TEXT,
@@ -29,9 +122,325 @@ CREATE TABLE IF NOT EXISTS orders (
     measurements TEXT DEFAULT '',
     notes TEXT DEFAULT '',
     status TEXT DEFAULT 'pending',
+    subtotal INTEGER DEFAULT 0,
+    discount_amount INTEGER DEFAULT 0,
+    total INTEGER DEFAULT 0,
+    coupon_code TEXT,
+    delivery_date TEXT,
+    fitting_date TEXT,
+    tracking_code TEXT,
+    suspected_spam INTEGER DEFAULT 0,
+    spam_reasons TEXT DEFAULT '',
+    paid_amount INTEGER DEFAULT 0, -- net of refunds, kept in step with payments
+    payment_status TEXT DEFAULT 'unpaid',
+    assigned_to TEXT, -- admin_users.username of the staff member in charge
+    selected_options TEXT DEFAULT '[]', -- legacy single-product orders, now kept per line in order_items
+    created_at TEXT NOT NULL,
+    updated_at TEXT,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE SET NULL,
+    FOREIGN KEY (customer_id) REFERENCES customers(id) ON DELETE SET NULL
+);
+
+-- Full-text index of what staff search orders by, keyed by orders.rowid.
+-- unicode61 strips accents but not the stroke of "đ", so that is folded here;
+-- phones are also indexed in national form (0912...) and order numbers
+-- without dashes. The view is the single definition of each document.
+CREATE VIRTUAL TABLE IF NOT EXISTS order_search USING fts5(
+    name, email, phone, number,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIEW IF NOT EXISTS order_search_source AS
+SELECT rowid AS order_rowid,
+    replace(replace(customer_name, 'đ', 'd'), 'Đ', 'D') AS name,
+    COALESCE(customer_email, '') AS email,
+    customer_phone || CASE WHEN customer_phone LIKE '+84%' THEN ' 0' || substr(customer_phone, 4) ELSE '' END AS phone,
+    COALESCE(order_number, '') || ' ' || replace(COALESCE(order_number, ''), '-', '') AS number
+FROM orders;
+
+CREATE TRIGGER IF NOT EXISTS orders_search_insert AFTER INSERT ON orders BEGIN
+    INSERT INTO order_search (rowid, name, email, phone, number)
+    SELECT order_rowid, name, email, phone, number FROM order_search_source WHERE order_rowid = new.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS orders_search_update
+AFTER UPDATE OF customer_name, customer_email, customer_phone, order_number ON orders BEGIN
+    DELETE FROM order_search WHERE rowid = old.rowid;
+    INSERT INTO order_search (rowid, name, email, phone, number)
+    SELECT order_rowid, name, email, phone, number FROM order_search_source WHERE order_rowid = new.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS orders_search_delete AFTER DELETE ON orders BEGIN
+    DELETE FROM order_search WHERE rowid = old.rowid;
+END;
+
+-- Order lines with prices as they were when ordered (VND).
+-- line_total = (unit_price + options_price) * quantity, before discount_amount
+CREATE TABLE IF NOT EXISTS order_items (
+    id TEXT PRIMARY KEY,
+    order_id TEXT NOT NULL,
+    product_id TEXT,
+    product_name TEXT NOT NULL,
+    variant TEXT DEFAULT '',
+    quantity INTEGER NOT NULL DEFAULT 1,
+    unit_price INTEGER NOT NULL DEFAULT 0,
+    options_price INTEGER NOT NULL DEFAULT 0,
+    line_total INTEGER NOT NULL DEFAULT 0,
+    discount_amount INTEGER NOT NULL DEFAULT 0,
+    selected_options TEXT DEFAULT '[]',
+    measurements TEXT DEFAULT '',
+    wearer_id TEXT,
+    sort_order INTEGER DEFAULT 0,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE SET NULL,
+    FOREIGN KEY (wearer_id) REFERENCES order_wearers(id) ON DELETE SET NULL
+);
+
+-- People a group order (wedding party, family) is sewn for, each with their own fitting progress
+CREATE TABLE IF NOT EXISTS order_wearers (
+    id TEXT PRIMARY KEY,
+    order_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    role TEXT DEFAULT '',
+    measurements TEXT DEFAULT '',
+    fitting_status TEXT DEFAULT 'pending',
+    notes TEXT DEFAULT '',
+    sort_order INTEGER DEFAULT 0,
+    updated_at TEXT,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE
+);
+
+-- Every status change of an order, with who made it
+CREATE TABLE IF NOT EXISTS order_status_history (
+    id TEXT PRIMARY KEY,
+    order_id TEXT NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    note TEXT DEFAULT '',
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE
+);
+
+-- Money received for an order; refunds are negative amounts
+CREATE TABLE IF NOT EXISTS payments (
+    id TEXT PRIMARY KEY,
+    order_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    method TEXT NOT NULL, -- cash, bank_transfer or e_wallet
+    reference TEXT DEFAULT '', -- bank or wallet transaction id
+    received_by TEXT NOT NULL,
+    note TEXT DEFAULT '',
+    paid_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES orders(id)
+);
+
+-- Bank statement files uploaded for reconciliation
+CREATE TABLE IF NOT EXISTS bank_imports (
+    id TEXT PRIMARY KEY,
+    filename TEXT DEFAULT '',
+    imported_by TEXT NOT NULL,
+    row_count INTEGER DEFAULT 0,
+    created_at TEXT NOT NULL
+);
+
+-- Incoming transfers read from a statement and the order each was matched to.
+-- status: proposed, unmatched, duplicate, confirmed or ignored
+CREATE TABLE IF NOT EXISTS bank_transactions (
+    id TEXT PRIMARY KEY,
+    import_id TEXT NOT NULL,
+    row_number INTEGER NOT NULL,
+    transaction_date TEXT NOT NULL, -- YYYY-MM-DD
+    amount INTEGER NOT NULL,
+    memo TEXT DEFAULT '',
+    reference TEXT DEFAULT '',
+    fingerprint TEXT NOT NULL, -- same transfer seen in an earlier statement
+    order_id TEXT,
+    confidence INTEGER DEFAULT 0, -- 0-100
+    match_reason TEXT DEFAULT '',
+    expected_amount INTEGER,
+    amount_status TEXT DEFAULT '', -- exact, deposit, underpaid or overpaid
+    status TEXT NOT NULL,
+    payment_id TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (import_id) REFERENCES bank_imports(id) ON DELETE CASCADE,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE SET NULL
+);
+
+-- Tailors and other workshop staff production stages are assigned to
+CREATE TABLE IF NOT EXISTS tailors (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    phone TEXT DEFAULT '',
+    stages TEXT NOT NULL DEFAULT '[]', -- ["cutting", "sewing", "finishing"]; empty means any stage
+    hours_per_day REAL NOT NULL DEFAULT 8,
+    active INTEGER DEFAULT 1,
+    notes TEXT DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- A production stage (cutting, sewing, finishing) of an order, or of one of
+-- its items, given to a tailor. status: todo, in_progress, done
+CREATE TABLE IF NOT EXISTS work_assignments (
+    id TEXT PRIMARY KEY,
+    order_id TEXT NOT NULL,
+    order_item_id TEXT, -- NULL covers every item of the order
+    stage TEXT NOT NULL,
+    tailor_id TEXT NOT NULL,
+    estimated_hours REAL NOT NULL DEFAULT 0,
+    due_date TEXT,
+    status TEXT NOT NULL DEFAULT 'todo',
+    started_at TEXT,
+    completed_at TEXT,
+    notes TEXT DEFAULT '',
+    assigned_by TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT,
-    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE SET NULL
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE,
+    FOREIGN KEY (order_item_id) REFERENCES order_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (tailor_id) REFERENCES tailors(id)
+);
+
+-- Discount codes (discount_type: 'percent' | 'fixed')
+CREATE TABLE IF NOT EXISTS coupons (
+    id TEXT PRIMARY KEY,
+    code TEXT UNIQUE NOT NULL,
+    description TEXT DEFAULT '',
+    discount_type TEXT NOT NULL,
+    discount_value INTEGER NOT NULL,
+    max_discount INTEGER,
+    min_order_value INTEGER DEFAULT 0,
+    product_ids TEXT DEFAULT '[]',
+    categories TEXT DEFAULT '[]',
+    usage_limit INTEGER,
+    per_phone_limit INTEGER,
+    starts_at TEXT,
+    ends_at TEXT,
+    active INTEGER DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- One row per order that used a coupon
+CREATE TABLE IF NOT EXISTS coupon_redemptions (
+    id TEXT PRIMARY KEY,
+    coupon_id TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    customer_phone TEXT NOT NULL,
+    discount_amount INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (coupon_id) REFERENCES coupons(id) ON DELETE CASCADE,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE
+);
+
+-- Storefront orders recently accepted, for rate limits and duplicate detection
+CREATE TABLE IF NOT EXISTS order_submissions (
+    id TEXT PRIMARY KEY,
+    order_id TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    phone TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    token_nonce TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- Order lookups by phone and order number that matched nothing, to throttle guessing
+CREATE TABLE IF NOT EXISTS tracking_lookup_misses (
+    ip TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Showrooms where measuring sessions and fittings take place
+CREATE TABLE IF NOT EXISTS showrooms (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    address TEXT DEFAULT '',
+    phone TEXT DEFAULT '',
+    working_hours TEXT NOT NULL DEFAULT '{}', -- {"mon": ["09:00-12:00", "13:30-18:00"], ...}
+    slot_minutes INTEGER NOT NULL DEFAULT 30,
+    capacity INTEGER NOT NULL DEFAULT 1, -- appointments that can share a slot
+    active INTEGER DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- Days a showroom is closed; a NULL showroom_id closes every showroom
+CREATE TABLE IF NOT EXISTS showroom_holidays (
+    id TEXT PRIMARY KEY,
+    showroom_id TEXT,
+    date TEXT NOT NULL,
+    name TEXT DEFAULT '',
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS appointments (
+    id TEXT PRIMARY KEY,
+    showroom_id TEXT NOT NULL REFERENCES showrooms(id),
+    order_id TEXT,
+    customer_id TEXT,
+    customer_name TEXT NOT NULL,
+    customer_phone TEXT NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'measuring',
+    starts_at TEXT NOT NULL, -- shop time, RFC 3339 with +07:00
+    ends_at TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'booked',
+    notes TEXT DEFAULT '',
+    manage_code TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- Rentals of a product variant; one row holds one piece from start_date to blocked_until
+CREATE TABLE IF NOT EXISTS rentals (
+    id TEXT PRIMARY KEY,
+    product_id TEXT NOT NULL REFERENCES products(id),
+    product_name TEXT NOT NULL,
+    variant TEXT NOT NULL DEFAULT '',
+    order_id TEXT,
+    customer_id TEXT,
+    customer_name TEXT NOT NULL,
+    customer_phone TEXT NOT NULL,
+    start_date TEXT NOT NULL, -- pickup day, YYYY-MM-DD
+    end_date TEXT NOT NULL, -- last day with the customer
+    price_per_day INTEGER NOT NULL,
+    rental_total INTEGER NOT NULL,
+    deposit INTEGER NOT NULL DEFAULT 0,
+    buffer_days INTEGER NOT NULL DEFAULT 0,
+    blocked_until TEXT NOT NULL, -- last day the piece is unavailable, cleaning included
+    status TEXT NOT NULL DEFAULT 'reserved',
+    picked_up_at TEXT,
+    returned_at TEXT,
+    returned_on TEXT,
+    late_fee INTEGER DEFAULT 0,
+    damage_notes TEXT DEFAULT '',
+    damage_fee INTEGER DEFAULT 0,
+    deposit_refund INTEGER,
+    notes TEXT DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT
+);
+
+-- Responses to order submissions, replayed when a client retries with the same Idempotency-Key
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key TEXT PRIMARY KEY,
+    request_hash TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    response TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Last order number handed out per shop-local year
+CREATE TABLE IF NOT EXISTS order_sequences (
+    year INTEGER PRIMARY KEY,
+    last_value INTEGER NOT NULL
+);
+
+-- One-off data migrations already applied (see db::run_once)
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    name TEXT PRIMARY KEY,
+    applied_at TEXT NOT NULL
 );
 
 -- Settings table
@@ -80,8 +489,21 @@ INSERT OR IGNORE INTO settings (key, value, type, updated_at) VALUES
     ('address', 'Hà Nội, Việt Nam', 'string', datetime('now')),
     ('categories', '["ao_dai_ngu_than","ao_dai_4_ta","ao_dai_2_ta","phap_phuc_linen"]', 'json', datetime('now')),
     ('size_chart', '{"S":{"weight":"dưới 48kg","bust":"84-66-90","ao_dai":"134","tay":"68","quan":"102"},"M":{"weight":"dưới 53kg","bust":"90-70-94","ao_dai":"137","tay":"69","quan":"104"},"L":{"weight":"dưới 58kg","bust":"92-74-98","ao_dai":"140","tay":"70","quan":"106"},"XL":{"weight":"dưới 72kg","bust":"92-112","ao_dai":"140","tay":"70","quan":"106"}}', 'json', datetime('now')),
+    ('order_number_prefix', 'HLV', 'string', datetime('now')),
+    ('require_form_token', 'false', 'string', datetime('now')),
+    ('bank_bin', '', 'string', datetime('now')),
+    ('bank_account_number', '', 'string', datetime('now')),
+    ('bank_account_name', '', 'string', datetime('now')),
+    ('deposit_percent', '50', 'string', datetime('now')),
+    ('admin_url', 'https://admin.hylacviet.vn', 'string', datetime('now')),
     ('materials', '["Linen cao cấp 100% sợi lanh","Gấm cao cấp","Lụa cao cấp"]', 'json', datetime('now'));
 
+-- Default showroom: Mon-Sat 9:00-18:00 with a lunch break
+INSERT OR IGNORE INTO showrooms (id, name, address, working_hours, slot_minutes, capacity, created_at) VALUES
+    ('showroom-001', 'Hỷ Lạc Việt', 'Hà Nội, Việt Nam',
+     '{"mon":["09:00-12:00","13:30-18:00"],"tue":["09:00-12:00","13:30-18:00"],"wed":["09:00-12:00","13:30-18:00"],"thu":["09:00-12:00","13:30-18:00"],"fri":["09:00-12:00","13:30-18:00"],"sat":["09:00-12:00","13:30-18:00"]}',
+     30, 2, datetime('now'));
+
 -- Create default admin (password: admin123 - CHANGE IN PRODUCTION!)
 -- Password hash is SHA256 of 'admin123'
 INSERT OR IGNORE INTO admin_users (id, username, password_hash, role, created_at) VALUES
@@ -92,3 +514,40 @@ CREATE INDEX IF NOT EXISTS idx_products_category ON products(category);
 CREATE INDEX IF NOT EXISTS idx_products_status ON products(status);
 CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(status);
 CREATE INDEX IF NOT EXISTS idx_orders_created ON orders(created_at);
+CREATE INDEX IF NOT EXISTS idx_orders_product ON orders(product_id);
+CREATE INDEX IF NOT EXISTS idx_orders_phone ON orders(customer_phone);
+CREATE INDEX IF NOT EXISTS idx_orders_customer ON orders(customer_id);
+CREATE INDEX IF NOT EXISTS idx_orders_payment_status ON orders(payment_status);
+CREATE INDEX IF NOT EXISTS idx_orders_spam_created ON orders(suspected_spam, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_orders_delivery ON orders(delivery_date);
+CREATE INDEX IF NOT EXISTS idx_orders_total ON orders(total);
+CREATE INDEX IF NOT EXISTS idx_orders_name_folded ON orders(customer_name_folded); -- sorting by name
+CREATE INDEX IF NOT EXISTS idx_orders_assigned ON orders(assigned_to);
+CREATE INDEX IF NOT EXISTS idx_payments_order ON payments(order_id, paid_at);
+CREATE INDEX IF NOT EXISTS idx_bank_transactions_import ON bank_transactions(import_id, row_number);
+CREATE INDEX IF NOT EXISTS idx_bank_transactions_fingerprint ON bank_transactions(fingerprint);
+CREATE INDEX IF NOT EXISTS idx_work_assignments_order ON work_assignments(order_id, stage);
+CREATE INDEX IF NOT EXISTS idx_work_assignments_tailor ON work_assignments(tailor_id, status);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_tracking_code ON orders(tracking_code);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_order_number ON orders(order_number);
+CREATE INDEX IF NOT EXISTS idx_appointments_showroom ON appointments(showroom_id, starts_at);
+CREATE INDEX IF NOT EXISTS idx_appointments_order ON appointments(order_id);
+CREATE INDEX IF NOT EXISTS idx_appointments_phone ON appointments(customer_phone);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_appointments_manage_code ON appointments(manage_code);
+CREATE INDEX IF NOT EXISTS idx_showroom_holidays_date ON showroom_holidays(date);
+CREATE INDEX IF NOT EXISTS idx_rentals_product ON rentals(product_id, variant, start_date);
+CREATE INDEX IF NOT EXISTS idx_rentals_phone ON rentals(customer_phone);
+CREATE INDEX IF NOT EXISTS idx_order_submissions_ip ON order_submissions(ip, created_at);
+CREATE INDEX IF NOT EXISTS idx_order_submissions_phone ON order_submissions(phone, created_at);
+CREATE INDEX IF NOT EXISTS idx_order_submissions_token ON order_submissions(token_nonce);
+CREATE INDEX IF NOT EXISTS idx_tracking_lookup_misses_ip ON tracking_lookup_misses(ip, created_at);
+CREATE INDEX IF NOT EXISTS idx_customer_measurements_customer ON customer_measurements(customer_id);
+CREATE INDEX IF NOT EXISTS idx_order_items_order ON order_items(order_id);
+CREATE INDEX IF NOT EXISTS idx_order_items_product ON order_items(product_id);
+CREATE INDEX IF NOT EXISTS idx_order_wearers_order ON order_wearers(order_id);
+CREATE INDEX IF NOT EXISTS idx_option_groups_product ON product_option_groups(product_id);
+CREATE INDEX IF NOT EXISTS idx_option_choices_group ON product_option_choices(group_id);
+CREATE INDEX IF NOT EXISTS idx_bundle_items_component ON bundle_items(component_id);
+CREATE INDEX IF NOT EXISTS idx_product_pairings_paired ON product_pairings(paired_product_id);
+CREATE INDEX IF NOT EXISTS idx_order_status_history_order ON order_status_history(order_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_coupon_redemptions_coupon ON coupon_redemptions(coupon_id, customer_phone);
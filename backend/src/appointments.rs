@@ -0,0 +1,288 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+
+use crate::models::{Appointment, DaySlots, Showroom, Slot};
+use crate::pricing::shop_offset;
+use crate::text;
+
+/// Kinds of visit, with their Vietnamese labels
+pub const KINDS: [(&str, &str); 3] = [
+    ("measuring", "Lấy số đo"),
+    ("fitting", "Thử đồ"),
+    ("pickup", "Nhận đồ"),
+];
+
+pub const STATUSES: [&str; 4] = ["booked", "completed", "cancelled", "no_show"];
+
+/// How far ahead customers may book
+pub const MAX_DAYS_AHEAD: i64 = 90;
+/// Upcoming appointments one phone number may hold at a time on the storefront
+pub const MAX_UPCOMING_PER_PHONE: i64 = 3;
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Mon-Sat 9:00-18:00 with a lunch break, closed on Sunday
+pub const DEFAULT_WORKING_HOURS: &str =
+    r#"{"mon":["09:00-12:00","13:30-18:00"],"tue":["09:00-12:00","13:30-18:00"],"wed":["09:00-12:00","13:30-18:00"],"thu":["09:00-12:00","13:30-18:00"],"fri":["09:00-12:00","13:30-18:00"],"sat":["09:00-12:00","13:30-18:00"]}"#;
+
+pub const APPOINTMENT_SELECT: &str = "SELECT a.id, a.showroom_id, s.name, a.order_id, o.order_number, a.customer_id,
+        a.customer_name, a.customer_phone, a.kind, a.starts_at, a.ends_at, a.status, a.notes, a.manage_code,
+        a.created_at, a.updated_at
+     FROM appointments a
+     JOIN showrooms s ON s.id = a.showroom_id
+     LEFT JOIN orders o ON o.id = a.order_id";
+
+pub fn appointment_from_row(row: &rusqlite::Row) -> rusqlite::Result<Appointment> {
+    let kind: String = row.get(8)?;
+    Ok(Appointment {
+        id: row.get(0)?,
+        showroom_id: row.get(1)?,
+        showroom_name: row.get(2)?,
+        order_id: row.get(3)?,
+        order_number: row.get(4)?,
+        customer_id: row.get(5)?,
+        customer_name: row.get(6)?,
+        customer_phone: row.get(7)?,
+        kind_label: kind_label(&kind),
+        kind,
+        starts_at: row.get(9)?,
+        ends_at: row.get(10)?,
+        status: row.get(11)?,
+        notes: row.get::<_, Option<String>>(12)?.unwrap_or_default(),
+        manage_code: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+pub fn kind_label(kind: &str) -> String {
+    text::label(&KINDS, kind)
+}
+
+pub fn is_valid_kind(kind: &str) -> bool {
+    KINDS.iter().any(|(k, _)| *k == kind)
+}
+
+/// Opening ranges per weekday, Monday first
+pub type WeeklyHours = [Vec<(NaiveTime, NaiveTime)>; 7];
+
+/// Parse working hours like `{"mon": ["09:00-12:00", "13:30-18:00"], ...}`.
+/// Missing days are closed.
+pub fn parse_hours(days: &BTreeMap<String, Vec<String>>) -> Result<WeeklyHours, String> {
+    let mut hours: WeeklyHours = Default::default();
+    for (day, ranges) in days {
+        let index = WEEKDAYS
+            .iter()
+            .position(|d| *d == day.to_lowercase())
+            .ok_or_else(|| format!("Unknown weekday in working_hours: {} (use mon..sun)", day))?;
+        for range in ranges {
+            let (open, close) = range
+                .split_once('-')
+                .and_then(|(a, b)| {
+                    Some((
+                        NaiveTime::parse_from_str(a.trim(), "%H:%M").ok()?,
+                        NaiveTime::parse_from_str(b.trim(), "%H:%M").ok()?,
+                    ))
+                })
+                .ok_or_else(|| format!("Invalid opening hours for {}: {} (use HH:MM-HH:MM)", day, range))?;
+            if open >= close {
+                return Err(format!("Opening hours for {} must close after they open: {}", day, range));
+            }
+            hours[index].push((open, close));
+        }
+        hours[index].sort();
+        if hours[index].windows(2).any(|w| w[0].1 > w[1].0) {
+            return Err(format!("Opening hours for {} overlap", day));
+        }
+    }
+    Ok(hours)
+}
+
+/// Parse a requested start: shop-local "YYYY-MM-DDTHH:MM" or any RFC 3339 timestamp
+pub fn parse_start(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&shop_offset()));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .ok()
+        .and_then(|dt| dt.and_local_timezone(shop_offset()).single())
+        .ok_or_else(|| "starts_at must be YYYY-MM-DDTHH:MM (shop time) or RFC 3339".to_string())
+}
+
+/// Stored form: RFC 3339 in shop time, so plain string comparison orders them
+pub fn format_time(dt: DateTime<FixedOffset>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
+pub fn load_showroom(conn: &Connection, id: &str) -> rusqlite::Result<Showroom> {
+    conn.query_row(
+        "SELECT id, name, address, phone, working_hours, slot_minutes, capacity, active, created_at, updated_at
+         FROM showrooms WHERE id = ?1",
+        params![id],
+        showroom_from_row,
+    )
+}
+
+pub fn showroom_from_row(row: &rusqlite::Row) -> rusqlite::Result<Showroom> {
+    let working_hours: String = row.get(4)?;
+    Ok(Showroom {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        address: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+        phone: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+        working_hours: serde_json::from_str(&working_hours).unwrap_or_default(),
+        slot_minutes: row.get(5)?,
+        capacity: row.get(6)?,
+        active: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// Why the showroom is closed on `date`, if it is
+fn closed_reason(conn: &Connection, showroom: &Showroom, hours: &WeeklyHours, date: NaiveDate) -> Option<String> {
+    let holiday: Option<String> = conn
+        .query_row(
+            "SELECT COALESCE(NULLIF(name, ''), 'Nghỉ lễ') FROM showroom_holidays
+             WHERE date = ?1 AND (showroom_id IS NULL OR showroom_id = ?2) LIMIT 1",
+            params![date.format("%Y-%m-%d").to_string(), showroom.id],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap_or(None);
+    holiday.or_else(|| {
+        hours[date.weekday().num_days_from_monday() as usize]
+            .is_empty()
+            .then(|| "Đóng cửa".to_string())
+    })
+}
+
+/// Slot start times of a day, in shop time
+fn slot_starts(showroom: &Showroom, hours: &WeeklyHours, date: NaiveDate) -> Vec<DateTime<FixedOffset>> {
+    let length = Duration::minutes(showroom.slot_minutes);
+    let mut starts = vec![];
+    for (open, close) in &hours[date.weekday().num_days_from_monday() as usize] {
+        let mut start = *open;
+        loop {
+            let (end, wrapped) = start.overflowing_add_signed(length);
+            if wrapped != 0 || end > *close {
+                break;
+            }
+            if let Some(dt) = date.and_time(start).and_local_timezone(shop_offset()).single() {
+                starts.push(dt);
+            }
+            start = end;
+        }
+    }
+    starts
+}
+
+/// Booked appointments overlapping `[start, end)` at a showroom, not counting `except`
+fn booked_between(conn: &Connection, showroom_id: &str, start: &str, end: &str, except: Option<&str>) -> i64 {
+    conn.query_row(
+        "SELECT COUNT(*) FROM appointments
+         WHERE showroom_id = ?1 AND status = 'booked' AND starts_at < ?3 AND ends_at > ?2
+           AND (?4 IS NULL OR id != ?4)",
+        params![showroom_id, start, end, except],
+        |row| row.get(0),
+    )
+    .unwrap_or(0)
+}
+
+/// Open slots for `days` days from `from`, with how many bookings each can still take
+pub fn availability(conn: &Connection, showroom: &Showroom, from: NaiveDate, days: i64, now: DateTime<Utc>) -> Result<Vec<DaySlots>, String> {
+    let hours = parse_hours(&showroom.working_hours)?;
+    let now = now.with_timezone(&shop_offset());
+    let last = now.date_naive() + Duration::days(MAX_DAYS_AHEAD);
+
+    let mut result = vec![];
+    for date in from.iter_days().take(days as usize) {
+        let closed = if date > last { Some("Chưa mở lịch".to_string()) } else { closed_reason(conn, showroom, &hours, date) };
+        let slots = match closed {
+            Some(_) => vec![],
+            None => slot_starts(showroom, &hours, date)
+                .into_iter()
+                .filter(|start| *start > now)
+                .map(|start| {
+                    let end = start + Duration::minutes(showroom.slot_minutes);
+                    let (start, end) = (format_time(start), format_time(end));
+                    let remaining = (showroom.capacity - booked_between(conn, &showroom.id, &start, &end, None)).max(0);
+                    Slot { starts_at: start, ends_at: end, remaining }
+                })
+                .collect(),
+        };
+        result.push(DaySlots {
+            date: date.format("%Y-%m-%d").to_string(),
+            closed_reason: closed,
+            slots,
+        });
+    }
+    Ok(result)
+}
+
+pub enum SlotError {
+    /// Not a time the showroom takes appointments
+    Unavailable(String),
+    /// A real slot, but every place in it is taken
+    Full,
+}
+
+/// Check that `start` is a bookable slot with room left and return it with
+/// its end, as stored. Run inside the transaction that writes the
+/// appointment so two customers can't take the last place at once;
+/// `except` is the appointment being moved.
+pub fn reserve(
+    conn: &Connection,
+    showroom: &Showroom,
+    start: DateTime<FixedOffset>,
+    except: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<(String, String), SlotError> {
+    if !showroom.active {
+        return Err(SlotError::Unavailable("This showroom is not taking appointments".to_string()));
+    }
+    let hours = parse_hours(&showroom.working_hours).map_err(SlotError::Unavailable)?;
+    let now = now.with_timezone(&shop_offset());
+    if start <= now {
+        return Err(SlotError::Unavailable("Appointments must be in the future".to_string()));
+    }
+    if start.date_naive() > now.date_naive() + Duration::days(MAX_DAYS_AHEAD) {
+        return Err(SlotError::Unavailable(format!("Appointments can be booked up to {} days ahead", MAX_DAYS_AHEAD)));
+    }
+    if let Some(reason) = closed_reason(conn, showroom, &hours, start.date_naive()) {
+        return Err(SlotError::Unavailable(format!("The showroom is closed that day ({})", reason)));
+    }
+    if !slot_starts(showroom, &hours, start.date_naive()).contains(&start) {
+        return Err(SlotError::Unavailable("That time is not one of the showroom's appointment slots".to_string()));
+    }
+
+    let end = start + Duration::minutes(showroom.slot_minutes);
+    let (start, end) = (format_time(start), format_time(end));
+    if booked_between(conn, &showroom.id, &start, &end, except) >= showroom.capacity {
+        return Err(SlotError::Full);
+    }
+    Ok((start, end))
+}
+
+/// Keep an order's expected fitting date on its earliest booked fitting.
+/// Without one, a date that came from a cancelled fitting is cleared, while
+/// a date staff set by hand is kept.
+pub fn sync_fitting_date(conn: &Connection, order_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE orders SET fitting_date = COALESCE(
+            (SELECT substr(MIN(starts_at), 1, 10) FROM appointments
+             WHERE order_id = ?1 AND kind = 'fitting' AND status = 'booked'),
+            CASE WHEN fitting_date IN (
+                SELECT substr(starts_at, 1, 10) FROM appointments WHERE order_id = ?1 AND kind = 'fitting')
+            THEN NULL ELSE fitting_date END)
+         WHERE id = ?1",
+        params![order_id],
+    )?;
+    Ok(())
+}
+
+pub fn load_appointment(conn: &Connection, id: &str) -> rusqlite::Result<Appointment> {
+    conn.query_row(&format!("{} WHERE a.id = ?1", APPOINTMENT_SELECT), params![id], appointment_from_row)
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::models::{BundleComponent, BundleItemInput, Product};
+use crate::pricing;
+use crate::products::{product_from_row, PRODUCT_COLUMNS};
+
+/// Stored bundle contents, in display order
+pub fn load_items(conn: &Connection, bundle_id: &str) -> Vec<BundleItemInput> {
+    let mut stmt = match conn.prepare(
+        "SELECT component_id, variant, quantity FROM bundle_items WHERE bundle_id = ?1 ORDER BY sort_order ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+
+    stmt.query_map(params![bundle_id], |row| {
+        Ok(BundleItemInput {
+            product_id: row.get(0)?,
+            variant: row.get(1)?,
+            quantity: row.get(2)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// Check a proposed component list before it is saved on `bundle_id`
+pub fn validate_items(conn: &Connection, bundle_id: &str, items: &[BundleItemInput]) -> Result<(), String> {
+    if items.is_empty() {
+        return Err("A bundle needs at least one component".to_string());
+    }
+    for item in items {
+        if item.product_id == bundle_id {
+            return Err("A bundle cannot contain itself".to_string());
+        }
+        if item.quantity < 1 {
+            return Err("Component quantity must be at least 1".to_string());
+        }
+        let product_type: String = conn
+            .query_row("SELECT product_type FROM products WHERE id = ?1", params![item.product_id], |row| row.get(0))
+            .map_err(|_| format!("Product not found: {}", item.product_id))?;
+        if product_type == "bundle" {
+            return Err("Bundles cannot contain other bundles".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Replace the stored components of a bundle (caller handles the transaction)
+pub fn save_items(conn: &Connection, bundle_id: &str, items: &[BundleItemInput]) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM bundle_items WHERE bundle_id = ?1", params![bundle_id])?;
+    for (position, item) in items.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO bundle_items (bundle_id, component_id, variant, quantity, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(bundle_id, component_id, variant) DO UPDATE SET quantity = quantity + excluded.quantity",
+            params![bundle_id, item.product_id, item.variant.trim(), item.quantity, position as i64],
+        )?;
+    }
+    Ok(())
+}
+
+/// Derive price, compare-at price and stock for every bundle in `products`.
+///
+/// With `bundle_discount_percent` set, the bundle costs the components'
+/// combined list price minus that percentage; otherwise its own `price`
+/// stands. The combined list price becomes the compare-at price unless one
+/// was set explicitly. Stock is the number of complete sets the tracked
+/// components allow. Run this before `pricing::apply_pricing`.
+pub fn resolve_bundles(conn: &Connection, products: &mut [Product], expand: bool) {
+    if !products.iter().any(|p| p.product_type == "bundle") {
+        return;
+    }
+
+    let now = Utc::now();
+    let promotions = pricing::load_active_promotions(conn, now);
+    let mut cache: HashMap<String, Option<Product>> = HashMap::new();
+
+    for bundle in products.iter_mut().filter(|p| p.product_type == "bundle") {
+        let mut components = vec![];
+        for item in load_items(conn, &bundle.id) {
+            let component = cache
+                .entry(item.product_id.clone())
+                .or_insert_with(|| {
+                    let mut product = conn
+                        .query_row(
+                            &format!("SELECT {} FROM products WHERE id = ?1", PRODUCT_COLUMNS),
+                            params![item.product_id],
+                            product_from_row,
+                        )
+                        .ok()?;
+                    pricing::apply_pricing(&mut product, &promotions, now);
+                    Some(product)
+                })
+                .clone();
+            if let Some(component) = component {
+                components.push(BundleComponent {
+                    product_id: component.id,
+                    name: component.name,
+                    image: component.images.first().cloned(),
+                    category: component.category,
+                    variant: item.variant,
+                    quantity: item.quantity,
+                    price: component.price,
+                    effective_price: component.effective_price,
+                    stock: component.stock,
+                });
+            }
+        }
+
+        let list_total: i64 = components.iter().map(|c| c.price * c.quantity).sum();
+        if let Some(percent) = bundle.bundle_discount_percent.filter(|p| *p > 0) {
+            bundle.price = pricing::discounted_price(list_total, percent);
+        }
+        if bundle.compare_at_price.is_none() && list_total > bundle.price {
+            bundle.compare_at_price = Some(list_total);
+        }
+        bundle.effective_price = bundle.price;
+
+        bundle.stock = components
+            .iter()
+            .filter_map(|c| c.stock.map(|stock| stock.max(0) / c.quantity.max(1)))
+            .min();
+        bundle.in_stock = bundle.stock.is_none_or(|s| s > 0) && !components.is_empty();
+
+        if expand {
+            bundle.components = Some(components);
+        }
+    }
+}
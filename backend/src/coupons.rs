@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::parse_json_array;
+use crate::models::Coupon;
+use crate::pricing;
+
+pub const DISCOUNT_TYPES: [&str; 2] = ["percent", "fixed"];
+
+/// One priced line of the order a coupon is evaluated against
+#[derive(Debug, Clone)]
+pub struct CouponLine {
+    pub product_id: Option<String>,
+    pub category: Option<String>,
+    pub amount: i64,
+}
+
+/// Outcome of applying a coupon to a set of lines
+#[derive(Debug, Clone)]
+pub struct CouponQuote {
+    pub coupon: Coupon,
+    pub discount_amount: i64,
+    /// `discount_amount` split across the input lines, in the same order
+    pub line_discounts: Vec<i64>,
+}
+
+/// Codes are matched case-insensitively and stored upper-case
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+pub fn coupon_from_row(row: &rusqlite::Row) -> rusqlite::Result<Coupon> {
+    Ok(Coupon {
+        id: row.get(0)?,
+        code: row.get(1)?,
+        description: row.get(2)?,
+        discount_type: row.get(3)?,
+        discount_value: row.get(4)?,
+        max_discount: row.get(5)?,
+        min_order_value: row.get(6)?,
+        product_ids: parse_json_array(&row.get::<_, String>(7)?),
+        categories: parse_json_array(&row.get::<_, String>(8)?),
+        usage_limit: row.get(9)?,
+        per_phone_limit: row.get(10)?,
+        starts_at: row.get(11)?,
+        ends_at: row.get(12)?,
+        active: row.get(13)?,
+        times_used: row.get(14)?,
+        created_at: row.get(15)?,
+        updated_at: row.get(16)?,
+    })
+}
+
+pub const COUPON_SELECT: &str = "SELECT c.id, c.code, c.description, c.discount_type, c.discount_value, c.max_discount,
+        c.min_order_value, c.product_ids, c.categories, c.usage_limit, c.per_phone_limit, c.starts_at, c.ends_at,
+        c.active, (SELECT COUNT(*) FROM coupon_redemptions r WHERE r.coupon_id = c.id), c.created_at, c.updated_at
+     FROM coupons c";
+
+/// Look up `code` and work out the discount it gives on `lines`.
+///
+/// The minimum order value is checked against the whole order, while the
+/// discount itself only applies to lines matching the product/category
+/// restrictions. Errors are messages suitable for the storefront.
+pub fn evaluate(
+    conn: &Connection,
+    code: &str,
+    customer_phone: &str,
+    lines: &[CouponLine],
+    now: DateTime<Utc>,
+) -> Result<CouponQuote, String> {
+    let coupon = conn
+        .query_row(
+            &format!("{} WHERE c.code = ?1", COUPON_SELECT),
+            params![normalize_code(code)],
+            coupon_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .filter(|c| c.active)
+        .ok_or_else(|| "Invalid discount code".to_string())?;
+
+    if !pricing::is_within_window(coupon.starts_at.as_deref(), coupon.ends_at.as_deref(), now) {
+        return Err("Discount code is not valid at this time".to_string());
+    }
+
+    if let Some(limit) = coupon.usage_limit {
+        if coupon.times_used >= limit {
+            return Err("Discount code has reached its usage limit".to_string());
+        }
+    }
+
+    if let Some(limit) = coupon.per_phone_limit {
+        let used: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM coupon_redemptions WHERE coupon_id = ?1 AND customer_phone = ?2",
+                params![coupon.id, customer_phone.trim()],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if used >= limit {
+            return Err("Discount code already used by this phone number".to_string());
+        }
+    }
+
+    let subtotal: i64 = lines.iter().map(|l| l.amount).sum();
+    if subtotal < coupon.min_order_value {
+        return Err(format!("Minimum order value for this code is {} VND", coupon.min_order_value));
+    }
+
+    let restricted = !coupon.product_ids.is_empty() || !coupon.categories.is_empty();
+    let applies: Vec<bool> = lines
+        .iter()
+        .map(|line| {
+            !restricted
+                || line.product_id.as_ref().is_some_and(|id| coupon.product_ids.contains(id))
+                || line.category.as_ref().is_some_and(|c| coupon.categories.contains(c))
+        })
+        .collect();
+    let eligible: i64 = lines.iter().zip(&applies).filter(|(_, a)| **a).map(|(l, _)| l.amount).sum();
+    if eligible <= 0 {
+        return Err("Discount code does not apply to these products".to_string());
+    }
+
+    let mut discount = match coupon.discount_type.as_str() {
+        "percent" => eligible * coupon.discount_value.clamp(0, 100) / 100,
+        _ => coupon.discount_value,
+    };
+    if let Some(cap) = coupon.max_discount {
+        discount = discount.min(cap);
+    }
+    let discount_amount = discount.clamp(0, eligible);
+
+    // Split proportionally over eligible lines; rounding leftovers go to the last one
+    let mut line_discounts: Vec<i64> = lines
+        .iter()
+        .zip(&applies)
+        .map(|(line, applies)| if *applies { discount_amount * line.amount / eligible } else { 0 })
+        .collect();
+    let remainder = discount_amount - line_discounts.iter().sum::<i64>();
+    if let Some(last) = lines.iter().zip(&applies).rposition(|(l, a)| *a && l.amount > 0) {
+        line_discounts[last] += remainder;
+    }
+
+    Ok(CouponQuote { coupon, discount_amount, line_discounts })
+}
@@ -0,0 +1,173 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::measurements::Measurements;
+use crate::models::{Customer, MeasurementProfile};
+use crate::phone;
+
+/// Customers with order statistics; cancelled orders don't count
+pub const CUSTOMER_SELECT: &str = "SELECT c.id, c.phone, c.name, c.email, c.notes,
+        (SELECT COUNT(*) FROM orders o WHERE o.customer_id = c.id AND o.status != 'cancelled'),
+        (SELECT COALESCE(SUM(o.total), 0) FROM orders o WHERE o.customer_id = c.id AND o.status != 'cancelled'),
+        (SELECT MAX(o.created_at) FROM orders o WHERE o.customer_id = c.id),
+        c.created_at, c.updated_at
+     FROM customers c";
+
+pub fn customer_from_row(row: &rusqlite::Row) -> rusqlite::Result<Customer> {
+    Ok(Customer {
+        id: row.get(0)?,
+        phone: row.get(1)?,
+        name: row.get(2)?,
+        email: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+        notes: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+        order_count: row.get(5)?,
+        lifetime_value: row.get(6)?,
+        last_order_at: row.get(7)?,
+        orders: None,
+        measurement_profiles: None,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// Find or create the customer for a normalized phone number and return its id.
+///
+/// Details from an order only fill in fields the customer record lacks, so
+/// a storefront submission never overwrites what staff have curated.
+pub fn upsert_by_phone(conn: &Connection, phone: &str, name: &str, email: &str) -> rusqlite::Result<String> {
+    let now = Utc::now().to_rfc3339();
+    conn.query_row(
+        "INSERT INTO customers (id, phone, name, email, created_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(phone) DO UPDATE SET
+            name = CASE WHEN customers.name = '' THEN excluded.name ELSE customers.name END,
+            email = CASE WHEN COALESCE(customers.email, '') = '' THEN excluded.email ELSE customers.email END
+         RETURNING id",
+        params![Uuid::new_v4().to_string(), phone, name.trim(), email.trim(), now],
+        |row| row.get(0),
+    )
+}
+
+/// Link orders without a customer to one, creating customers as needed.
+/// Only `customer_id` is set: the phone stays as it was typed on the order.
+/// Orders whose phone can't be normalized stay unlinked.
+pub fn link_orders(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT id, customer_name, customer_phone, COALESCE(customer_email, '') FROM orders
+         WHERE customer_id IS NULL ORDER BY created_at DESC",
+    )?;
+    let orders: Vec<(String, String, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (order_id, name, raw_phone, email) in orders {
+        let Some(phone) = phone::normalize(&raw_phone) else {
+            continue;
+        };
+        let customer_id = upsert_by_phone(conn, &phone, &name, &email)?;
+        conn.execute("UPDATE orders SET customer_id = ?1 WHERE id = ?2", params![customer_id, order_id])?;
+    }
+    Ok(())
+}
+
+/// Rewrite stored phone numbers to E.164, merging customers that turn out
+/// to share a number (e.g. an old 11-digit number and its 10-digit successor).
+/// Numbers that can't be normalized are left as they are.
+pub fn normalize_stored_phones(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT id, phone FROM customers ORDER BY created_at ASC")?;
+    let stored: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (id, raw_phone) in stored {
+        let Some(phone) = phone::normalize(&raw_phone).filter(|p| *p != raw_phone) else {
+            continue;
+        };
+        let existing: Option<String> = conn
+            .query_row("SELECT id FROM customers WHERE phone = ?1", params![phone], |row| row.get(0))
+            .optional()?;
+
+        match existing {
+            Some(keep) => {
+                conn.execute("UPDATE orders SET customer_id = ?1 WHERE customer_id = ?2", params![keep, id])?;
+                conn.execute(
+                    "UPDATE customer_measurements SET customer_id = ?1 WHERE customer_id = ?2",
+                    params![keep, id],
+                )?;
+                conn.execute(
+                    "UPDATE customers SET
+                        name = CASE WHEN name = '' THEN (SELECT name FROM customers WHERE id = ?2) ELSE name END,
+                        email = CASE WHEN COALESCE(email, '') = '' THEN (SELECT email FROM customers WHERE id = ?2) ELSE email END,
+                        notes = TRIM(COALESCE(notes, '') || char(10) || (SELECT COALESCE(notes, '') FROM customers WHERE id = ?2))
+                     WHERE id = ?1",
+                    params![keep, id],
+                )?;
+                conn.execute("DELETE FROM customers WHERE id = ?1", params![id])?;
+            }
+            None => {
+                conn.execute("UPDATE customers SET phone = ?1 WHERE id = ?2", params![phone, id])?;
+            }
+        }
+    }
+
+    for table in ["orders", "coupon_redemptions"] {
+        let mut stmt = conn.prepare(&format!("SELECT DISTINCT customer_phone FROM {}", table))?;
+        let phones: Vec<String> = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+        for raw_phone in phones {
+            if let Some(phone) = phone::normalize(&raw_phone).filter(|p| *p != raw_phone) {
+                conn.execute(
+                    &format!("UPDATE {} SET customer_phone = ?1 WHERE customer_phone = ?2", table),
+                    params![phone, raw_phone],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn load_profiles(conn: &Connection, customer_id: &str) -> Vec<MeasurementProfile> {
+    let mut stmt = match conn.prepare(
+        "SELECT id, name, measurements, created_at, updated_at FROM customer_measurements
+         WHERE customer_id = ?1 ORDER BY created_at ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+
+    stmt.query_map(params![customer_id], |row| {
+        Ok(MeasurementProfile {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            measurements: Measurements::from_stored(&row.get::<_, String>(2)?),
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// Measurements of a saved profile, only if it belongs to the customer with `phone`
+pub fn profile_measurements(conn: &Connection, phone: &str, profile_id: &str) -> Option<Measurements> {
+    conn.query_row(
+        "SELECT m.measurements FROM customer_measurements m JOIN customers c ON c.id = m.customer_id
+         WHERE m.id = ?1 AND c.phone = ?2",
+        params![profile_id, phone],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .map(|m| Measurements::from_stored(&m))
+}
+
+pub fn save_profile(conn: &Connection, customer_id: &str, name: &str, measurements: &Measurements) -> rusqlite::Result<String> {
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO customer_measurements (id, customer_id, name, measurements, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, customer_id, name.trim(), measurements.to_json(), Utc::now().to_rfc3339()],
+    )?;
+    Ok(id)
+}
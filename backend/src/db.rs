@@ -1,6 +1,10 @@
 use rusqlite::{Connection, Result};
 use std::sync::{Arc, Mutex};
 
+use crate::customers;
+use crate::order_filters;
+use crate::order_numbers;
+
 pub type DbPool = Arc<Mutex<Connection>>;
 
 pub fn init_db() -> Result<DbPool> {
@@ -9,13 +13,183 @@ pub fn init_db() -> Result<DbPool> {
     // Enable WAL mode for better concurrency
     conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
     
+    // Upgrade tables created by older versions before the schema (and its indexes) runs
+    migrate(&conn)?;
+    
     // Run schema
     let schema = include_str!("../schema.sql");
     conn.execute_batch(schema)?;
     
+    // Rewrites of existing data that must only happen once
+    run_once(&conn, "2026_normalize_phones_e164", customers::normalize_stored_phones)?;
+    
+    backfill(&conn)?;
+    
     Ok(Arc::new(Mutex::new(conn)))
 }
 
+/// Add columns introduced after a table was first created.
+/// Fresh databases get them straight from `schema.sql`.
+fn migrate(conn: &Connection) -> Result<()> {
+    // Sale pricing
+    add_column(conn, "products", "compare_at_price", "INTEGER")?;
+    add_column(conn, "products", "sale_price", "INTEGER")?;
+    add_column(conn, "products", "sale_starts_at", "TEXT")?;
+    add_column(conn, "products", "sale_ends_at", "TEXT")?;
+
+    // Bundles and stock
+    add_column(conn, "products", "product_type", "TEXT DEFAULT 'single'")?;
+    add_column(conn, "products", "bundle_discount_percent", "INTEGER")?;
+    add_column(conn, "products", "stock", "INTEGER")?;
+
+    // Rentals
+    add_column(conn, "products", "rental_price_per_day", "INTEGER")?;
+    add_column(conn, "products", "rental_deposit", "INTEGER DEFAULT 0")?;
+    add_column(conn, "products", "rental_buffer_days", "INTEGER DEFAULT 1")?;
+    add_column(conn, "products", "rental_units", "TEXT DEFAULT '{}'")?;
+
+    // Order pricing and discount codes
+    add_column(conn, "orders", "subtotal", "INTEGER DEFAULT 0")?;
+    add_column(conn, "orders", "discount_amount", "INTEGER DEFAULT 0")?;
+    add_column(conn, "orders", "total", "INTEGER DEFAULT 0")?;
+    add_column(conn, "orders", "coupon_code", "TEXT")?;
+
+    // Customization options chosen on the order
+    add_column(conn, "orders", "selected_options", "TEXT DEFAULT '[]'")?;
+
+    // Group orders
+    add_column(conn, "orders", "delivery_date", "TEXT")?;
+    add_column(conn, "order_items", "wearer_id", "TEXT")?;
+
+    // Customer records
+    add_column(conn, "orders", "customer_id", "TEXT")?;
+
+    // Public order tracking
+    add_column(conn, "orders", "fitting_date", "TEXT")?;
+    add_column(conn, "orders", "tracking_code", "TEXT")?;
+
+    // Human-readable order numbers
+    add_column(conn, "orders", "order_number", "TEXT")?;
+
+    // Spam flagging
+    add_column(conn, "orders", "suspected_spam", "INTEGER DEFAULT 0")?;
+    add_column(conn, "orders", "spam_reasons", "TEXT DEFAULT ''")?;
+
+    // Payments
+    add_column(conn, "orders", "paid_amount", "INTEGER DEFAULT 0")?;
+    add_column(conn, "orders", "payment_status", "TEXT DEFAULT 'unpaid'")?;
+
+    // Order search and assignment
+    add_column(conn, "orders", "customer_name_folded", "TEXT")?;
+    add_column(conn, "orders", "assigned_to", "TEXT")?;
+
+    Ok(())
+}
+
+/// Fill rows of tables added after data already existed. Every statement is
+/// idempotent, so this is safe to run on each start.
+fn backfill(conn: &Connection) -> Result<()> {
+    // Orders placed before status history was recorded get their current status as the first entry
+    conn.execute_batch(
+        "INSERT INTO order_status_history (id, order_id, from_status, to_status, actor, note, created_at)
+         SELECT lower(hex(randomblob(16))), o.id, NULL, COALESCE(o.status, 'pending'), 'system', '', o.created_at
+         FROM orders o
+         WHERE NOT EXISTS (SELECT 1 FROM order_status_history h WHERE h.order_id = o.id)",
+    )?;
+
+    // Single-product orders become one line each. Orders priced before
+    // subtotals existed take the product's current list price as their snapshot.
+    conn.execute_batch(
+        "INSERT INTO order_items (id, order_id, product_id, product_name, variant, quantity, unit_price, options_price,
+            line_total, discount_amount, selected_options, measurements, sort_order)
+         SELECT lower(hex(randomblob(16))), o.id, p.id, COALESCE(NULLIF(o.product_name, ''), p.name, ''), '', 1,
+            CASE WHEN COALESCE(o.subtotal, 0) > 0 THEN o.subtotal - opt.total ELSE COALESCE(p.price, 0) END,
+            CASE WHEN COALESCE(o.subtotal, 0) > 0 THEN opt.total ELSE 0 END,
+            CASE WHEN COALESCE(o.subtotal, 0) > 0 THEN o.subtotal ELSE COALESCE(p.price, 0) END,
+            COALESCE(o.discount_amount, 0), COALESCE(o.selected_options, '[]'), '', 0
+         FROM orders o
+         LEFT JOIN products p ON p.id = o.product_id
+         JOIN (SELECT o2.id AS order_id,
+                 (SELECT COALESCE(SUM(json_extract(value, '$.price_delta')), 0)
+                  FROM json_each(CASE WHEN json_valid(o2.selected_options) THEN o2.selected_options ELSE '[]' END)) AS total
+               FROM orders o2) opt ON opt.order_id = o.id
+         WHERE (o.product_id IS NOT NULL OR COALESCE(o.product_name, '') != '')
+           AND NOT EXISTS (SELECT 1 FROM order_items i WHERE i.order_id = o.id);
+
+         UPDATE orders SET
+            subtotal = (SELECT SUM(line_total) FROM order_items i WHERE i.order_id = orders.id),
+            total = (SELECT SUM(line_total) FROM order_items i WHERE i.order_id = orders.id) - COALESCE(discount_amount, 0)
+         WHERE COALESCE(subtotal, 0) = 0
+           AND (SELECT COALESCE(SUM(line_total), 0) FROM order_items i WHERE i.order_id = orders.id) > 0;",
+    )?;
+
+    // Orders placed before tracking existed get a code too
+    conn.execute_batch(
+        "UPDATE orders SET tracking_code = lower(hex(randomblob(16))) WHERE tracking_code IS NULL",
+    )?;
+
+    // Orders placed before order numbers existed are numbered in placement order
+    order_numbers::assign_missing(conn)?;
+
+    // Orders placed before customer records existed are linked by phone number
+    customers::link_orders(conn)?;
+
+    // Orders placed before sorting by folded name get theirs, and list
+    // filters compare these columns without COALESCE
+    order_filters::fold_missing_names(conn)?;
+    conn.execute_batch(
+        "UPDATE orders SET suspected_spam = 0 WHERE suspected_spam IS NULL;
+         UPDATE orders SET paid_amount = 0 WHERE paid_amount IS NULL;
+         UPDATE orders SET payment_status = 'unpaid' WHERE payment_status IS NULL;
+         -- Corrections were once taken for refunds while money was still held
+         UPDATE orders SET payment_status = CASE WHEN paid_amount >= total THEN 'paid' ELSE 'deposit' END
+         WHERE payment_status = 'refunded' AND paid_amount > 0;",
+    )?;
+
+    // The search index is keyed by rowid, which VACUUM may renumber on a
+    // table without an INTEGER PRIMARY KEY, so it is rebuilt from scratch
+    conn.execute_batch(
+        "DELETE FROM order_search;
+         INSERT INTO order_search (rowid, name, email, phone, number)
+         SELECT order_rowid, name, email, phone, number FROM order_search_source;",
+    )?;
+
+    Ok(())
+}
+
+/// Apply a one-off data migration unless `schema_migrations` says it already ran
+fn run_once(conn: &Connection, name: &str, migration: fn(&Connection) -> Result<()>) -> Result<()> {
+    let applied: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM schema_migrations WHERE name = ?1",
+        [name],
+        |row| row.get(0),
+    )?;
+    if applied {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    migration(&tx)?;
+    tx.execute(
+        "INSERT INTO schema_migrations (name, applied_at) VALUES (?1, datetime('now'))",
+        [name],
+    )?;
+    tx.commit()
+}
+
+/// `ALTER TABLE ... ADD COLUMN` if the table exists and lacks the column
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns: Vec<String> = stmt.query_map([], |row| row.get(1))?.filter_map(|r| r.ok()).collect();
+
+    // Table doesn't exist yet: schema.sql will create it with every column
+    if columns.is_empty() || columns.iter().any(|c| c == column) {
+        return Ok(());
+    }
+
+    conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+}
+
 pub fn parse_json_array(json: &str) -> Vec<String> {
     serde_json::from_str(json).unwrap_or_default()
 }
@@ -0,0 +1,434 @@
+use chrono::Utc;
+use rusqlite::Connection;
+
+use crate::measurements::{Measurements, FIELD_LABELS};
+use crate::models::{Order, OrderItem};
+use crate::order_numbers;
+use crate::order_status;
+use crate::payments;
+use crate::pdf::{Document, A4, A5};
+use crate::pricing;
+use crate::text;
+use crate::vietqr::{self, BankAccount};
+
+pub const DEFAULT_ADMIN_URL: &str = "https://admin.hylacviet.vn";
+
+/// Light gold band behind table headers, from the brand colour
+const BAND: (f32, f32, f32) = (0.96, 0.92, 0.80);
+
+/// Shop details printed on documents, from settings
+pub struct Shop {
+    pub name: String,
+    pub tagline: String,
+    pub address: String,
+    pub phone: String,
+    pub email: String,
+    /// Base of the links printed on work tickets
+    pub admin_url: String,
+}
+
+impl Shop {
+    pub fn load(conn: &Connection) -> Shop {
+        let setting = |key: &str| -> String {
+            conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get::<_, String>(0))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+        let admin_url = setting("admin_url");
+        Shop {
+            name: setting("site_name"),
+            tagline: setting("site_tagline"),
+            address: setting("address"),
+            phone: setting("phone"),
+            email: setting("email"),
+            admin_url: if admin_url.is_empty() { DEFAULT_ADMIN_URL.to_string() } else { admin_url },
+        }
+    }
+}
+
+/// `admin_url` must be an absolute http(s) URL for ticket QR codes to open
+pub fn validate_admin_url(url: &str) -> Result<(), String> {
+    if (url.starts_with("https://") || url.starts_with("http://")) && !url.contains(char::is_whitespace) {
+        Ok(())
+    } else {
+        Err("admin_url must start with http:// or https://".to_string())
+    }
+}
+
+/// Link to the order in the admin, encoded in the work ticket QR code
+pub fn order_url(shop: &Shop, order_id: &str) -> String {
+    format!("{}/orders/{}", shop.admin_url.trim_end_matches('/'), order_id)
+}
+
+/// "1.050.000 đ"
+pub fn vnd(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(c);
+    }
+    format!("{}{} đ", if amount < 0 { "-" } else { "" }, grouped)
+}
+
+/// A stored date or timestamp as a shop-local "dd/mm/yyyy"
+fn local_date(value: &str) -> String {
+    pricing::parse_datetime(value, false)
+        .map(|dt| dt.with_timezone(&pricing::shop_offset()).format("%d/%m/%Y").to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn local_datetime(value: &str) -> String {
+    pricing::parse_datetime(value, false)
+        .map(|dt| dt.with_timezone(&pricing::shop_offset()).format("%d/%m/%Y %H:%M").to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn order_title(order: &Order) -> String {
+    order.order_number.clone().unwrap_or_else(|| order.id.chars().take(8).collect())
+}
+
+/// Lines to print: the order's items, or a single line for orders placed
+/// before orders had items
+fn lines(order: &Order) -> Vec<OrderItem> {
+    if !order.items.is_empty() {
+        return order.items.clone();
+    }
+    vec![OrderItem {
+        id: String::new(),
+        product_id: order.product_id.clone(),
+        product_name: order.product_name.clone(),
+        variant: String::new(),
+        quantity: 1,
+        unit_price: order.subtotal,
+        options_price: 0,
+        line_total: order.subtotal,
+        discount_amount: 0,
+        selected_options: vec![],
+        measurements: order.measurements.clone(),
+        wearer_id: None,
+    }]
+}
+
+/// Customer invoice, or a receipt once the order is paid in full. Carries a
+/// VietQR code for the balance while one is due and bank transfer is set up.
+pub fn invoice(order: &Order, shop: &Shop, account: Option<&BankAccount>) -> Result<Vec<u8>, String> {
+    let paid_in_full = order.paid_amount > 0 && order.balance_due <= 0;
+    let heading = if paid_in_full { "BIÊN NHẬN" } else { "HÓA ĐƠN" };
+    let number = order_title(order);
+    let mut doc = Document::new(&format!("{} {}", heading, number), A4, 48.0);
+    let (left, right) = (doc.margin, doc.right());
+
+    // Shop details on the left, document title and number on the right
+    doc.text(left, 70.0, 22.0, &shop.name);
+    doc.text_right(right, 70.0, 18.0, heading);
+    doc.y = 70.0;
+    for line in [&shop.tagline, &shop.address, &shop.phone, &shop.email] {
+        if !line.is_empty() {
+            doc.y += 13.0;
+            let y = doc.y;
+            doc.text(left, y, 9.5, line);
+        }
+    }
+    doc.text_right(right, 88.0, 10.0, &format!("Số: {}", number));
+    doc.text_right(right, 101.0, 10.0, &format!("Ngày: {}", local_date(&order.created_at)));
+    doc.text_right(right, 114.0, 10.0, &format!("Thanh toán: {}", payments::status_label(&order.payment_status)));
+    doc.y = doc.y.max(114.0) + 14.0;
+    let y = doc.y;
+    doc.rule(y, 0.8);
+
+    // Customer
+    doc.y += 20.0;
+    let y = doc.y;
+    doc.text(left, y, 11.0, &format!("Khách hàng: {}", order.customer_name));
+    doc.y += 15.0;
+    let y = doc.y;
+    doc.text(left, y, 10.0, &format!("Điện thoại: {}", order.customer_phone));
+    if !order.customer_email.is_empty() {
+        doc.y += 14.0;
+        let y = doc.y;
+        doc.text(left, y, 10.0, &format!("Email: {}", order.customer_email));
+    }
+    if let Some(delivery) = order.delivery_date.as_deref() {
+        doc.y += 14.0;
+        let y = doc.y;
+        doc.text(left, y, 10.0, &format!("Ngày giao dự kiến: {}", local_date(delivery)));
+    }
+
+    // Lines
+    let (qty_right, price_right) = (right - 170.0, right - 85.0);
+    let name_x = left + 22.0;
+    let name_width = qty_right - 40.0 - name_x;
+    let table_header = |doc: &mut Document| {
+        doc.ensure_space(24.0);
+        let y = doc.y;
+        doc.fill_rect(left, y, right - left, 20.0, BAND);
+        doc.text(left + 4.0, y + 14.0, 9.5, "#");
+        doc.text(name_x, y + 14.0, 9.5, "Sản phẩm");
+        doc.text_right(qty_right, y + 14.0, 9.5, "SL");
+        doc.text_right(price_right, y + 14.0, 9.5, "Đơn giá");
+        doc.text_right(right - 4.0, y + 14.0, 9.5, "Thành tiền");
+        doc.y += 20.0;
+    };
+    doc.y += 18.0;
+    table_header(&mut doc);
+    for (i, item) in lines(order).iter().enumerate() {
+        let mut name = item.product_name.clone();
+        if !item.variant.is_empty() {
+            name = format!("{} ({})", name, item.variant);
+        }
+        let name_lines = doc.wrap(&name, 10.0, name_width);
+        let options: Vec<String> = item
+            .selected_options
+            .iter()
+            .map(|o| {
+                if o.price_delta != 0 {
+                    format!("{}: {} (+{})", o.group_name, o.choice_name, vnd(o.price_delta))
+                } else {
+                    format!("{}: {}", o.group_name, o.choice_name)
+                }
+            })
+            .collect();
+        let height = 8.0 + name_lines.len() as f32 * 13.0 + options.len() as f32 * 11.0;
+        if doc.y + height > A4.1 - doc.margin {
+            doc.new_page();
+            table_header(&mut doc);
+        }
+
+        let top = doc.y + 15.0;
+        doc.text(left + 4.0, top, 10.0, &(i + 1).to_string());
+        doc.text_right(qty_right, top, 10.0, &item.quantity.to_string());
+        doc.text_right(price_right, top, 10.0, &vnd(item.unit_price + item.options_price));
+        doc.text_right(right - 4.0, top, 10.0, &vnd(item.line_total));
+        let mut y = top;
+        for line in &name_lines {
+            doc.text(name_x, y, 10.0, line);
+            y += 13.0;
+        }
+        for option in &options {
+            doc.text(name_x + 6.0, y - 2.0, 8.5, option);
+            y += 11.0;
+        }
+        doc.y += height;
+        let rule_y = doc.y;
+        doc.rule(rule_y, 0.3);
+    }
+
+    // Totals
+    let mut totals = vec![("Tạm tính".to_string(), vnd(order.subtotal))];
+    if order.discount_amount > 0 {
+        let label = match order.coupon_code.as_deref() {
+            Some(code) => format!("Giảm giá ({})", code),
+            None => "Giảm giá".to_string(),
+        };
+        totals.push((label, vnd(-order.discount_amount)));
+    }
+    totals.push(("Tổng cộng".to_string(), vnd(order.total)));
+    totals.push(("Đã thanh toán".to_string(), vnd(order.paid_amount)));
+    totals.push(("Còn lại".to_string(), vnd(order.balance_due.max(0))));
+    doc.ensure_space(totals.len() as f32 * 17.0 + 12.0);
+    doc.y += 6.0;
+    for (label, amount) in &totals {
+        let size = if label == "Tổng cộng" { 12.0 } else { 10.0 };
+        doc.y += 17.0;
+        let y = doc.y;
+        doc.text_right(price_right, y, size, label);
+        doc.text_right(right - 4.0, y, size, amount);
+    }
+
+    // Payments received
+    let ledger = order.payments.as_deref().unwrap_or_default();
+    if !ledger.is_empty() {
+        doc.ensure_space(40.0 + 14.0);
+        doc.y += 28.0;
+        let y = doc.y;
+        doc.text(left, y, 11.0, "Lịch sử thanh toán");
+        for payment in ledger {
+            doc.ensure_space(14.0);
+            doc.y += 14.0;
+            let y = doc.y;
+            let mut label = format!("{}  {}", local_date(&payment.paid_at), payments::method_label(&payment.method));
+            if !payment.reference.is_empty() {
+                label = format!("{} · {}", label, payment.reference);
+            }
+            doc.text(left + 4.0, y, 9.5, &label);
+            doc.text_right(right - 4.0, y, 9.5, &vnd(payment.amount));
+        }
+    }
+
+    // Bank transfer for what is still due
+    if let Some(account) = account.filter(|_| order.balance_due > 0 && order.status != "cancelled") {
+        let qr_size = 96.0;
+        doc.ensure_space(qr_size + 36.0);
+        doc.y += 26.0;
+        let top = doc.y;
+        let memo = order_numbers::transfer_memo(order.order_number.as_deref().unwrap_or(&order.id));
+        doc.qr(left, top, qr_size, &vietqr::payload(account, order.balance_due, &memo))?;
+        let x = left + qr_size + 16.0;
+        let details = [
+            ("Chuyển khoản", String::new()),
+            ("Mã ngân hàng (BIN)", account.bin.clone()),
+            ("Số tài khoản", account.account_number.clone()),
+            ("Chủ tài khoản", account.account_name.clone()),
+            ("Số tiền", vnd(order.balance_due)),
+            ("Nội dung", memo),
+        ];
+        let mut y = top + 12.0;
+        for (label, value) in details {
+            if value.is_empty() {
+                doc.text(x, y, 11.0, label);
+            } else {
+                doc.text(x, y, 9.5, &format!("{}: {}", label, value));
+            }
+            y += 15.0;
+        }
+        doc.y = top + qr_size;
+    }
+
+    doc.ensure_space(40.0);
+    doc.y += 36.0;
+    let y = doc.y;
+    doc.text_centered(y, 10.0, &format!("Cảm ơn quý khách đã tin chọn {}", shop.name));
+    doc.finish()
+}
+
+/// Option groups that pick the fabric, printed apart from other options
+fn is_fabric(group_name: &str) -> bool {
+    let folded = text::fold(group_name);
+    ["chat lieu", "vai", "fabric", "material"].iter().any(|k| folded.contains(k))
+}
+
+/// Measurements a garment is cut to: its own, else its wearer's, else the orderer's
+fn garment_measurements<'a>(order: &'a Order, item: &'a OrderItem) -> (&'a Measurements, Option<String>) {
+    let wearer = item.wearer_id.as_deref().and_then(|id| order.wearers.iter().find(|w| w.id == id));
+    let wearer_name = wearer.map(|w| if w.role.is_empty() { w.name.clone() } else { format!("{} ({})", w.name, w.role) });
+    if !item.measurements.is_empty() {
+        (&item.measurements, wearer_name)
+    } else if let Some(w) = wearer.filter(|w| !w.measurements.is_empty()) {
+        (&w.measurements, wearer_name)
+    } else {
+        (&order.measurements, wearer_name)
+    }
+}
+
+/// Workshop ticket for the tailors: garments with their fabric, options and
+/// measurements, the dates to work to, and a QR code opening the order in
+/// the admin. Prices and contact details beyond the name and phone are left off.
+pub fn work_ticket(order: &Order, shop: &Shop) -> Result<Vec<u8>, String> {
+    let number = order_title(order);
+    let mut doc = Document::new(&format!("Phiếu may {}", number), A5, 28.0);
+    let (left, right) = (doc.margin, doc.right());
+    let qr_size = 78.0;
+
+    doc.text(left, 46.0, 14.0, "PHIẾU MAY");
+    doc.text(left, 72.0, 20.0, &number);
+    doc.qr(right - qr_size, left, qr_size, &order_url(shop, &order.id))?;
+    let mut info = vec![
+        format!("Khách: {} · {}", order.customer_name, order.customer_phone),
+        format!("Trạng thái: {}", order_status::label(&order.status)),
+    ];
+    if let Some(fitting) = order.fitting_date.as_deref() {
+        info.push(format!("Hẹn thử: {}", local_date(fitting)));
+    }
+    doc.y = 72.0;
+    for line in &info {
+        doc.y += 14.0;
+        let y = doc.y;
+        doc.text(left, y, 9.5, line);
+    }
+    doc.y += 20.0;
+    let due = order.delivery_date.as_deref().map(local_date).unwrap_or_else(|| "chưa hẹn".to_string());
+    let y = doc.y;
+    doc.text(left, y, 13.0, &format!("Ngày giao: {}", due));
+    doc.y = doc.y.max(left + qr_size) + 10.0;
+    let y = doc.y;
+    doc.rule(y, 0.8);
+
+    let width = right - left;
+    let items = lines(order);
+    for (i, item) in items.iter().enumerate() {
+        doc.ensure_space(60.0);
+        doc.y += 20.0;
+        let mut title = format!("{}. {}", i + 1, item.product_name);
+        if !item.variant.is_empty() {
+            title = format!("{} · {}", title, item.variant);
+        }
+        if item.quantity > 1 {
+            title = format!("{} × {}", title, item.quantity);
+        }
+        let y = doc.y;
+        doc.text(left, y, 12.0, &title);
+
+        let (measurements, wearer) = garment_measurements(order, item);
+        if let Some(wearer) = wearer {
+            doc.paragraph(left + 8.0, 9.5, 13.0, width - 8.0, &format!("Người mặc: {}", wearer));
+        }
+        for option in item.selected_options.iter().filter(|o| is_fabric(&o.group_name)) {
+            doc.paragraph(left + 8.0, 10.5, 14.0, width - 8.0, &format!("Vải: {}", option.choice_name));
+        }
+        for option in item.selected_options.iter().filter(|o| !is_fabric(&o.group_name)) {
+            doc.paragraph(left + 8.0, 9.5, 13.0, width - 8.0, &format!("{}: {}", option.group_name, option.choice_name));
+        }
+
+        // Measurements two to a row in a boxed grid
+        let fields: Vec<String> = FIELD_LABELS
+            .iter()
+            .filter_map(|(name, label)| {
+                let mut cell = format!("{}: {}", label, measurements.display(name)?);
+                if let Some(note) = measurements.notes.get(*name) {
+                    cell = format!("{} ({})", cell, note);
+                }
+                Some(cell)
+            })
+            .collect();
+        if !fields.is_empty() {
+            let rows = fields.len().div_ceil(2);
+            let height = rows as f32 * 16.0 + 6.0;
+            doc.ensure_space(height + 8.0);
+            doc.y += 8.0;
+            let top = doc.y;
+            doc.stroke_rect(left, top, width, height, 0.5);
+            for (n, cell) in fields.iter().enumerate() {
+                let x = left + 6.0 + (n % 2) as f32 * width / 2.0;
+                let y = top + 15.0 + (n / 2) as f32 * 16.0;
+                doc.text(x, y, 10.0, cell);
+            }
+            doc.y = top + height;
+        }
+        // Free text from before the structured form, when nothing could be read from it
+        if fields.is_empty() {
+            let text = match measurements.raw.as_deref() {
+                Some(raw) => format!("Số đo ghi tay: {}", raw),
+                None => "Chưa có số đo".to_string(),
+            };
+            doc.paragraph(left + 8.0, 9.5, 13.0, width - 8.0, &text);
+        }
+    }
+
+    let wearer_notes: Vec<String> = order
+        .wearers
+        .iter()
+        .filter(|w| !w.notes.is_empty())
+        .map(|w| format!("{}: {}", w.name, w.notes))
+        .collect();
+    if !order.notes.is_empty() || !wearer_notes.is_empty() {
+        doc.ensure_space(40.0);
+        doc.y += 22.0;
+        let y = doc.y;
+        doc.text(left, y, 11.0, "Ghi chú");
+        if !order.notes.is_empty() {
+            doc.paragraph(left + 8.0, 9.5, 13.0, width - 8.0, &order.notes);
+        }
+        for note in &wearer_notes {
+            doc.paragraph(left + 8.0, 9.5, 13.0, width - 8.0, note);
+        }
+    }
+
+    doc.ensure_space(24.0);
+    doc.y += 22.0;
+    let y = doc.y;
+    doc.text_right(right, y, 8.0, &format!("In lúc {}", local_datetime(&Utc::now().to_rfc3339())));
+    doc.finish()
+}
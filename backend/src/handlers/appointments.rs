@@ -0,0 +1,693 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use uuid::Uuid;
+
+use crate::appointments::{self, SlotError, APPOINTMENT_SELECT};
+use crate::customers;
+use crate::db::DbPool;
+use crate::handlers::auth;
+use crate::models::{
+    ApiResponse, Appointment, AvailabilityParams, CalendarDay, CalendarParams, CreateAppointment, CreateHoliday,
+    CreateShowroom, DaySlots, Holiday, HolidayListParams, RescheduleAppointment, Showroom, UpdateAppointment,
+    UpdateShowroom,
+};
+use crate::phone;
+use crate::pricing::shop_offset;
+
+fn parse_date(field: &str, value: &str) -> Result<NaiveDate, (StatusCode, Json<ApiResponse<()>>)> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("{} must be YYYY-MM-DD", field))))
+    })
+}
+
+fn slot_error(e: SlotError) -> (StatusCode, Json<ApiResponse<()>>) {
+    match e {
+        SlotError::Unavailable(message) => (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&message))),
+        SlotError::Full => (
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("That time is fully booked, please choose another")),
+        ),
+    }
+}
+
+fn validate_showroom(
+    working_hours: Option<&std::collections::BTreeMap<String, Vec<String>>>,
+    slot_minutes: Option<i64>,
+    capacity: Option<i64>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if let Some(hours) = working_hours {
+        appointments::parse_hours(hours).map_err(|e| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+        })?;
+    }
+    if slot_minutes.is_some_and(|m| !(10..=240).contains(&m)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("slot_minutes must be between 10 and 240"))));
+    }
+    if capacity.is_some_and(|c| !(1..=50).contains(&c)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("capacity must be between 1 and 50"))));
+    }
+    Ok(())
+}
+
+/// GET /api/showrooms - List showrooms; staff also see inactive ones (public)
+pub async fn list_showrooms(
+    State(db): State<DbPool>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<Showroom>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let all = auth::optional_claims(&headers).is_some();
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, address, phone, working_hours, slot_minutes, capacity, active, created_at, updated_at
+         FROM showrooms WHERE ?1 OR active = 1 ORDER BY created_at ASC",
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let showrooms: Vec<Showroom> = stmt.query_map(params![all], appointments::showroom_from_row)
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(showrooms)))
+}
+
+/// POST /api/showrooms - Create showroom (auth required)
+pub async fn create_showroom(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreateShowroom>,
+) -> Result<Json<ApiResponse<Showroom>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.name.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Showroom name is required"))));
+    }
+    validate_showroom(payload.working_hours.as_ref(), Some(payload.slot_minutes), Some(payload.capacity))?;
+    let working_hours = match payload.working_hours {
+        Some(hours) => serde_json::to_string(&hours).unwrap_or_default(),
+        None => appointments::DEFAULT_WORKING_HOURS.to_string(),
+    };
+    let id = Uuid::new_v4().to_string();
+
+    let conn = db.lock().unwrap();
+    conn.execute(
+        "INSERT INTO showrooms (id, name, address, phone, working_hours, slot_minutes, capacity, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            id,
+            payload.name.trim(),
+            payload.address.trim(),
+            payload.phone.trim(),
+            working_hours,
+            payload.slot_minutes,
+            payload.capacity,
+            Utc::now().to_rfc3339()
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let showroom = appointments::load_showroom(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(showroom)))
+}
+
+/// PUT /api/showrooms/:id - Update showroom; existing appointments are kept (auth required)
+pub async fn update_showroom(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateShowroom>,
+) -> Result<Json<ApiResponse<Showroom>>, (StatusCode, Json<ApiResponse<()>>)> {
+    validate_showroom(payload.working_hours.as_ref(), payload.slot_minutes, payload.capacity)?;
+    let working_hours = payload.working_hours.map(|hours| serde_json::to_string(&hours).unwrap_or_default());
+
+    let conn = db.lock().unwrap();
+    let rows = conn.execute(
+        "UPDATE showrooms SET
+            name = COALESCE(?1, name),
+            address = COALESCE(?2, address),
+            phone = COALESCE(?3, phone),
+            working_hours = COALESCE(?4, working_hours),
+            slot_minutes = COALESCE(?5, slot_minutes),
+            capacity = COALESCE(?6, capacity),
+            active = COALESCE(?7, active),
+            updated_at = ?8
+         WHERE id = ?9",
+        params![
+            payload.name.as_deref().map(str::trim).filter(|n| !n.is_empty()),
+            payload.address.as_deref().map(str::trim),
+            payload.phone.as_deref().map(str::trim),
+            working_hours,
+            payload.slot_minutes,
+            payload.capacity,
+            payload.active,
+            Utc::now().to_rfc3339(),
+            id
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Showroom not found"))));
+    }
+
+    let showroom = appointments::load_showroom(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(showroom)))
+}
+
+/// DELETE /api/showrooms/:id - Delete a showroom without appointments (auth required)
+///
+/// Showrooms with appointment history should be deactivated instead.
+pub async fn delete_showroom(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let in_use: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM appointments WHERE showroom_id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if in_use {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("This showroom has appointments; set it inactive instead")),
+        ));
+    }
+
+    conn.execute("DELETE FROM showroom_holidays WHERE showroom_id = ?1", params![id]).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let rows = conn.execute("DELETE FROM showrooms WHERE id = ?1", params![id]).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Showroom not found"))));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Showroom deleted".to_string()),
+    }))
+}
+
+/// GET /api/showrooms/:id/availability - Bookable slots by day (public)
+pub async fn showroom_availability(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Query(params): Query<AvailabilityParams>,
+) -> Result<Json<ApiResponse<Vec<DaySlots>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let now = Utc::now();
+    let today = now.with_timezone(&shop_offset()).date_naive();
+    let from = match params.from.as_deref().filter(|f| !f.trim().is_empty()) {
+        Some(from) => parse_date("from", from)?.max(today),
+        None => today,
+    };
+    let days = params.days.clamp(1, 31);
+
+    let conn = db.lock().unwrap();
+    let showroom = appointments::load_showroom(&conn, &id)
+        .ok()
+        .filter(|s| s.active)
+        .ok_or_else(|| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Showroom not found")))
+        })?;
+
+    let slots = appointments::availability(&conn, &showroom, from, days, now).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    Ok(Json(ApiResponse::success(slots)))
+}
+
+/// GET /api/holidays - List closed days (auth required)
+pub async fn list_holidays(
+    State(db): State<DbPool>,
+    Query(params): Query<HolidayListParams>,
+) -> Result<Json<ApiResponse<Vec<Holiday>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let from = params.from.as_deref().filter(|f| !f.trim().is_empty()).map(|f| parse_date("from", f)).transpose()?;
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, showroom_id, date, name, created_at FROM showroom_holidays
+         WHERE ?1 IS NULL OR date >= ?1 ORDER BY date ASC",
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let holidays: Vec<Holiday> = stmt.query_map(params![from.map(|d| d.format("%Y-%m-%d").to_string())], |row| {
+        Ok(Holiday {
+            id: row.get(0)?,
+            showroom_id: row.get(1)?,
+            date: row.get(2)?,
+            name: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            created_at: row.get(4)?,
+        })
+    }).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(holidays)))
+}
+
+/// POST /api/holidays - Close one showroom, or all of them, for a day (auth required)
+///
+/// Appointments already booked that day are left for staff to move.
+pub async fn create_holiday(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreateHoliday>,
+) -> Result<Json<ApiResponse<Holiday>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let date = parse_date("date", &payload.date)?.format("%Y-%m-%d").to_string();
+    let showroom_id = payload.showroom_id.filter(|s| !s.trim().is_empty());
+    let conn = db.lock().unwrap();
+
+    if let Some(ref showroom_id) = showroom_id {
+        appointments::load_showroom(&conn, showroom_id).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Showroom not found")))
+        })?;
+    }
+
+    let holiday = Holiday {
+        id: Uuid::new_v4().to_string(),
+        showroom_id,
+        date,
+        name: payload.name.trim().to_string(),
+        created_at: Utc::now().to_rfc3339(),
+    };
+    conn.execute(
+        "INSERT INTO showroom_holidays (id, showroom_id, date, name, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![holiday.id, holiday.showroom_id, holiday.date, holiday.name, holiday.created_at],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    Ok(Json(ApiResponse::success(holiday)))
+}
+
+/// DELETE /api/holidays/:id - Reopen a closed day (auth required)
+pub async fn delete_holiday(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    let rows = conn.execute("DELETE FROM showroom_holidays WHERE id = ?1", params![id]).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Holiday not found"))));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Holiday deleted".to_string()),
+    }))
+}
+
+/// POST /api/appointments - Book a measuring session, fitting or pickup (public)
+///
+/// Customers link the visit to their order with its tracking code; staff
+/// may pass `order_id` instead and aren't held to the per-phone limit.
+pub async fn book_appointment(
+    State(db): State<DbPool>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateAppointment>,
+) -> Result<Json<ApiResponse<Appointment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let phone = phone::normalize(&payload.customer_phone).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid phone number")))
+    })?;
+    let name = payload.customer_name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("customer_name is required"))));
+    }
+    if !appointments::is_valid_kind(&payload.kind) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("kind must be measuring, fitting or pickup"))));
+    }
+    let start = appointments::parse_start(&payload.starts_at).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    let from_staff = auth::optional_claims(&headers).is_some();
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+
+    let mut conn = db.lock().unwrap();
+    let showroom = appointments::load_showroom(&conn, &payload.showroom_id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Showroom not found")))
+    })?;
+
+    let order_id: Option<String> = match (payload.order_id.filter(|o| from_staff && !o.is_empty()), payload.tracking_code) {
+        (Some(order_id), _) => conn.query_row("SELECT id FROM orders WHERE id = ?1", params![order_id], |row| row.get(0)),
+        (None, Some(code)) if !code.trim().is_empty() => conn.query_row(
+            "SELECT id FROM orders WHERE tracking_code = ?1",
+            params![code.trim().to_lowercase()],
+            |row| row.get(0),
+        ),
+        _ => Ok(None),
+    }.optional().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+    })?;
+
+    if !from_staff {
+        let upcoming: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM appointments WHERE customer_phone = ?1 AND status = 'booked' AND starts_at > ?2",
+            params![phone, appointments::format_time(now.with_timezone(&shop_offset()))],
+            |row| row.get(0),
+        ).unwrap_or(0);
+        if upcoming >= appointments::MAX_UPCOMING_PER_PHONE {
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::<()>::error("You already have upcoming appointments; please reschedule one or call the shop")),
+            ));
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let (starts_at, ends_at) = appointments::reserve(&tx, &showroom, start, None, now).map_err(slot_error)?;
+    let customer_id = customers::upsert_by_phone(&tx, &phone, name, "").map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    tx.execute(
+        "INSERT INTO appointments (id, showroom_id, order_id, customer_id, customer_name, customer_phone, kind,
+            starts_at, ends_at, status, notes, manage_code, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'booked', ?10, ?11, ?12)",
+        params![
+            id,
+            showroom.id,
+            order_id,
+            customer_id,
+            name,
+            phone,
+            payload.kind,
+            starts_at,
+            ends_at,
+            payload.notes,
+            Uuid::new_v4().simple().to_string(),
+            now.to_rfc3339()
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if let Some(ref order_id) = order_id {
+        appointments::sync_fitting_date(&tx, order_id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let appointment = appointments::load_appointment(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(appointment)))
+}
+
+/// Appointment by its manage code, if the customer may still change it
+fn managed_appointment(conn: &Connection, code: &str) -> Result<Appointment, (StatusCode, Json<ApiResponse<()>>)> {
+    conn.query_row(
+        &format!("{} WHERE a.manage_code = ?1", APPOINTMENT_SELECT),
+        params![code.trim().to_lowercase()],
+        appointments::appointment_from_row,
+    ).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Appointment not found")))
+    })
+}
+
+fn ensure_changeable(appointment: &Appointment) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let now = appointments::format_time(Utc::now().with_timezone(&shop_offset()));
+    if appointment.status != "booked" || appointment.starts_at <= now {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("This appointment can no longer be changed; please call the shop")),
+        ));
+    }
+    Ok(())
+}
+
+/// Move an appointment to `start` at `showroom_id`, as part of the caller's transaction
+fn move_appointment(
+    tx: &Transaction,
+    appointment: &Appointment,
+    showroom_id: &str,
+    start: chrono::DateTime<chrono::FixedOffset>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let now = Utc::now();
+    let showroom = appointments::load_showroom(tx, showroom_id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Showroom not found")))
+    })?;
+
+    let (starts_at, ends_at) = appointments::reserve(tx, &showroom, start, Some(&appointment.id), now)
+        .map_err(slot_error)?;
+    tx.execute(
+        "UPDATE appointments SET showroom_id = ?1, starts_at = ?2, ends_at = ?3, status = 'booked', updated_at = ?4
+         WHERE id = ?5",
+        params![showroom.id, starts_at, ends_at, now.to_rfc3339(), appointment.id],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if let Some(ref order_id) = appointment.order_id {
+        appointments::sync_fitting_date(tx, order_id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    Ok(())
+}
+
+/// GET /api/appointments/manage/:code - View own appointment (public)
+pub async fn get_managed_appointment(
+    State(db): State<DbPool>,
+    Path(code): Path<String>,
+) -> Result<Json<ApiResponse<Appointment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    let appointment = managed_appointment(&conn, &code)?;
+    Ok(Json(ApiResponse::success(appointment)))
+}
+
+/// PUT /api/appointments/manage/:code - Reschedule own appointment (public)
+pub async fn reschedule_managed_appointment(
+    State(db): State<DbPool>,
+    Path(code): Path<String>,
+    Json(payload): Json<RescheduleAppointment>,
+) -> Result<Json<ApiResponse<Appointment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let start = appointments::parse_start(&payload.starts_at).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+
+    let mut conn = db.lock().unwrap();
+    let appointment = managed_appointment(&conn, &code)?;
+    ensure_changeable(&appointment)?;
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    move_appointment(&tx, &appointment, &appointment.showroom_id, start)?;
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let appointment = managed_appointment(&conn, &code)?;
+    Ok(Json(ApiResponse::success(appointment)))
+}
+
+/// DELETE /api/appointments/manage/:code - Cancel own appointment (public)
+pub async fn cancel_managed_appointment(
+    State(db): State<DbPool>,
+    Path(code): Path<String>,
+) -> Result<Json<ApiResponse<Appointment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let mut conn = db.lock().unwrap();
+    let appointment = managed_appointment(&conn, &code)?;
+    ensure_changeable(&appointment)?;
+
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    tx.execute(
+        "UPDATE appointments SET status = 'cancelled', updated_at = ?1 WHERE id = ?2",
+        params![Utc::now().to_rfc3339(), appointment.id],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if let Some(ref order_id) = appointment.order_id {
+        appointments::sync_fitting_date(&tx, order_id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let appointment = managed_appointment(&conn, &code)?;
+    Ok(Json(ApiResponse::success(appointment)))
+}
+
+/// GET /api/appointments - Calendar by day or week, or every appointment of an order (auth required)
+pub async fn list_appointments(
+    State(db): State<DbPool>,
+    Query(params): Query<CalendarParams>,
+) -> Result<Json<ApiResponse<Vec<CalendarDay>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let today = Utc::now().with_timezone(&shop_offset()).date_naive();
+    let date = match params.date.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(date) => parse_date("date", date)?,
+        None => today,
+    };
+    let (first, days) = match params.view.as_deref().unwrap_or("day") {
+        "day" => (date, 1),
+        "week" => (date - Duration::days(date.weekday().num_days_from_monday() as i64), 7),
+        _ => return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("view must be day or week")))),
+    };
+    let order_id = params.order_id.as_deref().filter(|o| !o.is_empty());
+    let showroom_id = params.showroom_id.as_deref().filter(|s| !s.is_empty());
+
+    let conn = db.lock().unwrap();
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE (?1 IS NOT NULL OR substr(a.starts_at, 1, 10) BETWEEN ?2 AND ?3)
+            AND (?1 IS NULL OR a.order_id = ?1)
+            AND (?4 IS NULL OR a.showroom_id = ?4)
+            AND (?5 OR a.status != 'cancelled')
+         ORDER BY a.starts_at ASC",
+        APPOINTMENT_SELECT
+    )).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let found: Vec<Appointment> = stmt.query_map(
+        params![
+            order_id,
+            first.format("%Y-%m-%d").to_string(),
+            (first + Duration::days(days - 1)).format("%Y-%m-%d").to_string(),
+            showroom_id,
+            params.include_cancelled
+        ],
+        appointments::appointment_from_row,
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    // Calendar views list every day of the period, even empty ones
+    let mut calendar: Vec<CalendarDay> = match order_id {
+        Some(_) => vec![],
+        None => first
+            .iter_days()
+            .take(days as usize)
+            .map(|d| CalendarDay { date: d.format("%Y-%m-%d").to_string(), appointments: vec![] })
+            .collect(),
+    };
+    for appointment in found {
+        let day = appointment.starts_at[..10].to_string();
+        match calendar.iter_mut().find(|d| d.date == day) {
+            Some(entry) => entry.appointments.push(appointment),
+            None => calendar.push(CalendarDay { date: day, appointments: vec![appointment] }),
+        }
+    }
+
+    Ok(Json(ApiResponse::success(calendar)))
+}
+
+/// GET /api/appointments/:id - Get appointment (auth required)
+pub async fn get_appointment(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Appointment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    let appointment = appointments::load_appointment(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Appointment not found")))
+    })?;
+    Ok(Json(ApiResponse::success(appointment)))
+}
+
+/// PUT /api/appointments/:id - Reschedule, relink or record the outcome of an appointment (auth required)
+///
+/// Moving it, or booking a cancelled one again, goes through the same slot
+/// and capacity checks as a new booking.
+pub async fn update_appointment(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateAppointment>,
+) -> Result<Json<ApiResponse<Appointment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.status.as_deref().is_some_and(|s| !appointments::STATUSES.contains(&s)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("status must be booked, completed, cancelled or no_show")),
+        ));
+    }
+    if payload.kind.as_deref().is_some_and(|k| !appointments::is_valid_kind(k)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("kind must be measuring, fitting or pickup"))));
+    }
+    let start = payload.starts_at.as_deref().map(appointments::parse_start).transpose().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+
+    let mut conn = db.lock().unwrap();
+    let appointment = appointments::load_appointment(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Appointment not found")))
+    })?;
+
+    let order_id = match payload.order_id.as_deref().map(str::trim) {
+        Some("") => Some(None),
+        Some(order_id) => Some(Some(
+            conn.query_row("SELECT id FROM orders WHERE id = ?1", params![order_id], |row| row.get::<_, String>(0))
+                .map_err(|_| {
+                    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+                })?,
+        )),
+        None => None,
+    };
+
+    // The move and the other fields are saved together or not at all
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let showroom_id = payload.showroom_id.clone().unwrap_or_else(|| appointment.showroom_id.clone());
+    let rebooking = payload.status.as_deref() == Some("booked") && appointment.status != "booked";
+    if start.is_some() || showroom_id != appointment.showroom_id || rebooking {
+        let start = match start {
+            Some(start) => start,
+            None => appointments::parse_start(&appointment.starts_at).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e)))
+            })?,
+        };
+        move_appointment(&tx, &appointment, &showroom_id, start)?;
+    }
+
+    tx.execute(
+        "UPDATE appointments SET
+            kind = COALESCE(?1, kind),
+            status = COALESCE(?2, status),
+            notes = COALESCE(?3, notes),
+            order_id = CASE WHEN ?4 THEN ?5 ELSE order_id END,
+            updated_at = ?6
+         WHERE id = ?7",
+        params![
+            payload.kind,
+            payload.status,
+            payload.notes,
+            order_id.is_some(),
+            order_id.clone().flatten(),
+            Utc::now().to_rfc3339(),
+            id
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    // Both the order it left and the one it joined
+    for order_id in [appointment.order_id.clone(), order_id.flatten()].into_iter().flatten() {
+        appointments::sync_fitting_date(&tx, &order_id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let appointment = appointments::load_appointment(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(appointment)))
+}
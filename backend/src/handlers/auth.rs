@@ -1,11 +1,11 @@
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     Json,
 };
 use rusqlite::params;
 use sha2::{Sha256, Digest};
-use jsonwebtoken::{encode, Header, EncodingKey};
+use jsonwebtoken::{decode, encode, DecodingKey, Header, EncodingKey, Validation};
 use chrono::{Utc, Duration};
 
 use crate::db::DbPool;
@@ -108,6 +108,14 @@ pub async fn get_me(
     Ok(Json(ApiResponse::success(user)))
 }
 
+/// Claims of a valid admin token sent to a public route, if any
+pub fn optional_claims(headers: &HeaderMap) -> Option<Claims> {
+    let token = headers.get(header::AUTHORIZATION)?.to_str().ok()?.strip_prefix("Bearer ")?;
+    decode::<Claims>(token, &DecodingKey::from_secret(JWT_SECRET.as_ref()), &Validation::default())
+        .ok()
+        .map(|data| data.claims)
+}
+
 pub fn get_jwt_secret() -> &'static str {
     JWT_SECRET
 }
@@ -0,0 +1,361 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::models::{ApiResponse, BankImport, BankTransaction, Claims, ReconcileBankImport};
+use crate::payments;
+use crate::pricing;
+use crate::reconciliation;
+use crate::vietqr;
+
+const IMPORT_SELECT: &str = "SELECT i.id, COALESCE(i.filename, ''), i.imported_by, COALESCE(i.row_count, 0),
+        COUNT(CASE WHEN t.status = 'proposed' THEN 1 END),
+        COUNT(CASE WHEN t.status = 'unmatched' THEN 1 END),
+        COUNT(CASE WHEN t.status = 'duplicate' THEN 1 END),
+        COUNT(CASE WHEN t.status = 'confirmed' THEN 1 END),
+        COUNT(CASE WHEN t.status = 'ignored' THEN 1 END),
+        i.created_at
+    FROM bank_imports i LEFT JOIN bank_transactions t ON t.import_id = i.id";
+
+fn import_from_row(row: &rusqlite::Row) -> rusqlite::Result<BankImport> {
+    Ok(BankImport {
+        id: row.get(0)?,
+        filename: row.get(1)?,
+        imported_by: row.get(2)?,
+        row_count: row.get(3)?,
+        proposed_count: row.get(4)?,
+        unmatched_count: row.get(5)?,
+        duplicate_count: row.get(6)?,
+        confirmed_count: row.get(7)?,
+        ignored_count: row.get(8)?,
+        created_at: row.get(9)?,
+        transactions: None,
+    })
+}
+
+fn load_transactions(conn: &Connection, import_id: &str) -> rusqlite::Result<Vec<BankTransaction>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.import_id, t.row_number, t.transaction_date, t.amount, COALESCE(t.memo, ''),
+                COALESCE(t.reference, ''), t.order_id, o.order_number, o.customer_name,
+                COALESCE(t.confidence, 0), COALESCE(t.match_reason, ''), t.expected_amount,
+                COALESCE(t.amount_status, ''), t.status, t.payment_id, t.created_at
+         FROM bank_transactions t LEFT JOIN orders o ON o.id = t.order_id
+         WHERE t.import_id = ?1 ORDER BY t.row_number ASC",
+    )?;
+    let transactions = stmt
+        .query_map(params![import_id], |row| {
+            Ok(BankTransaction {
+                id: row.get(0)?,
+                import_id: row.get(1)?,
+                row_number: row.get(2)?,
+                transaction_date: row.get(3)?,
+                amount: row.get(4)?,
+                memo: row.get(5)?,
+                reference: row.get(6)?,
+                order_id: row.get(7)?,
+                order_number: row.get(8)?,
+                customer_name: row.get(9)?,
+                confidence: row.get(10)?,
+                match_reason: row.get(11)?,
+                expected_amount: row.get(12)?,
+                amount_status: row.get(13)?,
+                status: row.get(14)?,
+                payment_id: row.get(15)?,
+                created_at: row.get(16)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(transactions)
+}
+
+fn load_import(conn: &Connection, id: &str) -> rusqlite::Result<BankImport> {
+    let mut import = conn.query_row(
+        &format!("{} WHERE i.id = ?1 GROUP BY i.id", IMPORT_SELECT),
+        params![id],
+        import_from_row,
+    )?;
+    import.transactions = Some(load_transactions(conn, id)?);
+    Ok(import)
+}
+
+/// A transfer of the import that is still waiting to be confirmed or ignored
+struct OpenTransaction {
+    date: String,
+    amount: i64,
+    memo: String,
+    reference: String,
+    order_id: Option<String>,
+}
+
+fn open_transaction(
+    conn: &Connection,
+    import_id: &str,
+    transaction_id: &str,
+) -> Result<OpenTransaction, (StatusCode, Json<ApiResponse<()>>)> {
+    let found = conn.query_row(
+        "SELECT status, transaction_date, amount, COALESCE(memo, ''), COALESCE(reference, ''), order_id
+         FROM bank_transactions WHERE id = ?1 AND import_id = ?2",
+        params![transaction_id, import_id],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                OpenTransaction {
+                    date: row.get(1)?,
+                    amount: row.get(2)?,
+                    memo: row.get(3)?,
+                    reference: row.get(4)?,
+                    order_id: row.get(5)?,
+                },
+            ))
+        },
+    ).optional().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let Some((status, transaction)) = found else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(&format!("Transaction {} not found in this import", transaction_id))),
+        ));
+    };
+    if status == "confirmed" || status == "ignored" {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(&format!("Transaction {} is already {}", transaction_id, status))),
+        ));
+    }
+    Ok(transaction)
+}
+
+/// GET /api/bank-imports - Uploaded statements, newest first (auth required)
+pub async fn list_bank_imports(
+    State(db): State<DbPool>,
+) -> Result<Json<ApiResponse<Vec<BankImport>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(&format!("{} GROUP BY i.id ORDER BY i.created_at DESC", IMPORT_SELECT)).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let imports: Vec<BankImport> = stmt
+        .query_map([], import_from_row)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(ApiResponse::success(imports)))
+}
+
+/// GET /api/bank-imports/:id - Statement with its transfers and proposed matches (auth required)
+pub async fn get_bank_import(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<BankImport>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    match load_import(&conn, &id) {
+        Ok(import) => Ok(Json(ApiResponse::success(import))),
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Bank import not found"))))
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))),
+    }
+}
+
+/// POST /api/bank-imports - Upload a CSV statement export (multipart field "file") (auth required)
+///
+/// Incoming transfers are matched to open orders by the order number,
+/// tracking code or phone number in their memo. Nothing is recorded until
+/// the matches are confirmed through the reconcile endpoint.
+pub async fn import_bank_statement(
+    State(db): State<DbPool>,
+    Extension(claims): Extension<Claims>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<ApiResponse<BankImport>>), (StatusCode, Json<ApiResponse<()>>)> {
+    let mut file: Option<(String, Vec<u8>)> = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let filename = field.file_name().unwrap_or("").to_string();
+        let data = field.bytes().await.map_err(|e| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        file = Some((filename, data.to_vec()));
+        break;
+    }
+    let Some((filename, data)) = file else {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Missing statement file in field \"file\""))));
+    };
+    if data.len() > reconciliation::MAX_FILE_SIZE {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Statement file is larger than 5 MB"))));
+    }
+
+    let rows = reconciliation::parse_statement(&data).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+
+    let mut conn = db.lock().unwrap();
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let candidates = reconciliation::load_candidates(&tx).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let deposit_percent = vietqr::deposit_percent(&tx);
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    tx.execute(
+        "INSERT INTO bank_imports (id, filename, imported_by, row_count, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, filename, claims.username, rows.len() as i64, now],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    for row in &rows {
+        let fingerprint = row.fingerprint();
+        let seen_before: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM bank_transactions WHERE fingerprint = ?1 AND import_id != ?2",
+            params![fingerprint, id],
+            |r| r.get(0),
+        ).unwrap_or(false);
+        let proposal = reconciliation::propose(row, &candidates, deposit_percent);
+        let status = if seen_before {
+            "duplicate"
+        } else if proposal.is_some() {
+            "proposed"
+        } else {
+            "unmatched"
+        };
+
+        tx.execute(
+            "INSERT INTO bank_transactions (id, import_id, row_number, transaction_date, amount, memo, reference,
+                fingerprint, order_id, confidence, match_reason, expected_amount, amount_status, status, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                Uuid::new_v4().to_string(),
+                id,
+                row.row_number,
+                row.date,
+                row.amount,
+                row.memo,
+                row.reference,
+                fingerprint,
+                proposal.as_ref().map(|p| p.order_id.clone()),
+                proposal.as_ref().map(|p| p.confidence).unwrap_or(0),
+                proposal.as_ref().map(|p| p.reason.clone()).unwrap_or_default(),
+                proposal.as_ref().map(|p| p.expected_amount),
+                proposal.as_ref().map(|p| p.amount_status).unwrap_or(""),
+                status,
+                now
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let import = load_import(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(import))))
+}
+
+/// POST /api/bank-imports/:id/reconcile - Confirm matches as payments or ignore transfers (auth required)
+///
+/// A confirmed transfer becomes a bank_transfer payment on its order, dated
+/// on the statement day. Staff may confirm it against a different order than
+/// the one proposed, including unmatched and duplicate transfers.
+pub async fn reconcile_bank_import(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<ReconcileBankImport>,
+) -> Result<Json<ApiResponse<BankImport>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.confirm.is_empty() && payload.ignore.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Nothing to confirm or ignore"))));
+    }
+
+    let mut conn = db.lock().unwrap();
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM bank_imports WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Bank import not found"))));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    for confirm in &payload.confirm {
+        let open = open_transaction(&tx, &id, &confirm.transaction_id)?;
+        let Some(order_id) = confirm.order_id.clone().filter(|o| !o.is_empty()).or(open.order_id) else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(&format!(
+                    "Transaction {} has no proposed order; pass order_id",
+                    confirm.transaction_id
+                ))),
+            ));
+        };
+        let order_exists: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM orders WHERE id = ?1",
+            params![order_id],
+            |row| row.get(0),
+        ).unwrap_or(false);
+        if !order_exists {
+            return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(&format!("Order {} not found", order_id)))));
+        }
+        let paid_at = pricing::parse_datetime(&open.date, false).map(|d| d.to_rfc3339()).unwrap_or_else(|| now.clone());
+
+        let payment_id = Uuid::new_v4().to_string();
+        tx.execute(
+            "INSERT INTO payments (id, order_id, amount, method, reference, received_by, note, paid_at, created_at)
+             VALUES (?1, ?2, ?3, 'bank_transfer', ?4, ?5, ?6, ?7, ?8)",
+            params![payment_id, order_id, open.amount, open.reference, claims.username, open.memo, paid_at, now],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        tx.execute(
+            "UPDATE bank_transactions SET status = 'confirmed', order_id = ?1, payment_id = ?2 WHERE id = ?3",
+            params![order_id, payment_id, confirm.transaction_id],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        payments::refresh(&tx, &order_id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+
+    for transaction_id in &payload.ignore {
+        open_transaction(&tx, &id, transaction_id)?;
+        tx.execute(
+            "UPDATE bank_transactions SET status = 'ignored' WHERE id = ?1",
+            params![transaction_id],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let import = load_import(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(import)))
+}
@@ -0,0 +1,303 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use rusqlite::params;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::coupons::{self, COUPON_SELECT, DISCOUNT_TYPES};
+use crate::db::{DbPool, to_json_array};
+use crate::models::{
+    ApiResponse, Coupon, CreateCoupon, UpdateCoupon, ValidateCoupon, CouponValidation, CouponRedemption, OrderItemInput,
+};
+use crate::order_items;
+use crate::phone;
+use crate::pricing;
+
+fn validate_coupon_fields(
+    discount_type: Option<&str>,
+    discount_value: Option<i64>,
+    starts_at: Option<&str>,
+    ends_at: Option<&str>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if let Some(discount_type) = discount_type {
+        if !DISCOUNT_TYPES.contains(&discount_type) {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("discount_type must be 'percent' or 'fixed'"))));
+        }
+        if discount_type == "percent" && discount_value.is_some_and(|v| v > 100) {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Percentage discount cannot exceed 100"))));
+        }
+    }
+    if discount_value.is_some_and(|v| v <= 0) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("discount_value must be positive"))));
+    }
+    pricing::validate_window(starts_at, ends_at).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })
+}
+
+/// GET /api/coupons - List all coupons with usage counts (auth required)
+pub async fn list_coupons(
+    State(db): State<DbPool>,
+) -> Result<Json<ApiResponse<Vec<Coupon>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(&format!("{} ORDER BY c.created_at DESC", COUPON_SELECT)).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let coupons: Vec<Coupon> = stmt.query_map([], coupons::coupon_from_row).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(coupons)))
+}
+
+/// GET /api/coupons/:id - Get single coupon (auth required)
+pub async fn get_coupon(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Coupon>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let coupon = conn.query_row(
+        &format!("{} WHERE c.id = ?1", COUPON_SELECT),
+        params![id],
+        coupons::coupon_from_row,
+    ).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Coupon not found")))
+    })?;
+
+    Ok(Json(ApiResponse::success(coupon)))
+}
+
+/// POST /api/coupons - Create coupon (auth required)
+pub async fn create_coupon(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreateCoupon>,
+) -> Result<Json<ApiResponse<Coupon>>, (StatusCode, Json<ApiResponse<()>>)> {
+    validate_coupon_fields(
+        Some(&payload.discount_type),
+        Some(payload.discount_value),
+        payload.starts_at.as_deref(),
+        payload.ends_at.as_deref(),
+    )?;
+
+    let code = coupons::normalize_code(&payload.code);
+    if code.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Coupon code is required"))));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    {
+        let conn = db.lock().unwrap();
+        conn.execute(
+            "INSERT INTO coupons (id, code, description, discount_type, discount_value, max_discount, min_order_value,
+                product_ids, categories, usage_limit, per_phone_limit, starts_at, ends_at, active, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, NULLIF(?6, 0), ?7, ?8, ?9, NULLIF(?10, 0), NULLIF(?11, 0), NULLIF(?12, ''), NULLIF(?13, ''), ?14, ?15)",
+            params![
+                id,
+                code,
+                payload.description,
+                payload.discount_type,
+                payload.discount_value,
+                payload.max_discount,
+                payload.min_order_value,
+                to_json_array(&payload.product_ids),
+                to_json_array(&payload.categories),
+                payload.usage_limit,
+                payload.per_phone_limit,
+                payload.starts_at,
+                payload.ends_at,
+                payload.active,
+                now
+            ],
+        ).map_err(|e| {
+            let status = if e.to_string().contains("UNIQUE") { StatusCode::CONFLICT } else { StatusCode::INTERNAL_SERVER_ERROR };
+            (status, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+
+    get_coupon(State(db), Path(id)).await
+}
+
+/// PUT /api/coupons/:id - Update coupon (auth required)
+pub async fn update_coupon(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateCoupon>,
+) -> Result<Json<ApiResponse<Coupon>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let now = Utc::now().to_rfc3339();
+    let code = payload.code.as_deref().map(coupons::normalize_code).filter(|c| !c.is_empty());
+    let product_ids = payload.product_ids.as_ref().map(|p| to_json_array(p));
+    let categories = payload.categories.as_ref().map(|c| to_json_array(c));
+
+    {
+        let conn = db.lock().unwrap();
+
+        // A discount change is checked against the stored type or value it
+        // keeps, so a percent coupon can't be raised past 100 by sending only the value
+        let (stored_type, stored_value): (String, i64) = conn.query_row(
+            "SELECT discount_type, discount_value FROM coupons WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Coupon not found")))
+        })?;
+        let changes_discount = payload.discount_type.is_some() || payload.discount_value.is_some();
+        validate_coupon_fields(
+            Some(payload.discount_type.as_deref().unwrap_or(&stored_type)).filter(|_| changes_discount),
+            Some(payload.discount_value.unwrap_or(stored_value)).filter(|_| changes_discount),
+            payload.starts_at.as_deref(),
+            payload.ends_at.as_deref(),
+        )?;
+
+        let rows = conn.execute(
+            "UPDATE coupons SET
+                code = COALESCE(?1, code),
+                description = COALESCE(?2, description),
+                discount_type = COALESCE(?3, discount_type),
+                discount_value = COALESCE(?4, discount_value),
+                max_discount = CASE WHEN ?5 IS NULL THEN max_discount ELSE NULLIF(?5, 0) END,
+                min_order_value = COALESCE(?6, min_order_value),
+                product_ids = COALESCE(?7, product_ids),
+                categories = COALESCE(?8, categories),
+                usage_limit = CASE WHEN ?9 IS NULL THEN usage_limit ELSE NULLIF(?9, 0) END,
+                per_phone_limit = CASE WHEN ?10 IS NULL THEN per_phone_limit ELSE NULLIF(?10, 0) END,
+                starts_at = CASE WHEN ?11 IS NULL THEN starts_at ELSE NULLIF(?11, '') END,
+                ends_at = CASE WHEN ?12 IS NULL THEN ends_at ELSE NULLIF(?12, '') END,
+                active = COALESCE(?13, active),
+                updated_at = ?14
+             WHERE id = ?15",
+            params![
+                code,
+                payload.description,
+                payload.discount_type,
+                payload.discount_value,
+                payload.max_discount,
+                payload.min_order_value,
+                product_ids,
+                categories,
+                payload.usage_limit,
+                payload.per_phone_limit,
+                payload.starts_at,
+                payload.ends_at,
+                payload.active,
+                now,
+                id
+            ],
+        ).map_err(|e| {
+            let status = if e.to_string().contains("UNIQUE") { StatusCode::CONFLICT } else { StatusCode::INTERNAL_SERVER_ERROR };
+            (status, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+
+        if rows == 0 {
+            return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Coupon not found"))));
+        }
+    }
+
+    get_coupon(State(db), Path(id)).await
+}
+
+/// DELETE /api/coupons/:id - Delete coupon and its redemption records (auth required)
+pub async fn delete_coupon(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let rows = conn.execute("DELETE FROM coupons WHERE id = ?1", params![id])
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Coupon not found"))));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Coupon deleted".to_string()),
+    }))
+}
+
+/// GET /api/coupons/:id/redemptions - Orders that used a coupon (auth required)
+pub async fn list_coupon_redemptions(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<CouponRedemption>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.coupon_id, r.order_id, COALESCE(o.customer_name, ''), r.customer_phone, r.discount_amount, r.created_at
+         FROM coupon_redemptions r LEFT JOIN orders o ON o.id = r.order_id
+         WHERE r.coupon_id = ?1 ORDER BY r.created_at DESC"
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let redemptions: Vec<CouponRedemption> = stmt.query_map(params![id], |row| {
+        Ok(CouponRedemption {
+            id: row.get(0)?,
+            coupon_id: row.get(1)?,
+            order_id: row.get(2)?,
+            customer_name: row.get(3)?,
+            customer_phone: row.get(4)?,
+            discount_amount: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    }).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(redemptions)))
+}
+
+/// POST /api/coupons/validate - Check a code against a cart before ordering (public)
+pub async fn validate_coupon(
+    State(db): State<DbPool>,
+    Json(payload): Json<ValidateCoupon>,
+) -> Result<Json<ApiResponse<CouponValidation>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let inputs = if !payload.items.is_empty() {
+        payload.items
+    } else if let Some(product_id) = payload.product_id {
+        vec![OrderItemInput {
+            product_id,
+            variant: String::new(),
+            quantity: 1,
+            selected_options: payload.selected_options,
+            measurements: None,
+        }]
+    } else {
+        vec![]
+    };
+    let lines = if inputs.is_empty() {
+        vec![]
+    } else {
+        order_items::price_items(&conn, &inputs).map_err(|e| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+        })?
+    };
+    let subtotal: i64 = lines.iter().map(|l| l.item.line_total).sum();
+
+    let customer_phone = phone::normalize(&payload.customer_phone).unwrap_or_default();
+    let quote = coupons::evaluate(&conn, &payload.code, &customer_phone, &order_items::coupon_lines(&lines), Utc::now())
+        .map_err(|e| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+        })?;
+
+    Ok(Json(ApiResponse::success(CouponValidation {
+        code: quote.coupon.code,
+        description: quote.coupon.description,
+        subtotal,
+        discount_amount: quote.discount_amount,
+        total: subtotal - quote.discount_amount,
+    })))
+}
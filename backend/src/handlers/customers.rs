@@ -0,0 +1,285 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use rusqlite::params;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::customers::{self, CUSTOMER_SELECT};
+use crate::db::DbPool;
+use crate::handlers::orders::{order_from_row, ORDER_COLUMNS};
+use crate::models::{
+    ApiResponse, Customer, CreateCustomer, UpdateCustomer, CustomerListParams, MeasurementProfile,
+    SaveMeasurementProfile, Order, PaginatedResponse,
+};
+use crate::phone;
+
+fn normalize_phone(value: &str) -> Result<String, (StatusCode, Json<ApiResponse<()>>)> {
+    phone::normalize(value).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid phone number")))
+    })
+}
+
+fn conflict_or_internal(e: rusqlite::Error) -> (StatusCode, Json<ApiResponse<()>>) {
+    if e.to_string().contains("UNIQUE") {
+        (StatusCode::CONFLICT, Json(ApiResponse::<()>::error("A customer with this phone number already exists")))
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    }
+}
+
+/// GET /api/customers - Search customers by name, phone or email (auth required)
+pub async fn list_customers(
+    State(db): State<DbPool>,
+    Query(params): Query<CustomerListParams>,
+) -> Result<Json<ApiResponse<PaginatedResponse<Customer>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let q = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let pattern = q.map(|q| format!("%{}%", q));
+    // Phones are stored as +84...; "0912 345" should still find +84912345678
+    let digits = q
+        .map(|q| q.chars().filter(|c| c.is_ascii_digit()).collect::<String>())
+        .map(|d| d.strip_prefix('0').map(String::from).unwrap_or(d))
+        .filter(|d| !d.is_empty())
+        .map(|d| format!("%{}%", d));
+    let filter = "WHERE (?1 IS NULL OR c.name LIKE ?1 OR c.email LIKE ?1 OR c.phone LIKE ?2)";
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM customers c {}", filter),
+        params![pattern, digits],
+        |row| row.get(0),
+    ).unwrap_or(0);
+
+    let limit = params.limit.clamp(1, 100);
+    let offset = (params.page.max(1) - 1) * limit;
+    let mut stmt = conn.prepare(
+        &format!("{} {} ORDER BY c.created_at DESC LIMIT ?3 OFFSET ?4", CUSTOMER_SELECT, filter)
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let items: Vec<Customer> = stmt.query_map(params![pattern, digits, limit, offset], customers::customer_from_row)
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(PaginatedResponse {
+        items,
+        total,
+        page: params.page.max(1),
+        limit,
+        total_pages: ((total as f64) / (limit as f64)).ceil() as u32,
+    })))
+}
+
+/// GET /api/customers/:id - Customer with order history and measurement profiles (auth required)
+pub async fn get_customer(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Customer>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let mut customer = conn.query_row(
+        &format!("{} WHERE c.id = ?1", CUSTOMER_SELECT),
+        params![id],
+        customers::customer_from_row,
+    ).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Customer not found")))
+    })?;
+
+    let mut stmt = conn.prepare(
+        &format!("SELECT {} FROM orders WHERE customer_id = ?1 ORDER BY created_at DESC", ORDER_COLUMNS)
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let orders: Vec<Order> = stmt.query_map(params![id], order_from_row).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    customer.orders = Some(orders);
+    customer.measurement_profiles = Some(customers::load_profiles(&conn, &id));
+
+    Ok(Json(ApiResponse::success(customer)))
+}
+
+/// POST /api/customers - Create customer (auth required)
+pub async fn create_customer(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreateCustomer>,
+) -> Result<Json<ApiResponse<Customer>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let phone = normalize_phone(&payload.phone)?;
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    {
+        let conn = db.lock().unwrap();
+        conn.execute(
+            "INSERT INTO customers (id, phone, name, email, notes, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, phone, payload.name.trim(), payload.email.trim(), payload.notes, now],
+        ).map_err(conflict_or_internal)?;
+
+        // Earlier orders from this number that couldn't be linked yet
+        conn.execute(
+            "UPDATE orders SET customer_id = ?1 WHERE customer_id IS NULL AND customer_phone = ?2",
+            params![id, phone],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+
+    get_customer(State(db), Path(id)).await
+}
+
+/// PUT /api/customers/:id - Update customer (auth required)
+pub async fn update_customer(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateCustomer>,
+) -> Result<Json<ApiResponse<Customer>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let phone = payload.phone.as_deref().map(normalize_phone).transpose()?;
+    let now = Utc::now().to_rfc3339();
+
+    {
+        let conn = db.lock().unwrap();
+        let rows = conn.execute(
+            "UPDATE customers SET
+                phone = COALESCE(?1, phone),
+                name = COALESCE(?2, name),
+                email = COALESCE(?3, email),
+                notes = COALESCE(?4, notes),
+                updated_at = ?5
+             WHERE id = ?6",
+            params![
+                phone,
+                payload.name.as_deref().map(str::trim),
+                payload.email.as_deref().map(str::trim),
+                payload.notes,
+                now,
+                id
+            ],
+        ).map_err(conflict_or_internal)?;
+
+        if rows == 0 {
+            return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Customer not found"))));
+        }
+    }
+
+    get_customer(State(db), Path(id)).await
+}
+
+/// DELETE /api/customers/:id - Delete customer; their orders are kept, unlinked (auth required)
+pub async fn delete_customer(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let mut conn = db.lock().unwrap();
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    // Columns added by migration carry no foreign key, so unlink explicitly
+    tx.execute("UPDATE orders SET customer_id = NULL WHERE customer_id = ?1", params![id])
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    let rows = tx.execute("DELETE FROM customers WHERE id = ?1", params![id])
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Customer not found"))));
+    }
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Customer deleted".to_string()),
+    }))
+}
+
+/// POST /api/customers/:id/measurements - Save a measurement profile (auth required)
+pub async fn create_measurement_profile(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<SaveMeasurementProfile>,
+) -> Result<Json<ApiResponse<Vec<MeasurementProfile>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let name = payload.name.as_deref().map(str::trim).filter(|n| !n.is_empty()).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Profile name is required")))
+    })?;
+    let measurements = payload.measurements.ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Measurements are required")))
+    })?.into_measurements().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+
+    let conn = db.lock().unwrap();
+    let exists: bool = conn.query_row("SELECT COUNT(*) > 0 FROM customers WHERE id = ?1", params![id], |row| row.get(0))
+        .unwrap_or(false);
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Customer not found"))));
+    }
+
+    customers::save_profile(&conn, &id, name, &measurements).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    Ok(Json(ApiResponse::success(customers::load_profiles(&conn, &id))))
+}
+
+/// PUT /api/customers/:id/measurements/:profile_id - Update a measurement profile (auth required)
+pub async fn update_measurement_profile(
+    State(db): State<DbPool>,
+    Path((id, profile_id)): Path<(String, String)>,
+    Json(payload): Json<SaveMeasurementProfile>,
+) -> Result<Json<ApiResponse<Vec<MeasurementProfile>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let measurements = payload.measurements.map(|input| input.into_measurements()).transpose().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?.map(|m| m.to_json());
+    let name = payload.name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    let conn = db.lock().unwrap();
+    let rows = conn.execute(
+        "UPDATE customer_measurements SET
+            name = COALESCE(?1, name),
+            measurements = COALESCE(?2, measurements),
+            updated_at = ?3
+         WHERE id = ?4 AND customer_id = ?5",
+        params![name, measurements, Utc::now().to_rfc3339(), profile_id, id],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Measurement profile not found"))));
+    }
+
+    Ok(Json(ApiResponse::success(customers::load_profiles(&conn, &id))))
+}
+
+/// DELETE /api/customers/:id/measurements/:profile_id - Delete a measurement profile (auth required)
+pub async fn delete_measurement_profile(
+    State(db): State<DbPool>,
+    Path((id, profile_id)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<Vec<MeasurementProfile>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let rows = conn.execute(
+        "DELETE FROM customer_measurements WHERE id = ?1 AND customer_id = ?2",
+        params![profile_id, id],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Measurement profile not found"))));
+    }
+
+    Ok(Json(ApiResponse::success(customers::load_profiles(&conn, &id))))
+}
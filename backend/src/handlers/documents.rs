@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::db::DbPool;
+use crate::documents::{self, Shop};
+use crate::handlers::orders::load_order;
+use crate::models::{ApiResponse, Order};
+use crate::vietqr;
+
+fn pdf_response(order: &Order, kind: &str, data: Vec<u8>) -> Response {
+    let number = order.order_number.clone().unwrap_or_else(|| order.id.clone());
+    let disposition = format!("inline; filename=\"{}-{}.pdf\"", number, kind);
+    ([(header::CONTENT_TYPE, "application/pdf".to_string()), (header::CONTENT_DISPOSITION, disposition)], data).into_response()
+}
+
+/// GET /api/orders/:id/invoice - Customer invoice, or receipt once paid, as PDF (auth required)
+pub async fn order_invoice(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)> {
+    let (order, shop, account) = {
+        let conn = db.lock().unwrap();
+        let order = load_order(&conn, &id).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+        })?;
+        (order, Shop::load(&conn), vietqr::load_account(&conn))
+    };
+
+    let data = documents::invoice(&order, &shop, account.as_ref()).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    Ok(pdf_response(&order, "hoa-don", data))
+}
+
+/// GET /api/orders/:id/work-ticket - Workshop ticket with measurements and a QR code, as PDF (auth required)
+pub async fn order_work_ticket(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)> {
+    let (order, shop) = {
+        let conn = db.lock().unwrap();
+        let order = load_order(&conn, &id).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+        })?;
+        (order, Shop::load(&conn))
+    };
+
+    let data = documents::work_ticket(&order, &shop).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    Ok(pdf_response(&order, "phieu-may", data))
+}
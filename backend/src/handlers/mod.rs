@@ -4,6 +4,16 @@ pub mod settings;
 pub mod auth;
 pub mod upload;
 pub mod categories;
+pub mod promotions;
+pub mod coupons;
+pub mod customers;
+pub mod tracking;
+pub mod appointments;
+pub mod rentals;
+pub mod payments;
+pub mod bank_imports;
+pub mod documents;
+pub mod workshop;
 
 pub use products::*;
 pub use orders::*;
@@ -11,3 +21,13 @@ pub use settings::*;
 pub use auth::*;
 pub use upload::*;
 pub use categories::*;
+pub use promotions::*;
+pub use coupons::*;
+pub use customers::*;
+pub use tracking::*;
+pub use appointments::*;
+pub use rentals::*;
+pub use payments::*;
+pub use bank_imports::*;
+pub use documents::*;
+pub use workshop::*;
@@ -1,59 +1,137 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    Json,
+    body::Body,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
-use rusqlite::params;
+use std::net::SocketAddr;
+use rusqlite::{params, params_from_iter, Connection};
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 
+use crate::coupons;
+use crate::customers;
 use crate::db::DbPool;
-use crate::models::{ApiResponse, Order, CreateOrder, UpdateOrder, PaginationParams, PaginatedResponse};
+use crate::handlers::auth;
+use crate::idempotency;
+use crate::measurements::Measurements;
+use crate::models::{
+    ApiResponse, Claims, Order, CreateOrder, UpdateOrder, OrderItemInput, OrderTransition, OrderStatusInfo,
+    OrderWearer, UpdateWearer, OrderListParams, OrderExportParams, FormToken, PaginatedResponse,
+};
+use crate::order_export;
+use crate::order_filters;
+use crate::order_items;
+use crate::order_numbers;
+use crate::order_status::{self, TransitionError};
+use crate::payments;
+use crate::phone;
+use crate::pricing;
+use crate::spam;
+use crate::wearers;
+
+pub(crate) const ORDER_COLUMNS: &str = "id, customer_name, customer_phone, customer_email, product_id, product_name, \
+     measurements, notes, status, subtotal, discount_amount, total, coupon_code, delivery_date, created_at, updated_at, customer_id, \
+     fitting_date, tracking_code, order_number, suspected_spam, spam_reasons, paid_amount, payment_status, assigned_to";
+
+/// Map a row selected with `ORDER_COLUMNS`
+pub(crate) fn order_from_row(row: &rusqlite::Row) -> rusqlite::Result<Order> {
+    let order_number: Option<String> = row.get(19)?;
+    let total = row.get::<_, Option<i64>>(11)?.unwrap_or(0);
+    let paid_amount = row.get::<_, Option<i64>>(22)?.unwrap_or(0);
+    Ok(Order {
+        id: row.get(0)?,
+        transfer_memo: order_number.as_deref().map(order_numbers::transfer_memo),
+        order_number,
+        customer_id: row.get(16)?,
+        customer_name: row.get(1)?,
+        customer_phone: row.get(2)?,
+        customer_email: row.get(3)?,
+        product_id: row.get(4).ok(),
+        product_name: row.get(5)?,
+        measurements: Measurements::from_stored(&row.get::<_, Option<String>>(6)?.unwrap_or_default()),
+        notes: row.get(7)?,
+        status: row.get(8)?,
+        subtotal: row.get::<_, Option<i64>>(9)?.unwrap_or(0),
+        discount_amount: row.get::<_, Option<i64>>(10)?.unwrap_or(0),
+        total,
+        coupon_code: row.get(12)?,
+        paid_amount,
+        balance_due: total - paid_amount,
+        payment_status: row.get::<_, Option<String>>(23)?.unwrap_or_else(|| "unpaid".to_string()),
+        payments: None,
+        items: vec![],
+        delivery_date: row.get(13)?,
+        fitting_date: row.get(17)?,
+        tracking_code: row.get(18)?,
+        assigned_to: row.get(24)?,
+        wearers: vec![],
+        progress: None,
+        status_history: None,
+        next_statuses: None,
+        suspected_spam: row.get::<_, Option<bool>>(20)?.unwrap_or(false),
+        spam_reasons: row.get::<_, Option<String>>(21)?
+            .map(|r| r.split(',').filter(|r| !r.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        created_at: row.get(14)?,
+        updated_at: row.get(15).ok(),
+    })
+}
+
+/// Attach items, wearers and group progress to an order read with `order_from_row`
+pub(crate) fn load_lines(conn: &Connection, order: &mut Order) {
+    order.items = order_items::load_items(conn, &order.id);
+    order.wearers = wearers::load_wearers(conn, &order.id);
+    if !order.wearers.is_empty() {
+        order.progress = Some(wearers::progress(&order.wearers, &order.items));
+    }
+}
+
+fn validate_date(field: &str, date: Option<&str>) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if let Some(date) = date.filter(|d| !d.is_empty()) {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("{} must be YYYY-MM-DD", field))))
+        })?;
+    }
+    Ok(())
+}
 
 /// GET /api/orders - List all orders (auth required)
 pub async fn list_orders(
     State(db): State<DbPool>,
-    Query(params): Query<PaginationParams>,
+    Query(params): Query<OrderListParams>,
 ) -> Result<Json<ApiResponse<PaginatedResponse<Order>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let filter = order_filters::build(&params).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    let order_by = order_filters::order_by(&params).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
     let conn = db.lock().unwrap();
     
-    let mut sql = "SELECT id, customer_name, customer_phone, customer_email, product_id, product_name, measurements, notes, status, created_at, updated_at FROM orders WHERE 1=1".to_string();
-    let mut count_sql = "SELECT COUNT(*) FROM orders WHERE 1=1".to_string();
-    
-    if let Some(ref status) = params.status {
-        sql.push_str(&format!(" AND status = '{}'", status));
-        count_sql.push_str(&format!(" AND status = '{}'", status));
-    }
-    
-    sql.push_str(" ORDER BY created_at DESC");
-    
     let offset = (params.page - 1) * params.limit;
-    sql.push_str(&format!(" LIMIT {} OFFSET {}", params.limit, offset));
+    let sql = format!(
+        "SELECT {} FROM orders {} {} LIMIT {} OFFSET {}",
+        ORDER_COLUMNS, filter.clause, order_by, params.limit, offset
+    );
+    let count_sql = format!("SELECT COUNT(*) FROM orders {}", filter.clause);
     
-    let total: i64 = conn.query_row(&count_sql, [], |row| row.get(0)).unwrap_or(0);
+    let total: i64 = conn.query_row(&count_sql, params_from_iter(&filter.params), |row| row.get(0)).unwrap_or(0);
     
     let mut stmt = conn.prepare(&sql).map_err(|e| {
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
     })?;
     
-    let orders: Vec<Order> = stmt.query_map([], |row| {
-        Ok(Order {
-            id: row.get(0)?,
-            customer_name: row.get(1)?,
-            customer_phone: row.get(2)?,
-            customer_email: row.get(3)?,
-            product_id: row.get(4).ok(),
-            product_name: row.get(5)?,
-            measurements: row.get(6)?,
-            notes: row.get(7)?,
-            status: row.get(8)?,
-            created_at: row.get(9)?,
-            updated_at: row.get(10).ok(),
-        })
-    }).map_err(|e| {
+    let mut orders: Vec<Order> = stmt.query_map(params_from_iter(&filter.params), order_from_row).map_err(|e| {
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
     })?.filter_map(|r| r.ok()).collect();
     
+    for order in &mut orders {
+        load_lines(&conn, order);
+    }
+    
     let total_pages = ((total as f64) / (params.limit as f64)).ceil() as u32;
     
     Ok(Json(ApiResponse::success(PaginatedResponse {
@@ -65,6 +143,120 @@ pub async fn list_orders(
     })))
 }
 
+/// An order with its lines, status history and the statuses it may move to
+pub(crate) fn load_order(conn: &Connection, id: &str) -> rusqlite::Result<Order> {
+    let mut order = conn.query_row(
+        &format!("SELECT {} FROM orders WHERE id = ?1", ORDER_COLUMNS),
+        params![id],
+        order_from_row,
+    )?;
+    load_lines(conn, &mut order);
+    order.status_history = Some(order_status::history(conn, &order.id));
+    order.next_statuses = Some(order_status::allowed_transitions(&order.status).into_iter().map(String::from).collect());
+    order.payments = Some(payments::load_payments(conn, &order.id));
+    Ok(order)
+}
+
+/// GET /api/orders/export - Every order matching the list filters as CSV or XLSX (auth required)
+///
+/// Orders are read in batches, releasing the database between them. CSV is
+/// streamed as it is written; XLSX is assembled in a temporary file first.
+pub async fn export_orders(
+    State(db): State<DbPool>,
+    Query(params): Query<OrderListParams>,
+    Query(export): Query<OrderExportParams>,
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)> {
+    let format = export.format.as_deref().unwrap_or("csv").to_string();
+    if !order_export::FORMATS.contains(&format.as_str()) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("format must be csv or xlsx"))));
+    }
+    let filter = order_filters::build(&params).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    let filename = format!(
+        "don-hang-{}.{}",
+        Utc::now().with_timezone(&pricing::shop_offset()).format("%Y%m%d-%H%M"),
+        format
+    );
+    let disposition = format!("attachment; filename=\"{}\"", filename);
+
+    if format == "xlsx" {
+        let file = tokio::task::spawn_blocking(move || -> Result<std::fs::File, String> {
+            let mut workbook = order_export::XlsxExport::new()?;
+            let mut cursor = None;
+            loop {
+                let rows = {
+                    let conn = db.lock().unwrap();
+                    order_export::fetch_batch(&conn, &filter, cursor.as_ref()).map_err(|e| e.to_string())?
+                };
+                workbook.append(&rows)?;
+                match rows.last() {
+                    Some(last) if rows.len() == order_export::BATCH_SIZE => cursor = Some(last.cursor()),
+                    _ => break,
+                }
+            }
+            workbook.finish()
+        })
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e))))?;
+
+        let body = Body::from_stream(ReaderStream::new(tokio::fs::File::from_std(file)));
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            body,
+        )
+            .into_response());
+    }
+
+    // Batches are handed to the response as they are read; a client that
+    // goes away closes the channel and stops the export
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Result<Vec<u8>, std::io::Error>>(4);
+    tokio::task::spawn_blocking(move || {
+        let mut cursor = None;
+        let mut first = true;
+        loop {
+            let rows = {
+                let conn = db.lock().unwrap();
+                order_export::fetch_batch(&conn, &filter, cursor.as_ref())
+            };
+            let chunk = rows
+                .map_err(|e| e.to_string())
+                .and_then(|rows| order_export::csv_chunk(&rows, first).map(|chunk| (rows, chunk)));
+            let (rows, chunk) = match chunk {
+                Ok(batch) => batch,
+                Err(e) => {
+                    let _ = sender.blocking_send(Err(std::io::Error::other(e)));
+                    return;
+                }
+            };
+            first = false;
+            if sender.blocking_send(Ok(chunk)).is_err() {
+                return;
+            }
+            match rows.last() {
+                Some(last) if rows.len() == order_export::BATCH_SIZE => cursor = Some(last.cursor()),
+                _ => return,
+            }
+        }
+    });
+    let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
 /// GET /api/orders/:id - Get single order (auth required)
 pub async fn get_order(
     State(db): State<DbPool>,
@@ -72,117 +264,523 @@ pub async fn get_order(
 ) -> Result<Json<ApiResponse<Order>>, (StatusCode, Json<ApiResponse<()>>)> {
     let conn = db.lock().unwrap();
     
-    let order = conn.query_row(
-        "SELECT id, customer_name, customer_phone, customer_email, product_id, product_name, measurements, notes, status, created_at, updated_at 
-         FROM orders WHERE id = ?1",
-        params![id],
-        |row| {
-            Ok(Order {
-                id: row.get(0)?,
-                customer_name: row.get(1)?,
-                customer_phone: row.get(2)?,
-                customer_email: row.get(3)?,
-                product_id: row.get(4).ok(),
-                product_name: row.get(5)?,
-                measurements: row.get(6)?,
-                notes: row.get(7)?,
-                status: row.get(8)?,
-                created_at: row.get(9)?,
-                updated_at: row.get(10).ok(),
-            })
-        }
-    ).map_err(|_| {
+    let order = load_order(&conn, &id).map_err(|_| {
         (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
     })?;
     
     Ok(Json(ApiResponse::success(order)))
 }
 
+/// GET /api/orders/form-token - Signed token for the storefront order form (public)
+pub async fn order_form_token() -> Json<ApiResponse<FormToken>> {
+    Json(ApiResponse::success(spam::issue_token(Utc::now())))
+}
+
 /// POST /api/orders - Create order (public)
+///
+/// Lines come from `items`, or from the single `product_id` older
+/// storefronts send. Each line is priced from the product's current
+/// effective price plus its options, and a coupon discount is split across
+/// the lines it applies to.
+///
+/// Storefront submissions go through the spam checks; orders entered by
+/// signed-in staff skip them.
+///
+/// A retry carrying the same `Idempotency-Key` header gets the original
+/// response instead of a second order; reusing a key for a different
+/// request is refused with 422.
 pub async fn create_order(
     State(db): State<DbPool>,
-    Json(payload): Json<CreateOrder>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<serde_json::Value>,
 ) -> Result<Json<ApiResponse<Order>>, (StatusCode, Json<ApiResponse<()>>)> {
-    let conn = db.lock().unwrap();
+    let idempotency_key = idempotency::key_from(&headers).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(e)))
+    })?;
+    let request_hash = idempotency::request_hash(&body);
+    let payload: CreateOrder = serde_json::from_value(body).map_err(|e| {
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
     
-    let id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
+    let phone = phone::normalize(&payload.customer_phone).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid phone number")))
+    })?;
+    let mut measurements = match payload.measurements {
+        Some(input) => input.into_measurements().map_err(|e| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+        })?,
+        None => Measurements::default(),
+    };
+    
+    validate_date("delivery_date", payload.delivery_date.as_deref())?;
     
-    // Get product name if product_id is provided
-    let product_name = if let Some(ref pid) = payload.product_id {
-        conn.query_row("SELECT name FROM products WHERE id = ?1", params![pid], |row| row.get(0))
-            .unwrap_or(payload.product_name.clone())
+    // Each wearer gets their own line, ahead of any shared items
+    let mut wearers = vec![];
+    let mut wearer_inputs = vec![];
+    let mut wearer_profiles = vec![];
+    for wearer in payload.wearers {
+        let name = wearer.name.trim().to_string();
+        if name.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Every wearer needs a name"))));
+        }
+        let measurements = match wearer.measurements {
+            Some(input) => input.into_measurements().map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("{}: {}", name, e))))
+            })?,
+            None => Measurements::default(),
+        };
+        wearers.push(OrderWearer {
+            id: Uuid::new_v4().to_string(),
+            name,
+            role: wearer.role.trim().to_string(),
+            measurements,
+            fitting_status: "pending".to_string(),
+            notes: wearer.notes,
+            updated_at: None,
+        });
+        wearer_profiles.push(wearer.measurement_profile_id);
+        wearer_inputs.push(OrderItemInput {
+            product_id: wearer.product_id,
+            variant: wearer.variant,
+            quantity: wearer.quantity,
+            selected_options: wearer.selected_options,
+            measurements: None,
+        });
+    }
+    
+    let inputs = if !wearer_inputs.is_empty() || !payload.items.is_empty() {
+        wearer_inputs.into_iter().chain(payload.items).collect()
+    } else if let Some(product_id) = payload.product_id.filter(|p| !p.is_empty()) {
+        vec![OrderItemInput {
+            product_id,
+            variant: String::new(),
+            quantity: 1,
+            selected_options: payload.selected_options,
+            measurements: None,
+        }]
     } else {
-        payload.product_name.clone()
+        vec![]
     };
     
-    conn.execute(
-        "INSERT INTO orders (id, customer_name, customer_phone, customer_email, product_id, product_name, measurements, notes, status, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'pending', ?9)",
-        params![id, payload.customer_name, payload.customer_phone, payload.customer_email, payload.product_id, product_name, payload.measurements, payload.notes, now],
-    ).map_err(|e| {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
-    })?;
+    let ip = spam::client_ip(&headers, peer);
+    let from_staff = auth::optional_claims(&headers).is_some();
+    
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let created_at = now.to_rfc3339();
     
-    let order = Order {
-        id,
-        customer_name: payload.customer_name,
-        customer_phone: payload.customer_phone,
-        customer_email: payload.customer_email,
-        product_id: payload.product_id,
-        product_name,
-        measurements: payload.measurements,
-        notes: payload.notes,
-        status: "pending".to_string(),
-        created_at: now,
-        updated_at: None,
+    let order = {
+        let mut conn = db.lock().unwrap();
+        
+        // Checked under the lock the order is created under, so two
+        // simultaneous retries can't both get through
+        if let Some(ref key) = idempotency_key {
+            let lookup = idempotency::lookup(&conn, key, &request_hash, now).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+            match lookup {
+                idempotency::Lookup::Replay(order) => return Ok(Json(ApiResponse::success(*order))),
+                idempotency::Lookup::Mismatch => {
+                    return Err((
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        Json(ApiResponse::<()>::error("Idempotency-Key was already used for a different order")),
+                    ));
+                }
+                idempotency::Lookup::New => {}
+            }
+        }
+        
+        let submission = (!from_staff).then(|| spam::Submission {
+            ip: &ip,
+            phone: &phone,
+            fingerprint: spam::fingerprint(&phone, &inputs, &payload.product_name),
+            honeypot: &payload.website,
+            form_token: payload.form_token.as_deref(),
+            require_token: spam::token_required(&conn),
+        });
+        let verdict = match submission {
+            Some(ref submission) => Some(spam::check(&conn, submission, now).map_err(|rejection| match rejection {
+                spam::Rejection::RateLimited => (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(ApiResponse::<()>::error("Too many orders, please try again in a few minutes or call the shop")),
+                ),
+                spam::Rejection::Duplicate(order_number) => (
+                    StatusCode::CONFLICT,
+                    Json(ApiResponse::<()>::error(&format!("This order was already received as {}", order_number))),
+                ),
+                spam::Rejection::InvalidToken(message) => {
+                    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message)))
+                }
+            })?),
+            None => None,
+        };
+        let spam_reasons = verdict.as_ref().map(|v| v.reasons.join(",")).unwrap_or_default();
+        
+        // Saved profiles fill in measurements that weren't sent
+        let profile = |profile_id: &Option<String>| match profile_id.as_deref().filter(|p| !p.is_empty()) {
+            Some(profile_id) => customers::profile_measurements(&conn, &phone, profile_id).map(Some).ok_or_else(|| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Measurement profile not found")))
+            }),
+            None => Ok(None),
+        };
+        if measurements.is_empty() {
+            if let Some(saved) = profile(&payload.measurement_profile_id)? {
+                measurements = saved;
+            }
+        }
+        for (wearer, profile_id) in wearers.iter_mut().zip(&wearer_profiles) {
+            if wearer.measurements.is_empty() {
+                if let Some(saved) = profile(profile_id)? {
+                    wearer.measurements = saved;
+                }
+            }
+            if !wearer.measurements.is_empty() {
+                wearer.fitting_status = "measured".to_string();
+            }
+        }
+        
+        // Enquiries without a product keep only the name the customer typed
+        let mut lines = if inputs.is_empty() {
+            vec![]
+        } else {
+            order_items::price_items(&conn, &inputs).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?
+        };
+        for (line, wearer) in lines.iter_mut().zip(&wearers) {
+            line.item.wearer_id = Some(wearer.id.clone());
+        }
+        let subtotal: i64 = lines.iter().map(|l| l.item.line_total).sum();
+        let product_id = lines.first().and_then(|l| l.item.product_id.clone());
+        let product_name = if lines.is_empty() { payload.product_name.clone() } else { order_items::summary_name(&lines) };
+        
+        let quote = match payload.coupon_code.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            Some(code) => {
+                let quote = coupons::evaluate(&conn, code, &phone, &order_items::coupon_lines(&lines), now)
+                    .map_err(|e| {
+                        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+                    })?;
+                for (line, discount) in lines.iter_mut().zip(&quote.line_discounts) {
+                    line.item.discount_amount = *discount;
+                }
+                Some(quote)
+            }
+            None => None,
+        };
+        let discount_amount = quote.as_ref().map(|q| q.discount_amount).unwrap_or(0);
+        
+        let tx = conn.transaction().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        let customer_id = customers::upsert_by_phone(&tx, &phone, &payload.customer_name, &payload.customer_email)
+            .map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+        if let Some(name) = payload.save_measurements_as.as_deref().filter(|n| !n.trim().is_empty()) {
+            if !measurements.is_empty() {
+                customers::save_profile(&tx, &customer_id, name, &measurements).map_err(|e| {
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+                })?;
+            }
+        }
+        
+        let order_number = order_numbers::next(&tx, now).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        tx.execute(
+            "INSERT INTO orders (id, customer_id, order_number, customer_name, customer_phone, customer_email, product_id, product_name, measurements, notes, status,
+                subtotal, discount_amount, total, coupon_code, delivery_date, tracking_code, suspected_spam, spam_reasons, created_at, customer_name_folded)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'pending', ?11, ?12, ?13, ?14, NULLIF(?15, ''), ?16, ?17, ?18, ?19, ?20)",
+            params![
+                id,
+                customer_id,
+                order_number,
+                payload.customer_name,
+                phone,
+                payload.customer_email,
+                product_id,
+                product_name,
+                measurements.to_json(),
+                payload.notes,
+                subtotal,
+                discount_amount,
+                subtotal - discount_amount,
+                quote.as_ref().map(|q| q.coupon.code.clone()),
+                payload.delivery_date,
+                Uuid::new_v4().simple().to_string(),
+                !spam_reasons.is_empty(),
+                spam_reasons,
+                created_at,
+                order_filters::fold_name(&payload.customer_name)
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        if let (Some(submission), Some(verdict)) = (&submission, &verdict) {
+            spam::record(&tx, submission, verdict, &id, now).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+        }
+        
+        wearers::save_wearers(&tx, &id, &wearers).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        order_items::save_items(&tx, &id, &lines).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        order_status::record(&tx, &id, None, "pending", "customer", "").map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        if let Some(ref quote) = quote {
+            tx.execute(
+                "INSERT INTO coupon_redemptions (id, coupon_id, order_id, customer_phone, discount_amount, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![Uuid::new_v4().to_string(), quote.coupon.id, id, phone, discount_amount, created_at],
+            ).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+        }
+        
+        let order = load_order(&tx, &id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        if let Some(ref key) = idempotency_key {
+            idempotency::save(&tx, key, &request_hash, &order, now).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+        }
+        
+        tx.commit().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        order
     };
     
     Ok(Json(ApiResponse::success(order)))
 }
 
 /// PUT /api/orders/:id - Update order (auth required)
+///
+/// A `status` change goes through the same transition rules as
+/// `POST /api/orders/:id/status`.
 pub async fn update_order(
     State(db): State<DbPool>,
     Path(id): Path<String>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<UpdateOrder>,
 ) -> Result<Json<ApiResponse<Order>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let measurements = payload.measurements.map(|input| input.into_measurements()).transpose().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?.map(|m| m.to_json());
+    validate_date("delivery_date", payload.delivery_date.as_deref())?;
+    validate_date("fitting_date", payload.fitting_date.as_deref())?;
+    // The product lives on the order's lines; rewriting the summary columns
+    // would leave the lines, totals and invoice saying something else
+    if payload.product_id.is_some() || payload.product_name.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("The products of an order can't be changed; cancel it and place a new one")),
+        ));
+    }
+    let phone = payload.customer_phone.as_deref().map(|p| {
+        phone::normalize(p).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid phone number")))
+        })
+    }).transpose()?;
     let now = Utc::now().to_rfc3339();
     
     {
-        let conn = db.lock().unwrap();
-        conn.execute(
+        let mut conn = db.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        // A new phone number moves the order to that number's customer, who
+        // gets the order's name and email unless new ones were sent
+        let customer_id = match phone {
+            Some(ref phone) => {
+                let (stored_name, stored_email): (String, String) = tx.query_row(
+                    "SELECT customer_name, COALESCE(customer_email, '') FROM orders WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                ).map_err(|_| {
+                    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+                })?;
+                Some(customers::upsert_by_phone(
+                    &tx,
+                    phone,
+                    payload.customer_name.as_deref().unwrap_or(&stored_name),
+                    payload.customer_email.as_deref().unwrap_or(&stored_email),
+                ).map_err(|e| {
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+                })?)
+            }
+            None => None,
+        };
+        
+        if let Some(username) = payload.assigned_to.as_deref().filter(|u| !u.is_empty()) {
+            let known: bool = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM admin_users WHERE username = ?1",
+                params![username],
+                |row| row.get(0),
+            ).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+            if !known {
+                return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("assigned_to must be a staff username"))));
+            }
+        }
+        
+        let rows = tx.execute(
             "UPDATE orders SET 
                 customer_name = COALESCE(?1, customer_name),
+                customer_name_folded = COALESCE(?12, customer_name_folded),
                 customer_phone = COALESCE(?2, customer_phone),
                 customer_email = COALESCE(?3, customer_email),
-                product_id = COALESCE(?4, product_id),
-                product_name = COALESCE(?5, product_name),
-                measurements = COALESCE(?6, measurements),
-                notes = COALESCE(?7, notes),
-                status = COALESCE(?8, status),
-                updated_at = ?9
-             WHERE id = ?10",
+                measurements = COALESCE(?4, measurements),
+                notes = COALESCE(?5, notes),
+                delivery_date = CASE WHEN ?6 IS NULL THEN delivery_date ELSE NULLIF(?6, '') END,
+                customer_id = COALESCE(?9, customer_id),
+                fitting_date = CASE WHEN ?10 IS NULL THEN fitting_date ELSE NULLIF(?10, '') END,
+                suspected_spam = COALESCE(?11, suspected_spam),
+                assigned_to = CASE WHEN ?13 IS NULL THEN assigned_to ELSE NULLIF(?13, '') END,
+                updated_at = ?7
+             WHERE id = ?8",
             params![
                 payload.customer_name,
-                payload.customer_phone,
+                phone,
                 payload.customer_email,
-                payload.product_id,
-                payload.product_name,
-                payload.measurements,
+                measurements,
                 payload.notes,
-                payload.status,
+                payload.delivery_date,
                 now,
+                id,
+                customer_id,
+                payload.fitting_date,
+                payload.suspected_spam,
+                payload.customer_name.as_deref().map(order_filters::fold_name),
+                payload.assigned_to
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        
+        if rows == 0 {
+            return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found"))));
+        }
+        
+        if let Some(ref status) = payload.status {
+            let current: String = tx.query_row("SELECT status FROM orders WHERE id = ?1", params![id], |row| row.get(0))
+                .map_err(|e| {
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+                })?;
+            if current != *status {
+                order_status::transition(&tx, &id, status, &claims.username, "").map_err(transition_error)?;
+            }
+        }
+        
+        tx.commit().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    
+    get_order(State(db), Path(id)).await
+}
+
+/// POST /api/orders/:id/status - Move an order through its lifecycle (auth required)
+pub async fn transition_order(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<OrderTransition>,
+) -> Result<Json<ApiResponse<Order>>, (StatusCode, Json<ApiResponse<()>>)> {
+    {
+        let mut conn = db.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        order_status::transition(&tx, &id, payload.status.trim(), &claims.username, payload.note.trim())
+            .map_err(transition_error)?;
+        tx.commit().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    
+    get_order(State(db), Path(id)).await
+}
+
+/// PUT /api/orders/:id/wearers/:wearer_id - Update a group-order wearer (auth required)
+pub async fn update_wearer(
+    State(db): State<DbPool>,
+    Path((id, wearer_id)): Path<(String, String)>,
+    Json(payload): Json<UpdateWearer>,
+) -> Result<Json<ApiResponse<Order>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Some(ref status) = payload.fitting_status {
+        if !wearers::is_valid_status(status) {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("Unknown fitting status: {}", status)))));
+        }
+    }
+    if payload.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Wearer name cannot be empty"))));
+    }
+    let measurements = payload.measurements.map(|input| input.into_measurements()).transpose().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?.map(|m| m.to_json());
+    let now = Utc::now().to_rfc3339();
+    
+    {
+        let conn = db.lock().unwrap();
+        let rows = conn.execute(
+            "UPDATE order_wearers SET
+                name = COALESCE(?1, name),
+                role = COALESCE(?2, role),
+                measurements = COALESCE(?3, measurements),
+                fitting_status = COALESCE(?4, fitting_status),
+                notes = COALESCE(?5, notes),
+                updated_at = ?6
+             WHERE id = ?7 AND order_id = ?8",
+            params![
+                payload.name.as_deref().map(str::trim),
+                payload.role,
+                measurements,
+                payload.fitting_status,
+                payload.notes,
+                now,
+                wearer_id,
                 id
             ],
         ).map_err(|e| {
             (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
         })?;
+        
+        if rows == 0 {
+            return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Wearer not found"))));
+        }
     }
     
     get_order(State(db), Path(id)).await
 }
 
+/// GET /api/orders/statuses - Order lifecycle with allowed transitions (auth required)
+pub async fn list_order_statuses() -> Json<ApiResponse<Vec<OrderStatusInfo>>> {
+    Json(ApiResponse::success(order_status::lifecycle()))
+}
+
+fn transition_error(e: TransitionError) -> (StatusCode, Json<ApiResponse<()>>) {
+    match e {
+        TransitionError::NotFound => (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found"))),
+        TransitionError::Invalid(message) => (StatusCode::CONFLICT, Json(ApiResponse::<()>::error(&message))),
+        TransitionError::Database(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))),
+    }
+}
+
 /// DELETE /api/orders/:id - Delete order (auth required)
 pub async fn delete_order(
     State(db): State<DbPool>,
@@ -190,6 +788,18 @@ pub async fn delete_order(
 ) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
     let conn = db.lock().unwrap();
     
+    let has_payments: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM payments WHERE order_id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if has_payments {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("Order has payments; cancel it and record a refund instead")),
+        ));
+    }
+    
     let rows = conn.execute("DELETE FROM orders WHERE id = ?1", params![id])
         .map_err(|e| {
             (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::Utc;
+use rusqlite::params;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::handlers::orders::load_order;
+use crate::models::{ApiResponse, Claims, CreatePayment, Order, Payment};
+use crate::payments;
+use crate::pricing;
+
+/// GET /api/orders/:id/payments - Payment ledger of an order (auth required)
+pub async fn list_payments(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<Payment>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM orders WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found"))));
+    }
+
+    Ok(Json(ApiResponse::success(payments::load_payments(&conn, &id))))
+}
+
+/// POST /api/orders/:id/payments - Record a deposit, balance payment or refund (auth required)
+///
+/// Entries are never edited: a mistake is corrected with a refund entry.
+/// Refunds cannot exceed what was received.
+pub async fn record_payment(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreatePayment>,
+) -> Result<Json<ApiResponse<Order>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.amount == 0 {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("amount cannot be zero"))));
+    }
+    if !payments::is_valid_method(&payload.method) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("method must be cash, bank_transfer or e_wallet")),
+        ));
+    }
+    let now = Utc::now();
+    let paid_at = match payload.paid_at.as_deref().filter(|p| !p.trim().is_empty()) {
+        Some(paid_at) => pricing::parse_datetime(paid_at, false).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("paid_at must be RFC 3339 or YYYY-MM-DD")))
+        })?,
+        None => now,
+    };
+    if paid_at > now {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("paid_at cannot be in the future"))));
+    }
+
+    let mut conn = db.lock().unwrap();
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM orders WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found"))));
+    }
+    if payload.amount < 0 {
+        let paid = payments::paid_amount(&tx, &id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        if -payload.amount > paid {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(&format!("Cannot refund more than the {} VND received", paid))),
+            ));
+        }
+    }
+
+    tx.execute(
+        "INSERT INTO payments (id, order_id, amount, method, reference, received_by, note, paid_at, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            Uuid::new_v4().to_string(),
+            id,
+            payload.amount,
+            payload.method,
+            payload.reference.trim(),
+            claims.username,
+            payload.note.trim(),
+            paid_at.to_rfc3339(),
+            now.to_rfc3339()
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    payments::refresh(&tx, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let order = load_order(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(order)))
+}
@@ -4,11 +4,78 @@ use axum::{
     Json,
 };
 use rusqlite::params;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 use chrono::Utc;
 
-use crate::db::{DbPool, parse_json_array, to_json_array};
-use crate::models::{ApiResponse, Product, CreateProduct, UpdateProduct, PaginationParams, PaginatedResponse};
+use crate::bundles;
+use crate::db::{DbPool, to_json_array};
+use crate::models::{
+    ApiResponse, Product, CreateProduct, UpdateProduct, PaginationParams, PaginatedResponse,
+    RelatedProduct, RelatedParams, UpdatePairings, ProductOptionGroup, UpdateProductOptions,
+};
+use crate::options;
+use crate::pricing;
+use crate::products::{load_product, product_from_row, PRODUCT_COLUMNS};
+use crate::recommendations::{self, RelatedSignals};
+use crate::rentals;
+
+fn validate_sale(
+    sale_price: Option<i64>,
+    compare_at_price: Option<i64>,
+    sale_starts_at: Option<&str>,
+    sale_ends_at: Option<&str>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if sale_price.is_some_and(|p| p < 0) || compare_at_price.is_some_and(|p| p < 0) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Prices cannot be negative"))));
+    }
+    pricing::validate_window(sale_starts_at, sale_ends_at).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })
+}
+
+fn validate_bundle(
+    product_type: Option<&str>,
+    bundle_discount_percent: Option<i64>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if product_type.is_some_and(|t| t != "single" && t != "bundle") {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("product_type must be 'single' or 'bundle'"))));
+    }
+    if bundle_discount_percent.is_some_and(|p| !(0..=90).contains(&p)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("bundle_discount_percent must be between 0 and 90"))));
+    }
+    Ok(())
+}
+
+fn validate_rental(
+    rental_price_per_day: Option<i64>,
+    rental_deposit: Option<i64>,
+    rental_buffer_days: Option<i64>,
+    rental_units: Option<&BTreeMap<String, i64>>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if rental_price_per_day.is_some_and(|p| p < 0) || rental_deposit.is_some_and(|d| d < 0) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Prices cannot be negative"))));
+    }
+    if rental_buffer_days.is_some_and(|d| !(0..=rentals::MAX_BUFFER_DAYS).contains(&d)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("rental_buffer_days must be between 0 and {}", rentals::MAX_BUFFER_DAYS))),
+        ));
+    }
+    if rental_units.is_some_and(|units| units.values().any(|n| !(0..=rentals::MAX_UNITS).contains(n))) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("rental_units must be between 0 and {}", rentals::MAX_UNITS))),
+        ));
+    }
+    Ok(())
+}
+
+/// Rental pieces as stored: variant labels trimmed, empty entries dropped
+fn rental_units_json(units: &BTreeMap<String, i64>) -> String {
+    let units: BTreeMap<&str, i64> = units.iter().filter(|(_, n)| **n > 0).map(|(v, n)| (v.trim(), *n)).collect();
+    serde_json::to_string(&units).unwrap_or_else(|_| "{}".to_string())
+}
 
 /// GET /api/products - List all products
 pub async fn list_products(
@@ -17,7 +84,7 @@ pub async fn list_products(
 ) -> Result<Json<ApiResponse<PaginatedResponse<Product>>>, (StatusCode, Json<ApiResponse<()>>)> {
     let conn = db.lock().unwrap();
     
-    let mut sql = "SELECT id, name, description, price, images, category, status, sort_order, created_at, updated_at FROM products WHERE 1=1".to_string();
+    let mut sql = format!("SELECT {} FROM products WHERE 1=1", PRODUCT_COLUMNS);
     let mut count_sql = "SELECT COUNT(*) FROM products WHERE 1=1".to_string();
     
     if let Some(ref status) = params.status {
@@ -40,23 +107,17 @@ pub async fn list_products(
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
     })?;
     
-    let products: Vec<Product> = stmt.query_map([], |row| {
-        Ok(Product {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            price: row.get(3)?,
-            images: parse_json_array(&row.get::<_, String>(4)?),
-            category: row.get(5)?,
-            status: row.get(6)?,
-            sort_order: row.get(7)?,
-            created_at: row.get(8)?,
-            updated_at: row.get(9).ok(),
-        })
-    }).map_err(|e| {
+    let mut products: Vec<Product> = stmt.query_map([], product_from_row).map_err(|e| {
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
     })?.filter_map(|r| r.ok()).collect();
     
+    bundles::resolve_bundles(&conn, &mut products, false);
+    let now = Utc::now();
+    let promotions = pricing::load_active_promotions(&conn, now);
+    for product in &mut products {
+        pricing::apply_pricing(product, &promotions, now);
+    }
+    
     let total_pages = ((total as f64) / (params.limit as f64)).ceil() as u32;
     
     Ok(Json(ApiResponse::success(PaginatedResponse {
@@ -75,25 +136,7 @@ pub async fn get_product(
 ) -> Result<Json<ApiResponse<Product>>, (StatusCode, Json<ApiResponse<()>>)> {
     let conn = db.lock().unwrap();
     
-    let product = conn.query_row(
-        "SELECT id, name, description, price, images, category, status, sort_order, created_at, updated_at 
-         FROM products WHERE id = ?1",
-        params![id],
-        |row| {
-            Ok(Product {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                price: row.get(3)?,
-                images: parse_json_array(&row.get::<_, String>(4)?),
-                category: row.get(5)?,
-                status: row.get(6)?,
-                sort_order: row.get(7)?,
-                created_at: row.get(8)?,
-                updated_at: row.get(9).ok(),
-            })
-        }
-    ).map_err(|_| {
+    let product = load_product(&conn, &id).map_err(|_| {
         (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
     })?;
     
@@ -105,34 +148,78 @@ pub async fn create_product(
     State(db): State<DbPool>,
     Json(payload): Json<CreateProduct>,
 ) -> Result<Json<ApiResponse<Product>>, (StatusCode, Json<ApiResponse<()>>)> {
-    let conn = db.lock().unwrap();
+    validate_sale(
+        payload.sale_price,
+        payload.compare_at_price,
+        payload.sale_starts_at.as_deref(),
+        payload.sale_ends_at.as_deref(),
+    )?;
+    validate_bundle(Some(&payload.product_type), payload.bundle_discount_percent)?;
+    validate_rental(
+        payload.rental_price_per_day,
+        payload.rental_deposit,
+        payload.rental_buffer_days,
+        payload.rental_units.as_ref(),
+    )?;
     
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let images_json = to_json_array(&payload.images);
+    let rental_units_json = rental_units_json(&payload.rental_units.clone().unwrap_or_default());
     
-    conn.execute(
-        "INSERT INTO products (id, name, description, price, images, category, status, sort_order, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, ?8)",
-        params![id, payload.name, payload.description, payload.price, images_json, payload.category, payload.sort_order, now],
-    ).map_err(|e| {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
-    })?;
-    
-    let product = Product {
-        id,
-        name: payload.name,
-        description: payload.description,
-        price: payload.price,
-        images: payload.images,
-        category: payload.category,
-        status: "active".to_string(),
-        sort_order: payload.sort_order,
-        created_at: now,
-        updated_at: None,
-    };
+    {
+        let mut conn = db.lock().unwrap();
+        let is_bundle = payload.product_type == "bundle";
+        if is_bundle {
+            bundles::validate_items(&conn, &id, &payload.components).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        
+        let tx = conn.transaction().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        tx.execute(
+            "INSERT INTO products (id, name, description, price, images, category, status, sort_order,
+                compare_at_price, sale_price, sale_starts_at, sale_ends_at, product_type, bundle_discount_percent, stock,
+                rental_price_per_day, rental_deposit, rental_buffer_days, rental_units, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, NULLIF(?8, 0), NULLIF(?9, 0), NULLIF(?10, ''), NULLIF(?11, ''),
+                ?12, NULLIF(?13, 0), ?14, NULLIF(?15, 0), ?16, ?17, ?18, ?19)",
+            params![
+                id,
+                payload.name,
+                payload.description,
+                payload.price,
+                images_json,
+                payload.category,
+                payload.sort_order,
+                payload.compare_at_price,
+                payload.sale_price,
+                payload.sale_starts_at,
+                payload.sale_ends_at,
+                payload.product_type,
+                payload.bundle_discount_percent,
+                payload.stock.filter(|s| *s >= 0),
+                payload.rental_price_per_day,
+                payload.rental_deposit.unwrap_or(0),
+                payload.rental_buffer_days.unwrap_or(rentals::DEFAULT_BUFFER_DAYS),
+                rental_units_json,
+                now
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        if is_bundle {
+            bundles::save_items(&tx, &id, &payload.components).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+        }
+        tx.commit().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
     
-    Ok(Json(ApiResponse::success(product)))
+    get_product(State(db), Path(id)).await
 }
 
 /// PUT /api/products/:id - Update product (auth required)
@@ -141,12 +228,44 @@ pub async fn update_product(
     Path(id): Path<String>,
     Json(payload): Json<UpdateProduct>,
 ) -> Result<Json<ApiResponse<Product>>, (StatusCode, Json<ApiResponse<()>>)> {
+    validate_sale(
+        payload.sale_price,
+        payload.compare_at_price,
+        payload.sale_starts_at.as_deref(),
+        payload.sale_ends_at.as_deref(),
+    )?;
+    validate_bundle(None, payload.bundle_discount_percent)?;
+    validate_rental(
+        payload.rental_price_per_day,
+        payload.rental_deposit,
+        payload.rental_buffer_days,
+        payload.rental_units.as_ref(),
+    )?;
+    
     let now = Utc::now().to_rfc3339();
     let images_json = payload.images.as_ref().map(|i| to_json_array(i));
+    let rental_units_json = payload.rental_units.as_ref().map(rental_units_json);
     
     {
-        let conn = db.lock().unwrap();
-        conn.execute(
+        let mut conn = db.lock().unwrap();
+        if let Some(ref components) = payload.components {
+            let product_type: String = conn.query_row(
+                "SELECT product_type FROM products WHERE id = ?1", params![id], |row| row.get(0)
+            ).map_err(|_| {
+                (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+            })?;
+            if product_type != "bundle" {
+                return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Only bundle products have components"))));
+            }
+            bundles::validate_items(&conn, &id, components).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        
+        let tx = conn.transaction().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        tx.execute(
             "UPDATE products SET 
                 name = COALESCE(?1, name),
                 description = COALESCE(?2, description),
@@ -155,8 +274,18 @@ pub async fn update_product(
                 category = COALESCE(?5, category),
                 status = COALESCE(?6, status),
                 sort_order = COALESCE(?7, sort_order),
-                updated_at = ?8
-             WHERE id = ?9",
+                compare_at_price = CASE WHEN ?8 IS NULL THEN compare_at_price ELSE NULLIF(?8, 0) END,
+                sale_price = CASE WHEN ?9 IS NULL THEN sale_price ELSE NULLIF(?9, 0) END,
+                sale_starts_at = CASE WHEN ?10 IS NULL THEN sale_starts_at ELSE NULLIF(?10, '') END,
+                sale_ends_at = CASE WHEN ?11 IS NULL THEN sale_ends_at ELSE NULLIF(?11, '') END,
+                bundle_discount_percent = CASE WHEN ?12 IS NULL THEN bundle_discount_percent ELSE NULLIF(?12, 0) END,
+                stock = CASE WHEN ?13 IS NULL THEN stock WHEN ?13 < 0 THEN NULL ELSE ?13 END,
+                rental_price_per_day = CASE WHEN ?14 IS NULL THEN rental_price_per_day ELSE NULLIF(?14, 0) END,
+                rental_deposit = COALESCE(?15, rental_deposit),
+                rental_buffer_days = COALESCE(?16, rental_buffer_days),
+                rental_units = COALESCE(?17, rental_units),
+                updated_at = ?18
+             WHERE id = ?19",
             params![
                 payload.name,
                 payload.description,
@@ -165,12 +294,30 @@ pub async fn update_product(
                 payload.category,
                 payload.status,
                 payload.sort_order,
+                payload.compare_at_price,
+                payload.sale_price,
+                payload.sale_starts_at,
+                payload.sale_ends_at,
+                payload.bundle_discount_percent,
+                payload.stock,
+                payload.rental_price_per_day,
+                payload.rental_deposit,
+                payload.rental_buffer_days,
+                rental_units_json,
                 now,
                 id
             ],
         ).map_err(|e| {
             (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
         })?;
+        if let Some(ref components) = payload.components {
+            bundles::save_items(&tx, &id, components).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+        }
+        tx.commit().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
     }
     
     // Fetch updated product
@@ -184,6 +331,24 @@ pub async fn delete_product(
 ) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
     let conn = db.lock().unwrap();
     
+    let bundle_count: i64 = conn.query_row(
+        "SELECT COUNT(DISTINCT bundle_id) FROM bundle_items WHERE component_id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(0);
+    if bundle_count > 0 {
+        return Err((StatusCode::CONFLICT, Json(ApiResponse::<()>::error("Product is part of a bundle; remove it from the bundle first"))));
+    }
+    
+    let rented: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM rentals WHERE product_id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if rented {
+        return Err((StatusCode::CONFLICT, Json(ApiResponse::<()>::error("Product has rentals; set it inactive instead"))));
+    }
+    
     let rows = conn.execute("DELETE FROM products WHERE id = ?1", params![id])
         .map_err(|e| {
             (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
@@ -199,3 +364,161 @@ pub async fn delete_product(
         message: Some("Product deleted".to_string()),
     }))
 }
+
+/// GET /api/products/:id/related - Ranked related products (public)
+pub async fn related_products(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Query(params): Query<RelatedParams>,
+) -> Result<Json<ApiResponse<Vec<RelatedProduct>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    
+    let target = load_product(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+    })?;
+    let limit = params.limit.unwrap_or(recommendations::DEFAULT_RELATED_LIMIT).clamp(1, recommendations::MAX_RELATED_LIMIT);
+    
+    let mut signals = RelatedSignals::default();
+    
+    // Links curated on this product come first, links pointing at it follow
+    let mut stmt = conn.prepare(
+        "SELECT paired_product_id, sort_order, 0 FROM product_pairings WHERE product_id = ?1
+         UNION ALL
+         SELECT product_id, sort_order, 1 FROM product_pairings WHERE paired_product_id = ?1"
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let pairings: Vec<(String, i64, i64)> = stmt.query_map(params![id], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    }).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+    for (paired_id, sort_order, reverse) in pairings {
+        let position = (sort_order.max(0) + reverse * 10) as usize;
+        let entry = signals.pairings.entry(paired_id).or_insert(position);
+        *entry = (*entry).min(position);
+    }
+    
+    // Customers (by phone) who ordered both products
+    let mut stmt = conn.prepare(
+        "SELECT i2.product_id, COUNT(DISTINCT o2.customer_phone)
+         FROM order_items i1
+         JOIN orders o1 ON o1.id = i1.order_id
+         JOIN orders o2 ON o2.customer_phone = o1.customer_phone
+         JOIN order_items i2 ON i2.order_id = o2.id AND i2.product_id != i1.product_id
+         WHERE i1.product_id = ?1 AND i2.product_id IS NOT NULL
+           AND o1.status != 'cancelled' AND o2.status != 'cancelled'
+         GROUP BY i2.product_id"
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    signals.co_orders = stmt.query_map(params![id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    }).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+    
+    let mut stmt = conn.prepare(
+        &format!("SELECT {} FROM products WHERE status = 'active' AND id != ?1", PRODUCT_COLUMNS)
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let mut candidates: Vec<Product> = stmt.query_map(params![id], product_from_row).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+    
+    bundles::resolve_bundles(&conn, &mut candidates, false);
+    let now = Utc::now();
+    let promotions = pricing::load_active_promotions(&conn, now);
+    for product in &mut candidates {
+        pricing::apply_pricing(product, &promotions, now);
+    }
+    
+    Ok(Json(ApiResponse::success(recommendations::rank(&target, candidates, &signals, limit))))
+}
+
+/// GET /api/products/:id/pairings - Curated "pairs well with" products (auth required)
+pub async fn get_product_pairings(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<Product>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    
+    let mut stmt = conn.prepare(
+        "SELECT paired_product_id FROM product_pairings WHERE product_id = ?1 ORDER BY sort_order ASC"
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let paired_ids: Vec<String> = stmt.query_map(params![id], |row| row.get(0)).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+    
+    let products = paired_ids.iter().filter_map(|pid| load_product(&conn, pid).ok()).collect();
+    
+    Ok(Json(ApiResponse::success(products)))
+}
+
+/// PUT /api/products/:id/pairings - Replace curated "pairs well with" products (auth required)
+pub async fn update_product_pairings(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdatePairings>,
+) -> Result<Json<ApiResponse<Vec<Product>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.product_ids.contains(&id) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("A product cannot be paired with itself"))));
+    }
+    
+    {
+        let mut conn = db.lock().unwrap();
+        load_product(&conn, &id).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+        })?;
+        
+        let now = Utc::now().to_rfc3339();
+        let tx = conn.transaction().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        tx.execute("DELETE FROM product_pairings WHERE product_id = ?1", params![id]).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+        for (position, paired_id) in payload.product_ids.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO product_pairings (product_id, paired_product_id, sort_order, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, paired_id, position as i64, now],
+            ).map_err(|_| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("Product not found: {}", paired_id))))
+            })?;
+        }
+        tx.commit().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    
+    get_product_pairings(State(db), Path(id)).await
+}
+
+/// PUT /api/products/:id/options - Replace customization option groups (auth required)
+pub async fn update_product_options(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateProductOptions>,
+) -> Result<Json<ApiResponse<Vec<ProductOptionGroup>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let mut conn = db.lock().unwrap();
+    
+    load_product(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+    })?;
+    
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    options::save_groups(&tx, &id, &payload.groups).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    
+    Ok(Json(ApiResponse::success(options::load_groups(&conn, &id))))
+}
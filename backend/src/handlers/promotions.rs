@@ -0,0 +1,174 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use rusqlite::params;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::db::DbPool;
+use crate::models::{ApiResponse, Promotion, CreatePromotion, UpdatePromotion};
+use crate::pricing::{self, promotion_from_row, PROMOTION_COLUMNS};
+
+fn validate_promotion(
+    discount_percent: Option<i64>,
+    starts_at: Option<&str>,
+    ends_at: Option<&str>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if discount_percent.is_some_and(|p| !(1..=90).contains(&p)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("discount_percent must be between 1 and 90"))));
+    }
+    pricing::validate_window(starts_at, ends_at).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })
+}
+
+/// GET /api/promotions - List all promotions (auth required)
+pub async fn list_promotions(
+    State(db): State<DbPool>,
+) -> Result<Json<ApiResponse<Vec<Promotion>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM promotions ORDER BY created_at DESC", PROMOTION_COLUMNS)).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let promotions: Vec<Promotion> = stmt.query_map([], promotion_from_row).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(promotions)))
+}
+
+/// GET /api/promotions/:id - Get single promotion (auth required)
+pub async fn get_promotion(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Promotion>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let promotion = conn.query_row(
+        &format!("SELECT {} FROM promotions WHERE id = ?1", PROMOTION_COLUMNS),
+        params![id],
+        promotion_from_row,
+    ).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Promotion not found")))
+    })?;
+
+    Ok(Json(ApiResponse::success(promotion)))
+}
+
+/// POST /api/promotions - Create promotion (auth required)
+pub async fn create_promotion(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreatePromotion>,
+) -> Result<Json<ApiResponse<Promotion>>, (StatusCode, Json<ApiResponse<()>>)> {
+    validate_promotion(Some(payload.discount_percent), payload.starts_at.as_deref(), payload.ends_at.as_deref())?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    {
+        let conn = db.lock().unwrap();
+        conn.execute(
+            "INSERT INTO promotions (id, name, discount_percent, category, starts_at, ends_at, active, created_at)
+             VALUES (?1, ?2, ?3, NULLIF(?4, ''), NULLIF(?5, ''), NULLIF(?6, ''), ?7, ?8)",
+            params![
+                id,
+                payload.name,
+                payload.discount_percent,
+                payload.category,
+                payload.starts_at,
+                payload.ends_at,
+                payload.active,
+                now
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+
+    get_promotion(State(db), Path(id)).await
+}
+
+/// PUT /api/promotions/:id - Update promotion (auth required)
+pub async fn update_promotion(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdatePromotion>,
+) -> Result<Json<ApiResponse<Promotion>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let now = Utc::now().to_rfc3339();
+
+    {
+        let conn = db.lock().unwrap();
+
+        // The window is checked as it will be saved, so moving one end can't
+        // put it on the wrong side of the stored other end
+        let (stored_starts, stored_ends): (Option<String>, Option<String>) = conn.query_row(
+            "SELECT starts_at, ends_at FROM promotions WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Promotion not found")))
+        })?;
+        validate_promotion(
+            payload.discount_percent,
+            payload.starts_at.as_deref().or(stored_starts.as_deref()),
+            payload.ends_at.as_deref().or(stored_ends.as_deref()),
+        )?;
+
+        let rows = conn.execute(
+            "UPDATE promotions SET
+                name = COALESCE(?1, name),
+                discount_percent = COALESCE(?2, discount_percent),
+                category = CASE WHEN ?3 IS NULL THEN category ELSE NULLIF(?3, '') END,
+                starts_at = CASE WHEN ?4 IS NULL THEN starts_at ELSE NULLIF(?4, '') END,
+                ends_at = CASE WHEN ?5 IS NULL THEN ends_at ELSE NULLIF(?5, '') END,
+                active = COALESCE(?6, active),
+                updated_at = ?7
+             WHERE id = ?8",
+            params![
+                payload.name,
+                payload.discount_percent,
+                payload.category,
+                payload.starts_at,
+                payload.ends_at,
+                payload.active,
+                now,
+                id
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+
+        if rows == 0 {
+            return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Promotion not found"))));
+        }
+    }
+
+    get_promotion(State(db), Path(id)).await
+}
+
+/// DELETE /api/promotions/:id - Delete promotion (auth required)
+pub async fn delete_promotion(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let rows = conn.execute("DELETE FROM promotions WHERE id = ?1", params![id])
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Promotion not found"))));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Promotion deleted".to_string()),
+    }))
+}
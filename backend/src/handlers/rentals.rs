@@ -0,0 +1,460 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::params;
+use uuid::Uuid;
+
+use crate::customers;
+use crate::db::DbPool;
+use crate::models::{
+    ApiResponse, CreateRental, Rental, RentalAvailabilityParams, RentalCalendar, RentalListParams, ReturnRental,
+    UpdateRental,
+};
+use crate::phone;
+use crate::pricing::shop_offset;
+use crate::products::load_product;
+use crate::rentals::{self, RENTAL_SELECT};
+
+fn today() -> NaiveDate {
+    Utc::now().with_timezone(&shop_offset()).date_naive()
+}
+
+fn parse_date(field: &str, value: &str) -> Result<NaiveDate, (StatusCode, Json<ApiResponse<()>>)> {
+    rentals::parse_date(value).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("{} must be YYYY-MM-DD", field))))
+    })
+}
+
+/// Check a requested rental period against today and the length limits
+fn validate_range(start: NaiveDate, end: NaiveDate, today: NaiveDate) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if end < start {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("end_date cannot be before start_date"))));
+    }
+    if rentals::rental_days(start, end) > rentals::MAX_RENTAL_DAYS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("A rental can last at most {} days", rentals::MAX_RENTAL_DAYS))),
+        ));
+    }
+    if start < today {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("start_date cannot be in the past"))));
+    }
+    if start > today + Duration::days(rentals::MAX_DAYS_AHEAD) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("Rentals open {} days ahead", rentals::MAX_DAYS_AHEAD))),
+        ));
+    }
+    Ok(())
+}
+
+fn conflict(day: NaiveDate) -> (StatusCode, Json<ApiResponse<()>>) {
+    (
+        StatusCode::CONFLICT,
+        Json(ApiResponse::<()>::error(&format!(
+            "No piece is free on {} (rented out or being cleaned)",
+            rentals::format_date(day)
+        ))),
+    )
+}
+
+fn wrong_status(rental: &Rental, action: &str) -> (StatusCode, Json<ApiResponse<()>>) {
+    (
+        StatusCode::CONFLICT,
+        Json(ApiResponse::<()>::error(&format!("Cannot {} a rental that is {}", action, rental.status))),
+    )
+}
+
+/// GET /api/products/:id/rental-availability - Free rental pieces per variant, day by day (public)
+pub async fn product_rental_availability(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Query(params): Query<RentalAvailabilityParams>,
+) -> Result<Json<ApiResponse<RentalCalendar>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let today = today();
+    let from = match params.from.as_deref().filter(|f| !f.trim().is_empty()) {
+        Some(from) => parse_date("from", from)?.max(today),
+        None => today,
+    };
+    let days = params.days.clamp(1, 90);
+
+    let conn = db.lock().unwrap();
+    let product = load_product(&conn, &id)
+        .ok()
+        .filter(|p| p.status == "active")
+        .ok_or_else(|| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+        })?;
+    let Some(price_per_day) = product.rental_price_per_day else {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("This product is not for rent"))));
+    };
+
+    let days = rentals::calendar(&conn, &product, from, days, today).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(RentalCalendar {
+        product_id: product.id,
+        rental_price_per_day: price_per_day,
+        rental_deposit: product.rental_deposit,
+        rental_buffer_days: product.rental_buffer_days,
+        days,
+    })))
+}
+
+/// GET /api/rentals - List rentals (auth required)
+pub async fn list_rentals(
+    State(db): State<DbPool>,
+    Query(params): Query<RentalListParams>,
+) -> Result<Json<ApiResponse<Vec<Rental>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let from = params.from.as_deref().filter(|f| !f.trim().is_empty()).map(|f| parse_date("from", f)).transpose()?;
+    let to = params.to.as_deref().filter(|t| !t.trim().is_empty()).map(|t| parse_date("to", t)).transpose()?;
+    let phone = params.phone.as_deref().filter(|p| !p.trim().is_empty()).map(|p| {
+        phone::normalize(p).unwrap_or_else(|| p.trim().to_string())
+    });
+
+    let conn = db.lock().unwrap();
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE (?1 IS NULL OR r.status = ?1)
+            AND (?2 IS NULL OR r.product_id = ?2)
+            AND (?3 IS NULL OR r.end_date >= ?3)
+            AND (?4 IS NULL OR r.start_date <= ?4)
+            AND (?5 IS NULL OR r.customer_phone = ?5)
+         ORDER BY r.start_date ASC, r.created_at ASC",
+        RENTAL_SELECT
+    )).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let found: Vec<Rental> = stmt.query_map(
+        params![
+            params.status.as_deref().filter(|s| !s.is_empty()),
+            params.product_id.as_deref().filter(|p| !p.is_empty()),
+            from.map(rentals::format_date),
+            to.map(rentals::format_date),
+            phone
+        ],
+        rentals::rental_from_row,
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(found)))
+}
+
+/// POST /api/rentals - Book a rental piece for a date range (auth required)
+pub async fn create_rental(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreateRental>,
+) -> Result<Json<ApiResponse<Rental>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let phone = phone::normalize(&payload.customer_phone).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid phone number")))
+    })?;
+    let name = payload.customer_name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("customer_name is required"))));
+    }
+    let today = today();
+    let start = parse_date("start_date", &payload.start_date)?;
+    let end = parse_date("end_date", &payload.end_date)?;
+    validate_range(start, end, today)?;
+    let variant = payload.variant.trim();
+    let id = Uuid::new_v4().to_string();
+
+    let mut conn = db.lock().unwrap();
+    let product = load_product(&conn, &payload.product_id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+    })?;
+    let (Some(price_per_day), Some(_)) = (product.rental_price_per_day, rentals::units(&product, variant)) else {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("This product is not for rent in that variant"))));
+    };
+    let order_id = match payload.order_id.as_deref().map(str::trim).filter(|o| !o.is_empty()) {
+        Some(order_id) => Some(
+            conn.query_row("SELECT id FROM orders WHERE id = ?1", params![order_id], |row| row.get::<_, String>(0))
+                .map_err(|_| {
+                    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+                })?,
+        ),
+        None => None,
+    };
+    let until = rentals::blocked_until(end, product.rental_buffer_days);
+
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if let Some(day) = rentals::first_conflict(&tx, &product, variant, start, until, None, today).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })? {
+        return Err(conflict(day));
+    }
+    let customer_id = customers::upsert_by_phone(&tx, &phone, name, "").map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    tx.execute(
+        "INSERT INTO rentals (id, product_id, product_name, variant, order_id, customer_id, customer_name, customer_phone,
+            start_date, end_date, price_per_day, rental_total, deposit, buffer_days, blocked_until, status, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, 'reserved', ?16, ?17)",
+        params![
+            id,
+            product.id,
+            product.name,
+            variant,
+            order_id,
+            customer_id,
+            name,
+            phone,
+            rentals::format_date(start),
+            rentals::format_date(end),
+            price_per_day,
+            price_per_day * rentals::rental_days(start, end),
+            product.rental_deposit,
+            product.rental_buffer_days,
+            rentals::format_date(until),
+            payload.notes.trim(),
+            Utc::now().to_rfc3339()
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let rental = rentals::load_rental(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(rental)))
+}
+
+/// GET /api/rentals/:id - Get rental (auth required)
+pub async fn get_rental(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Rental>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    let rental = rentals::load_rental(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Rental not found")))
+    })?;
+    Ok(Json(ApiResponse::success(rental)))
+}
+
+/// PUT /api/rentals/:id - Change dates, variant, notes or linked order (auth required)
+///
+/// New dates are priced at the rate the rental was booked at.
+pub async fn update_rental(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateRental>,
+) -> Result<Json<ApiResponse<Rental>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let today = today();
+    let mut conn = db.lock().unwrap();
+    let rental = rentals::load_rental(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Rental not found")))
+    })?;
+
+    let order_id = match payload.order_id.as_deref().map(str::trim) {
+        Some("") => Some(None),
+        Some(order_id) => Some(Some(
+            conn.query_row("SELECT id FROM orders WHERE id = ?1", params![order_id], |row| row.get::<_, String>(0))
+                .map_err(|_| {
+                    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+                })?,
+        )),
+        None => None,
+    };
+
+    let moving = payload.start_date.is_some() || payload.end_date.is_some() || payload.variant.is_some();
+    let mut dates = None;
+    if moving {
+        if rental.status != "reserved" {
+            return Err(wrong_status(&rental, "move"));
+        }
+        let start = match payload.start_date.as_deref() {
+            Some(start) => parse_date("start_date", start)?,
+            None => parse_date("start_date", &rental.start_date)?,
+        };
+        let end = match payload.end_date.as_deref() {
+            Some(end) => parse_date("end_date", end)?,
+            None => parse_date("end_date", &rental.end_date)?,
+        };
+        validate_range(start, end, today)?;
+        let variant = payload.variant.as_deref().map(str::trim).unwrap_or(&rental.variant).to_string();
+
+        let product = load_product(&conn, &rental.product_id).map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Product not found")))
+        })?;
+        if rentals::units(&product, &variant).is_none() {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("This product is not for rent in that variant"))));
+        }
+        let buffer_days: i64 = conn.query_row(
+            "SELECT buffer_days FROM rentals WHERE id = ?1", params![id], |row| row.get(0)
+        ).unwrap_or(product.rental_buffer_days);
+        dates = Some((product, variant, start, end, rentals::blocked_until(end, buffer_days)));
+    }
+
+    let tx = conn.transaction().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if let Some((product, variant, start, end, until)) = dates {
+        if let Some(day) = rentals::first_conflict(&tx, &product, &variant, start, until, Some(&id), today).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })? {
+            return Err(conflict(day));
+        }
+        tx.execute(
+            "UPDATE rentals SET variant = ?1, start_date = ?2, end_date = ?3, blocked_until = ?4,
+                rental_total = price_per_day * ?5
+             WHERE id = ?6",
+            params![
+                variant,
+                rentals::format_date(start),
+                rentals::format_date(end),
+                rentals::format_date(until),
+                rentals::rental_days(start, end),
+                id
+            ],
+        ).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    }
+    tx.execute(
+        "UPDATE rentals SET
+            notes = COALESCE(?1, notes),
+            order_id = CASE WHEN ?2 THEN ?3 ELSE order_id END,
+            updated_at = ?4
+         WHERE id = ?5",
+        params![
+            payload.notes.as_deref().map(str::trim),
+            order_id.is_some(),
+            order_id.flatten(),
+            Utc::now().to_rfc3339(),
+            id
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    tx.commit().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let rental = rentals::load_rental(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(rental)))
+}
+
+/// POST /api/rentals/:id/pickup - Hand the piece to the customer (auth required)
+pub async fn pickup_rental(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Rental>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    let rental = rentals::load_rental(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Rental not found")))
+    })?;
+    if rental.status != "reserved" {
+        return Err(wrong_status(&rental, "pick up"));
+    }
+    // Earlier would take the piece while it may still be out or being cleaned
+    if rentals::format_date(today()) < rental.start_date {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(&format!("This rental starts on {}", rental.start_date))),
+        ));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE rentals SET status = 'picked_up', picked_up_at = ?1, updated_at = ?1 WHERE id = ?2",
+        params![now, id],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let rental = rentals::load_rental(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(rental)))
+}
+
+/// POST /api/rentals/:id/return - Take the piece back, charging late days and damage against the deposit (auth required)
+pub async fn return_rental(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReturnRental>,
+) -> Result<Json<ApiResponse<Rental>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.damage_fee < 0 {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("damage_fee cannot be negative"))));
+    }
+    let today = today();
+    let returned_on = match payload.returned_on.as_deref().filter(|r| !r.trim().is_empty()) {
+        Some(returned_on) => parse_date("returned_on", returned_on)?,
+        None => today,
+    };
+    if returned_on > today {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("returned_on cannot be in the future"))));
+    }
+
+    let conn = db.lock().unwrap();
+    let rental = rentals::load_rental(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Rental not found")))
+    })?;
+    if rental.status != "picked_up" {
+        return Err(wrong_status(&rental, "return"));
+    }
+    let end = parse_date("end_date", &rental.end_date)?;
+    let late_fee = (returned_on - end).num_days().max(0) * rental.price_per_day;
+    let deposit_refund = (rental.deposit - late_fee - payload.damage_fee).max(0);
+
+    // Cleaning starts from the day it actually came back
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE rentals SET status = 'returned', returned_at = ?1, returned_on = ?2,
+            blocked_until = date(?2, '+' || buffer_days || ' days'),
+            late_fee = ?3, damage_notes = ?4, damage_fee = ?5, deposit_refund = ?6, updated_at = ?1
+         WHERE id = ?7",
+        params![
+            now,
+            rentals::format_date(returned_on),
+            late_fee,
+            payload.damage_notes.trim(),
+            payload.damage_fee,
+            deposit_refund,
+            id
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let rental = rentals::load_rental(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(rental)))
+}
+
+/// POST /api/rentals/:id/cancel - Cancel a reservation and free the piece (auth required)
+pub async fn cancel_rental(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Rental>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+    let rental = rentals::load_rental(&conn, &id).map_err(|_| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Rental not found")))
+    })?;
+    if rental.status != "reserved" {
+        return Err(wrong_status(&rental, "cancel"));
+    }
+
+    conn.execute(
+        "UPDATE rentals SET status = 'cancelled', updated_at = ?1 WHERE id = ?2",
+        params![Utc::now().to_rfc3339(), id],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let rental = rentals::load_rental(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(rental)))
+}
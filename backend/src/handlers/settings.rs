@@ -7,7 +7,13 @@ use rusqlite::params;
 use chrono::Utc;
 
 use crate::db::DbPool;
-use crate::models::{ApiResponse, Setting, UpdateSettings, DashboardStats, Order};
+use crate::documents;
+use crate::handlers::orders::{order_from_row, ORDER_COLUMNS};
+use crate::measurements::MeasurementsInput;
+use crate::models::{ApiResponse, Setting, UpdateSettings, DashboardStats, Order, SizeRecommendation};
+use crate::order_numbers;
+use crate::sizing;
+use crate::vietqr;
 
 /// GET /api/settings - Get all settings (public)
 pub async fn get_all_settings(
@@ -64,14 +70,49 @@ pub async fn update_settings(
     State(db): State<DbPool>,
     Json(payload): Json<UpdateSettings>,
 ) -> Result<Json<ApiResponse<Vec<Setting>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    // Settings the backend interprets must stay well-formed
+    for setting in &payload.settings {
+        if setting.key == "size_chart" {
+            sizing::parse_chart(&setting.value).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        if setting.key == "order_number_prefix" {
+            order_numbers::validate_prefix(&setting.value).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        // Empty bank settings turn bank transfer QR codes off
+        let value = setting.value.trim();
+        if setting.key == "bank_bin" && !value.is_empty() {
+            vietqr::validate_bin(value).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        if setting.key == "bank_account_number" && !value.is_empty() {
+            vietqr::validate_account_number(value).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        if setting.key == "admin_url" {
+            documents::validate_admin_url(value).map_err(|e| {
+                (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+            })?;
+        }
+        if setting.key == "deposit_percent" && !value.parse::<i64>().is_ok_and(|p| (0..=100).contains(&p)) {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("deposit_percent must be between 0 and 100"))));
+        }
+    }
+    
     let now = Utc::now().to_rfc3339();
     
     {
         let conn = db.lock().unwrap();
         for setting in &payload.settings {
-            // Use INSERT OR REPLACE to create new settings or update existing ones
+            // Use INSERT OR REPLACE to create new settings or update existing ones, keeping their type
             conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value, type, updated_at) VALUES (?1, ?2, 'string', ?3)",
+                "INSERT OR REPLACE INTO settings (key, value, type, updated_at)
+                 VALUES (?1, ?2, COALESCE((SELECT type FROM settings WHERE key = ?1), 'string'), ?3)",
                 params![setting.key, setting.value, now],
             ).map_err(|e| {
                 (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
@@ -82,6 +123,31 @@ pub async fn update_settings(
     get_all_settings(State(db)).await
 }
 
+/// POST /api/size-chart/recommend - Suggest a size from the customer's measurements (public)
+pub async fn recommend_size(
+    State(db): State<DbPool>,
+    Json(payload): Json<MeasurementsInput>,
+) -> Result<Json<ApiResponse<SizeRecommendation>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let measurements = payload.into_measurements().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    
+    let conn = db.lock().unwrap();
+    let value: String = conn.query_row("SELECT value FROM settings WHERE key = 'size_chart'", [], |row| row.get(0))
+        .map_err(|_| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Size chart not configured")))
+        })?;
+    let chart = sizing::parse_chart(&value).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    
+    let recommendation = sizing::recommend(&chart, &measurements).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&e)))
+    })?;
+    
+    Ok(Json(ApiResponse::success(recommendation)))
+}
+
 /// GET /api/stats - Get dashboard stats (auth required)
 pub async fn get_stats(
     State(db): State<DbPool>,
@@ -90,31 +156,17 @@ pub async fn get_stats(
     
     let total_products: i64 = conn.query_row("SELECT COUNT(*) FROM products", [], |row| row.get(0)).unwrap_or(0);
     let active_products: i64 = conn.query_row("SELECT COUNT(*) FROM products WHERE status = 'active'", [], |row| row.get(0)).unwrap_or(0);
-    let total_orders: i64 = conn.query_row("SELECT COUNT(*) FROM orders", [], |row| row.get(0)).unwrap_or(0);
-    let pending_orders: i64 = conn.query_row("SELECT COUNT(*) FROM orders WHERE status = 'pending'", [], |row| row.get(0)).unwrap_or(0);
+    // Suspected spam stays out of the dashboard as it does the order list
+    let total_orders: i64 = conn.query_row("SELECT COUNT(*) FROM orders WHERE COALESCE(suspected_spam, 0) = 0", [], |row| row.get(0)).unwrap_or(0);
+    let pending_orders: i64 = conn.query_row("SELECT COUNT(*) FROM orders WHERE status = 'pending' AND COALESCE(suspected_spam, 0) = 0", [], |row| row.get(0)).unwrap_or(0);
     
     let mut stmt = conn.prepare(
-        "SELECT id, customer_name, customer_phone, customer_email, product_id, product_name, measurements, notes, status, created_at, updated_at 
-         FROM orders ORDER BY created_at DESC LIMIT 5"
+        &format!("SELECT {} FROM orders WHERE COALESCE(suspected_spam, 0) = 0 ORDER BY created_at DESC LIMIT 5", ORDER_COLUMNS)
     ).map_err(|e| {
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
     })?;
     
-    let recent_orders: Vec<Order> = stmt.query_map([], |row| {
-        Ok(Order {
-            id: row.get(0)?,
-            customer_name: row.get(1)?,
-            customer_phone: row.get(2)?,
-            customer_email: row.get(3)?,
-            product_id: row.get(4).ok(),
-            product_name: row.get(5)?,
-            measurements: row.get(6)?,
-            notes: row.get(7)?,
-            status: row.get(8)?,
-            created_at: row.get(9)?,
-            updated_at: row.get(10).ok(),
-        })
-    }).map_err(|e| {
+    let recent_orders: Vec<Order> = stmt.query_map([], order_from_row).map_err(|e| {
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
     })?.filter_map(|r| r.ok()).collect();
     
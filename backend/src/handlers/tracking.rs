@@ -0,0 +1,222 @@
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::net::SocketAddr;
+
+use crate::db::DbPool;
+use crate::models::{ApiResponse, OrderTracking, PaymentQr, PaymentQrParams, TrackingEvent, TrackingItem, TrackingLookup};
+use crate::order_items;
+use crate::order_numbers;
+use crate::order_status;
+use crate::phone;
+use crate::spam;
+use crate::vietqr;
+use crate::wearers;
+
+fn not_found() -> (StatusCode, Json<ApiResponse<()>>) {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+}
+
+/// Redacted view of an order: no contact details, measurements, notes or
+/// who changed the status
+fn load_tracking(conn: &Connection, id: &str) -> rusqlite::Result<Option<OrderTracking>> {
+    let order = conn
+        .query_row(
+            "SELECT status, total, fitting_date, delivery_date, created_at, COALESCE(order_number, '')
+             FROM orders WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((status, total, fitting_date, delivery_date, placed_at, order_number)) = order else {
+        return Ok(None);
+    };
+
+    let history = order_status::history(conn, id)
+        .into_iter()
+        .map(|change| TrackingEvent {
+            label: order_status::label(&change.to_status),
+            status: change.to_status,
+            at: change.created_at,
+        })
+        .collect();
+    let items = order_items::load_items(conn, id);
+    let order_wearers = wearers::load_wearers(conn, id);
+    let progress = (!order_wearers.is_empty()).then(|| wearers::progress(&order_wearers, &items));
+
+    Ok(Some(OrderTracking {
+        transfer_memo: order_numbers::transfer_memo(&order_number),
+        order_number,
+        status_label: order_status::label(&status),
+        status,
+        placed_at,
+        history,
+        fitting_date,
+        delivery_date,
+        items: items
+            .into_iter()
+            .map(|item| TrackingItem {
+                product_name: item.product_name,
+                variant: item.variant,
+                quantity: item.quantity,
+            })
+            .collect(),
+        total,
+        progress,
+    }))
+}
+
+/// GET /api/track/:code - Track an order by its tracking code (public)
+pub async fn track_order(
+    State(db): State<DbPool>,
+    Path(code): Path<String>,
+) -> Result<Json<ApiResponse<OrderTracking>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM orders WHERE tracking_code = ?1",
+            params![code.trim().to_lowercase()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?;
+    let id = id.ok_or_else(not_found)?;
+
+    let tracking = load_tracking(&conn, &id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?
+        .ok_or_else(not_found)?;
+    Ok(Json(ApiResponse::success(tracking)))
+}
+
+/// GET /api/track?phone=&order_number= - Track an order by phone and order number (public)
+///
+/// Both must match the same order; a wrong phone and an unknown number give
+/// the same answer so neither can be probed on its own. Clients that keep
+/// missing are throttled, as order numbers are easy to guess in sequence.
+pub async fn lookup_order(
+    State(db): State<DbPool>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(params): Query<TrackingLookup>,
+) -> Result<Json<ApiResponse<OrderTracking>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let ip = spam::client_ip(&headers, peer);
+    let now = Utc::now();
+    let conn = db.lock().unwrap();
+
+    if !spam::lookup_allowed(&conn, &ip, now) {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse::<()>::error("Too many lookups, please try again in a few minutes or use the link in your order confirmation")),
+        ));
+    }
+
+    // The whole number is required, typed with or without dashes
+    let number = order_numbers::compact(&params.order_number);
+    let id: Option<String> = match phone::normalize(&params.phone).filter(|_| !number.is_empty()) {
+        Some(phone) => conn
+            .query_row(
+                "SELECT id FROM orders WHERE customer_phone = ?1 AND replace(order_number, '-', '') = ?2",
+                params![phone, number],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?,
+        None => None,
+    };
+    let Some(id) = id else {
+        spam::record_lookup_miss(&conn, &ip, now)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?;
+        return Err(not_found());
+    };
+
+    let tracking = load_tracking(&conn, &id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?
+        .ok_or_else(not_found)?;
+    Ok(Json(ApiResponse::success(tracking)))
+}
+
+/// GET /api/track/:code/payment-qr - VietQR bank transfer for an order's deposit or balance (public)
+///
+/// `format=png` or `format=svg` returns the QR image itself, for an `<img>`
+/// on the tracking page or a printed invoice; the default JSON carries the
+/// raw payload and account details for apps that draw their own.
+pub async fn payment_qr(
+    State(db): State<DbPool>,
+    Path(code): Path<String>,
+    Query(params): Query<PaymentQrParams>,
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)> {
+    let format = params.format.as_deref().unwrap_or("json");
+    if !["json", "png", "svg"].contains(&format) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("format must be json, png or svg"))));
+    }
+    if params.purpose.as_deref().is_some_and(|p| p != "deposit" && p != "balance") {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("purpose must be deposit or balance"))));
+    }
+
+    let conn = db.lock().unwrap();
+    let order = conn
+        .query_row(
+            "SELECT status, COALESCE(total, 0), COALESCE(paid_amount, 0), COALESCE(order_number, '')
+             FROM orders WHERE tracking_code = ?1",
+            params![code.trim().to_lowercase()],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?, row.get::<_, String>(3)?)),
+        )
+        .optional()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string()))))?;
+    let (status, total, paid, order_number) = order.ok_or_else(not_found)?;
+    if status == "cancelled" {
+        return Err((StatusCode::CONFLICT, Json(ApiResponse::<()>::error("This order was cancelled"))));
+    }
+    let account = vietqr::load_account(&conn).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Bank transfer is not set up")))
+    })?;
+
+    let deposit = vietqr::deposit_due(total, paid, vietqr::deposit_percent(&conn));
+    let purpose = match params.purpose.as_deref() {
+        Some(purpose) => purpose,
+        None if paid <= 0 && deposit > 0 && deposit < total => "deposit",
+        None => "balance",
+    };
+    let amount = match purpose {
+        "deposit" => deposit,
+        _ => (total - paid).max(0),
+    };
+    if amount == 0 {
+        return Err((StatusCode::CONFLICT, Json(ApiResponse::<()>::error("Nothing is due for this order"))));
+    }
+
+    let memo = order_numbers::transfer_memo(&order_number);
+    let payload = vietqr::payload(&account, amount, &memo);
+    let image_error = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e)));
+    // The amount changes as payments come in, so a cached image would ask for the wrong sum
+    let no_store = (header::CACHE_CONTROL, "no-store");
+    Ok(match format {
+        "png" => ([(header::CONTENT_TYPE, "image/png"), no_store], vietqr::png(&payload).map_err(image_error)?).into_response(),
+        "svg" => ([(header::CONTENT_TYPE, "image/svg+xml"), no_store], vietqr::svg(&payload).map_err(image_error)?).into_response(),
+        _ => Json(ApiResponse::success(PaymentQr {
+            purpose: purpose.to_string(),
+            amount,
+            memo,
+            bank_bin: account.bin,
+            account_number: account.account_number,
+            account_name: account.account_name,
+            payload,
+        }))
+        .into_response(),
+    })
+}
@@ -0,0 +1,437 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::db::{to_json_array, DbPool};
+use crate::models::{
+    ApiResponse, BoardColumn, Claims, CreateTailor, CreateWorkAssignment, Tailor, TailorWorkload, UpdateTailor,
+    UpdateWorkAssignment, WorkAssignment, WorkshopParams,
+};
+use crate::order_status;
+use crate::phone;
+use crate::workshop::{self, TAILOR_SELECT};
+
+fn validate_tailor(stages: Option<&[String]>, hours_per_day: Option<f64>) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if let Some(stage) = stages.unwrap_or_default().iter().find(|s| !workshop::is_stage(s)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("Unknown stage: {} (cutting, sewing or finishing)", stage))),
+        ));
+    }
+    if hours_per_day.is_some_and(|h| !(h > 0.0 && h <= workshop::MAX_HOURS_PER_DAY)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("hours_per_day must be between 0 and 24"))));
+    }
+    Ok(())
+}
+
+fn normalize_phone(value: &str) -> Result<String, (StatusCode, Json<ApiResponse<()>>)> {
+    if value.trim().is_empty() {
+        return Ok(String::new());
+    }
+    phone::normalize(value).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid phone number")))
+    })
+}
+
+fn validate_assignment(
+    estimated_hours: Option<f64>,
+    due_date: Option<&str>,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if estimated_hours.is_some_and(|h| !(0.0..=workshop::MAX_ESTIMATED_HOURS).contains(&h)) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("estimated_hours must be between 0 and 500"))));
+    }
+    if let Some(date) = due_date.filter(|d| !d.is_empty()) {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("due_date must be YYYY-MM-DD")))
+        })?;
+    }
+    Ok(())
+}
+
+/// The tailor must exist, be active and take on `stage`
+fn check_tailor(conn: &Connection, tailor_id: &str, stage: &str) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let tailor = workshop::load_tailor(conn, tailor_id).optional().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Unknown tailor")))
+    })?;
+    if !tailor.active {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(&format!("{} is inactive", tailor.name)))));
+    }
+    if !tailor.stages.is_empty() && !tailor.stages.iter().any(|s| s == stage) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("{} doesn't work on {}", tailor.name, stage))),
+        ));
+    }
+    Ok(())
+}
+
+/// GET /api/tailors - List tailors, active ones first (auth required)
+pub async fn list_tailors(
+    State(db): State<DbPool>,
+) -> Result<Json<ApiResponse<Vec<Tailor>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let mut stmt = conn.prepare(&format!("{} ORDER BY active DESC, name ASC", TAILOR_SELECT)).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    let tailors: Vec<Tailor> = stmt.query_map([], workshop::tailor_from_row).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.filter_map(|r| r.ok()).collect();
+
+    Ok(Json(ApiResponse::success(tailors)))
+}
+
+/// POST /api/tailors - Create tailor profile (auth required)
+pub async fn create_tailor(
+    State(db): State<DbPool>,
+    Json(payload): Json<CreateTailor>,
+) -> Result<Json<ApiResponse<Tailor>>, (StatusCode, Json<ApiResponse<()>>)> {
+    if payload.name.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Tailor name is required"))));
+    }
+    validate_tailor(Some(&payload.stages), Some(payload.hours_per_day))?;
+    let phone = normalize_phone(&payload.phone)?;
+    let id = Uuid::new_v4().to_string();
+
+    let conn = db.lock().unwrap();
+    conn.execute(
+        "INSERT INTO tailors (id, name, phone, stages, hours_per_day, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            id,
+            payload.name.trim(),
+            phone,
+            to_json_array(&payload.stages),
+            payload.hours_per_day,
+            payload.notes.trim(),
+            Utc::now().to_rfc3339()
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let tailor = workshop::load_tailor(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(tailor)))
+}
+
+/// PUT /api/tailors/:id - Update tailor; existing assignments are kept (auth required)
+pub async fn update_tailor(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateTailor>,
+) -> Result<Json<ApiResponse<Tailor>>, (StatusCode, Json<ApiResponse<()>>)> {
+    validate_tailor(payload.stages.as_deref(), payload.hours_per_day)?;
+    let phone = payload.phone.as_deref().map(normalize_phone).transpose()?;
+
+    let conn = db.lock().unwrap();
+    let rows = conn.execute(
+        "UPDATE tailors SET
+            name = COALESCE(?1, name),
+            phone = COALESCE(?2, phone),
+            stages = COALESCE(?3, stages),
+            hours_per_day = COALESCE(?4, hours_per_day),
+            active = COALESCE(?5, active),
+            notes = COALESCE(?6, notes),
+            updated_at = ?7
+         WHERE id = ?8",
+        params![
+            payload.name.as_deref().map(str::trim).filter(|n| !n.is_empty()),
+            phone,
+            payload.stages.as_deref().map(to_json_array),
+            payload.hours_per_day,
+            payload.active,
+            payload.notes.as_deref().map(str::trim),
+            Utc::now().to_rfc3339(),
+            id
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Tailor not found"))));
+    }
+
+    let tailor = workshop::load_tailor(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(tailor)))
+}
+
+/// DELETE /api/tailors/:id - Delete a tailor who was never assigned work (auth required)
+///
+/// Tailors with assignment history should be deactivated instead.
+pub async fn delete_tailor(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let in_use: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM work_assignments WHERE tailor_id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if in_use {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("This tailor has assignments; set them inactive instead")),
+        ));
+    }
+
+    let rows = conn.execute("DELETE FROM tailors WHERE id = ?1", params![id]).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Tailor not found"))));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Tailor deleted".to_string()),
+    }))
+}
+
+/// GET /api/orders/:id/assignments - Who is making an order, per item and stage (auth required)
+pub async fn list_order_assignments(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<WorkAssignment>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM orders WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found"))));
+    }
+
+    let assignments = workshop::order_assignments(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(assignments)))
+}
+
+/// POST /api/orders/:id/assignments - Give a stage of an order, or of one item, to a tailor (auth required)
+///
+/// Each stage is assigned once per item (or once for the whole order);
+/// hand it to someone else by updating the assignment.
+pub async fn create_order_assignment(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateWorkAssignment>,
+) -> Result<Json<ApiResponse<WorkAssignment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let stage = payload.stage.trim();
+    if !workshop::is_stage(stage) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("stage must be cutting, sewing or finishing"))));
+    }
+    validate_assignment(Some(payload.estimated_hours), payload.due_date.as_deref())?;
+    let item_id = payload.order_item_id.as_deref().filter(|i| !i.is_empty());
+
+    let conn = db.lock().unwrap();
+
+    let (status, delivery_date): (String, Option<String>) = conn.query_row(
+        "SELECT status, delivery_date FROM orders WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).optional().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Order not found")))
+    })?;
+    if order_status::CLOSED_STATUSES.contains(&status.as_str()) {
+        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("This order is already delivered or cancelled"))));
+    }
+
+    if let Some(item_id) = item_id {
+        let on_order: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM order_items WHERE id = ?1 AND order_id = ?2",
+            params![item_id, id],
+            |row| row.get(0),
+        ).unwrap_or(false);
+        if !on_order {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Item is not part of this order"))));
+        }
+    }
+
+    check_tailor(&conn, &payload.tailor_id, stage)?;
+
+    let taken: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM work_assignments WHERE order_id = ?1 AND stage = ?2 AND order_item_id IS ?3",
+        params![id, stage, item_id],
+        |row| row.get(0),
+    ).unwrap_or(false);
+    if taken {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("This stage is already assigned; update that assignment instead")),
+        ));
+    }
+
+    let assignment_id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO work_assignments (id, order_id, order_item_id, stage, tailor_id, estimated_hours, due_date, notes,
+            assigned_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            assignment_id,
+            id,
+            item_id,
+            stage,
+            payload.tailor_id,
+            payload.estimated_hours,
+            payload.due_date.filter(|d| !d.is_empty()).or(delivery_date),
+            payload.notes.trim(),
+            claims.username,
+            Utc::now().to_rfc3339()
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let assignment = workshop::load_assignment(&conn, &assignment_id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(assignment)))
+}
+
+/// PUT /api/assignments/:id - Reassign, re-estimate or report progress on an assignment (auth required)
+///
+/// Moving to in_progress or done stamps when work started and finished;
+/// moving back clears the later stamps.
+pub async fn update_assignment(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateWorkAssignment>,
+) -> Result<Json<ApiResponse<WorkAssignment>>, (StatusCode, Json<ApiResponse<()>>)> {
+    validate_assignment(payload.estimated_hours, payload.due_date.as_deref())?;
+    if let Some(status) = payload.status.as_deref().filter(|s| !workshop::is_status(s)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("Unknown status: {} (todo, in_progress or done)", status))),
+        ));
+    }
+
+    let conn = db.lock().unwrap();
+
+    let current = workshop::load_assignment(&conn, &id).optional().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Assignment not found")))
+    })?;
+    if let Some(tailor_id) = payload.tailor_id.as_deref().filter(|t| *t != current.tailor_id) {
+        check_tailor(&conn, tailor_id, &current.stage)?;
+    }
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE work_assignments SET
+            tailor_id = COALESCE(?1, tailor_id),
+            estimated_hours = COALESCE(?2, estimated_hours),
+            due_date = CASE WHEN ?3 IS NULL THEN due_date ELSE NULLIF(?3, '') END,
+            started_at = CASE WHEN ?4 IS NULL THEN started_at WHEN ?4 = 'todo' THEN NULL ELSE COALESCE(started_at, ?6) END,
+            completed_at = CASE WHEN ?4 IS NULL THEN completed_at WHEN ?4 = 'done' THEN COALESCE(completed_at, ?6) ELSE NULL END,
+            status = COALESCE(?4, status),
+            notes = COALESCE(?5, notes),
+            updated_at = ?6
+         WHERE id = ?7",
+        params![
+            payload.tailor_id,
+            payload.estimated_hours,
+            payload.due_date,
+            payload.status,
+            payload.notes.as_deref().map(str::trim),
+            now,
+            id
+        ],
+    ).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+
+    let assignment = workshop::load_assignment(&conn, &id).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(assignment)))
+}
+
+/// DELETE /api/assignments/:id - Take a stage back from a tailor (auth required)
+pub async fn delete_assignment(
+    State(db): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let rows = conn.execute("DELETE FROM work_assignments WHERE id = ?1", params![id]).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    if rows == 0 {
+        return Err((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Assignment not found"))));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Assignment deleted".to_string()),
+    }))
+}
+
+/// GET /api/workshop/workload - Open work and hours queued per active tailor, or for `tailor_id` (auth required)
+pub async fn workshop_workload(
+    State(db): State<DbPool>,
+    Query(params): Query<WorkshopParams>,
+) -> Result<Json<ApiResponse<Vec<TailorWorkload>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let tailors: Vec<Tailor> = match params.tailor_id.as_deref().filter(|t| !t.is_empty()) {
+        Some(tailor_id) => vec![workshop::load_tailor(&conn, tailor_id).optional().map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?.ok_or_else(|| {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Tailor not found")))
+        })?],
+        None => {
+            let mut stmt = conn.prepare(&format!("{} WHERE active = 1 ORDER BY name ASC", TAILOR_SELECT)).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?;
+            let tailors = stmt.query_map([], workshop::tailor_from_row).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+            })?.filter_map(|r| r.ok()).collect();
+            tailors
+        }
+    };
+
+    let workloads = tailors
+        .into_iter()
+        .map(|tailor| workshop::workload(&conn, tailor))
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+        })?;
+    Ok(Json(ApiResponse::success(workloads)))
+}
+
+/// GET /api/workshop/board - Garments in production grouped by stage (auth required)
+///
+/// With `tailor_id`, only garments whose current stage is that tailor's.
+pub async fn workshop_board(
+    State(db): State<DbPool>,
+    Query(params): Query<WorkshopParams>,
+) -> Result<Json<ApiResponse<Vec<BoardColumn>>>, (StatusCode, Json<ApiResponse<()>>)> {
+    let conn = db.lock().unwrap();
+
+    let columns = workshop::board(&conn, params.tailor_id.as_deref().filter(|t| !t.is_empty())).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(&e.to_string())))
+    })?;
+    Ok(Json(ApiResponse::success(columns)))
+}
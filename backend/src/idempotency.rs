@@ -0,0 +1,80 @@
+use axum::http::HeaderMap;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+use crate::handlers::orders::load_order;
+use crate::models::Order;
+
+/// How long a key is remembered; retries after this create a new order
+const RETENTION_HOURS: i64 = 24;
+const MAX_KEY_LEN: usize = 255;
+
+pub enum Lookup {
+    /// First time this key is seen
+    New,
+    /// Same key and request as before: the response originally sent
+    Replay(Box<Order>),
+    /// Same key, different request
+    Mismatch,
+}
+
+/// The `Idempotency-Key` header, if sent. Keys are opaque to the server but
+/// must be printable ASCII, e.g. a UUID the client made for this submission.
+pub fn key_from(headers: &HeaderMap) -> Result<Option<String>, &'static str> {
+    let Some(value) = headers.get("idempotency-key") else {
+        return Ok(None);
+    };
+    let key = value.to_str().map_err(|_| "Invalid Idempotency-Key")?.trim();
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err("Invalid Idempotency-Key");
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Hash of the request body that ignores key order and whitespace.
+/// `serde_json::Value` keeps object keys sorted, so re-serializing it is canonical.
+pub fn request_hash(body: &serde_json::Value) -> String {
+    hex::encode(Sha256::digest(body.to_string().as_bytes()))
+}
+
+pub fn lookup(conn: &Connection, key: &str, request_hash: &str, now: DateTime<Utc>) -> rusqlite::Result<Lookup> {
+    let stored: Option<(String, String, String)> = conn
+        .query_row(
+            "SELECT request_hash, order_id, response FROM idempotency_keys WHERE key = ?1 AND created_at > ?2",
+            params![key, (now - Duration::hours(RETENTION_HOURS)).to_rfc3339()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    Ok(match stored {
+        None => Lookup::New,
+        Some((hash, _, _)) if hash != request_hash => Lookup::Mismatch,
+        Some((_, order_id, response)) => match serde_json::from_str(&response) {
+            Ok(order) => Lookup::Replay(Box::new(order)),
+            // Stored by an older version with a different shape: send the order as it is now
+            Err(_) => Lookup::Replay(Box::new(load_order(conn, &order_id)?)),
+        },
+    })
+}
+
+/// Remember the response to a key, in the same transaction as the order so a
+/// key is never stored without its order or the other way round
+pub fn save(
+    conn: &Connection,
+    key: &str,
+    request_hash: &str,
+    order: &Order,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM idempotency_keys WHERE created_at <= ?1",
+        params![(now - Duration::hours(RETENTION_HOURS)).to_rfc3339()],
+    )?;
+    let response = serde_json::to_string(order).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO idempotency_keys (key, request_hash, order_id, response, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![key, request_hash, order.id, response, now.to_rfc3339()],
+    )?;
+    Ok(())
+}
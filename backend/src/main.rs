@@ -1,6 +1,33 @@
+mod appointments;
+mod bundles;
+mod coupons;
+mod customers;
 mod db;
+mod documents;
 mod handlers;
+mod idempotency;
+mod measurements;
 mod models;
+mod options;
+mod order_export;
+mod order_filters;
+mod order_items;
+mod order_numbers;
+mod order_status;
+mod payments;
+mod pdf;
+mod phone;
+mod pricing;
+mod products;
+mod reconciliation;
+mod recommendations;
+mod rentals;
+mod sizing;
+mod spam;
+mod text;
+mod vietqr;
+mod wearers;
+mod workshop;
 
 use axum::{
     extract::{Request, State, DefaultBodyLimit},
@@ -63,11 +90,25 @@ async fn main() {
     let public_routes = Router::new()
         .route("/api/products", get(handlers::list_products))
         .route("/api/products/{id}", get(handlers::get_product))
+        .route("/api/products/{id}/related", get(handlers::related_products))
+        .route("/api/products/{id}/rental-availability", get(handlers::product_rental_availability))
         .route("/api/categories", get(handlers::list_categories))
         .route("/api/categories/{id}", get(handlers::get_category))
         .route("/api/orders", post(handlers::create_order))
+        .route("/api/orders/form-token", get(handlers::order_form_token))
+        .route("/api/track", get(handlers::lookup_order))
+        .route("/api/track/{code}", get(handlers::track_order))
+        .route("/api/track/{code}/payment-qr", get(handlers::payment_qr))
+        .route("/api/showrooms", get(handlers::list_showrooms))
+        .route("/api/showrooms/{id}/availability", get(handlers::showroom_availability))
+        .route("/api/appointments", post(handlers::book_appointment))
+        .route("/api/appointments/manage/{code}", get(handlers::get_managed_appointment))
+        .route("/api/appointments/manage/{code}", put(handlers::reschedule_managed_appointment))
+        .route("/api/appointments/manage/{code}", delete(handlers::cancel_managed_appointment))
+        .route("/api/coupons/validate", post(handlers::validate_coupon))
         .route("/api/settings", get(handlers::get_all_settings))
         .route("/api/settings/{key}", get(handlers::get_setting))
+        .route("/api/size-chart/recommend", post(handlers::recommend_size))
         .route("/api/auth/login", post(handlers::login));
     
     // Protected routes (require authentication)
@@ -75,13 +116,73 @@ async fn main() {
         .route("/api/products", post(handlers::create_product))
         .route("/api/products/{id}", put(handlers::update_product))
         .route("/api/products/{id}", delete(handlers::delete_product))
+        .route("/api/products/{id}/pairings", get(handlers::get_product_pairings))
+        .route("/api/products/{id}/pairings", put(handlers::update_product_pairings))
+        .route("/api/products/{id}/options", put(handlers::update_product_options))
         .route("/api/categories", post(handlers::create_category))
         .route("/api/categories/{id}", put(handlers::update_category))
         .route("/api/categories/{id}", delete(handlers::delete_category))
+        .route("/api/promotions", get(handlers::list_promotions))
+        .route("/api/promotions", post(handlers::create_promotion))
+        .route("/api/promotions/{id}", get(handlers::get_promotion))
+        .route("/api/promotions/{id}", put(handlers::update_promotion))
+        .route("/api/promotions/{id}", delete(handlers::delete_promotion))
+        .route("/api/coupons", get(handlers::list_coupons))
+        .route("/api/coupons", post(handlers::create_coupon))
+        .route("/api/coupons/{id}", get(handlers::get_coupon))
+        .route("/api/coupons/{id}", put(handlers::update_coupon))
+        .route("/api/coupons/{id}", delete(handlers::delete_coupon))
+        .route("/api/coupons/{id}/redemptions", get(handlers::list_coupon_redemptions))
         .route("/api/orders", get(handlers::list_orders))
+        .route("/api/orders/statuses", get(handlers::list_order_statuses))
+        .route("/api/orders/export", get(handlers::export_orders))
         .route("/api/orders/{id}", get(handlers::get_order))
+        .route("/api/orders/{id}/status", post(handlers::transition_order))
+        .route("/api/orders/{id}/payments", get(handlers::list_payments))
+        .route("/api/orders/{id}/payments", post(handlers::record_payment))
+        .route("/api/orders/{id}/invoice", get(handlers::order_invoice))
+        .route("/api/orders/{id}/work-ticket", get(handlers::order_work_ticket))
+        .route("/api/orders/{id}/wearers/{wearer_id}", put(handlers::update_wearer))
+        .route("/api/orders/{id}/assignments", get(handlers::list_order_assignments))
+        .route("/api/orders/{id}/assignments", post(handlers::create_order_assignment))
         .route("/api/orders/{id}", put(handlers::update_order))
         .route("/api/orders/{id}", delete(handlers::delete_order))
+        .route("/api/customers", get(handlers::list_customers))
+        .route("/api/customers", post(handlers::create_customer))
+        .route("/api/customers/{id}", get(handlers::get_customer))
+        .route("/api/customers/{id}", put(handlers::update_customer))
+        .route("/api/customers/{id}", delete(handlers::delete_customer))
+        .route("/api/customers/{id}/measurements", post(handlers::create_measurement_profile))
+        .route("/api/customers/{id}/measurements/{profile_id}", put(handlers::update_measurement_profile))
+        .route("/api/customers/{id}/measurements/{profile_id}", delete(handlers::delete_measurement_profile))
+        .route("/api/showrooms", post(handlers::create_showroom))
+        .route("/api/showrooms/{id}", put(handlers::update_showroom))
+        .route("/api/showrooms/{id}", delete(handlers::delete_showroom))
+        .route("/api/holidays", get(handlers::list_holidays))
+        .route("/api/holidays", post(handlers::create_holiday))
+        .route("/api/holidays/{id}", delete(handlers::delete_holiday))
+        .route("/api/appointments", get(handlers::list_appointments))
+        .route("/api/appointments/{id}", get(handlers::get_appointment))
+        .route("/api/appointments/{id}", put(handlers::update_appointment))
+        .route("/api/rentals", get(handlers::list_rentals))
+        .route("/api/rentals", post(handlers::create_rental))
+        .route("/api/rentals/{id}", get(handlers::get_rental))
+        .route("/api/rentals/{id}", put(handlers::update_rental))
+        .route("/api/rentals/{id}/pickup", post(handlers::pickup_rental))
+        .route("/api/rentals/{id}/return", post(handlers::return_rental))
+        .route("/api/rentals/{id}/cancel", post(handlers::cancel_rental))
+        .route("/api/bank-imports", get(handlers::list_bank_imports))
+        .route("/api/bank-imports", post(handlers::import_bank_statement))
+        .route("/api/bank-imports/{id}", get(handlers::get_bank_import))
+        .route("/api/bank-imports/{id}/reconcile", post(handlers::reconcile_bank_import))
+        .route("/api/tailors", get(handlers::list_tailors))
+        .route("/api/tailors", post(handlers::create_tailor))
+        .route("/api/tailors/{id}", put(handlers::update_tailor))
+        .route("/api/tailors/{id}", delete(handlers::delete_tailor))
+        .route("/api/assignments/{id}", put(handlers::update_assignment))
+        .route("/api/assignments/{id}", delete(handlers::delete_assignment))
+        .route("/api/workshop/workload", get(handlers::workshop_workload))
+        .route("/api/workshop/board", get(handlers::workshop_board))
         .route("/api/settings", put(handlers::update_settings))
         .route("/api/stats", get(handlers::get_stats))
         .route("/api/upload", post(handlers::upload_image))
@@ -103,7 +204,10 @@ async fn main() {
             ServeDir::new("uploads").precompressed_gzip(),
         )
         .layer(axum::middleware::map_response(|mut response: Response| async move {
-            // Add cache headers for upload files (7 days)
+            // Add cache headers for upload files (7 days), unless the handler set its own
+            if response.headers().contains_key(header::CACHE_CONTROL) {
+                return response;
+            }
             if let Some(ct) = response.headers().get(header::CONTENT_TYPE) {
                 let ct_str = ct.to_str().unwrap_or("");
                 if ct_str.starts_with("image/") {
@@ -123,6 +227,7 @@ async fn main() {
     println!("🌐 Server listening on http://{}", addr);
     
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // Peer addresses are needed to rate-limit storefront orders
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
 }
 
@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::text;
+
+const CM_PER_INCH: f64 = 2.54;
+
+/// Plausible range of each body measurement in centimetres (weight in kg)
+const RANGES: [(&str, f64, f64); 11] = [
+    ("bust", 60.0, 160.0),
+    ("waist", 45.0, 150.0),
+    ("hip", 60.0, 170.0),
+    ("shoulder", 28.0, 60.0),
+    ("sleeve_length", 40.0, 90.0),
+    ("ao_dai_length", 90.0, 170.0),
+    ("pants_length", 70.0, 130.0),
+    ("neck", 25.0, 55.0),
+    ("arm_circumference", 18.0, 50.0),
+    ("height", 130.0, 210.0),
+    ("weight", 30.0, 150.0),
+];
+
+/// Fields as printed for tailors, in measuring order
+pub const FIELD_LABELS: [(&str, &str); 11] = [
+    ("height", "Chiều cao"),
+    ("weight", "Cân nặng"),
+    ("neck", "Vòng cổ"),
+    ("shoulder", "Rộng vai"),
+    ("bust", "Vòng ngực"),
+    ("waist", "Vòng eo"),
+    ("hip", "Vòng mông"),
+    ("arm_circumference", "Vòng bắp tay"),
+    ("sleeve_length", "Dài tay"),
+    ("ao_dai_length", "Dài áo"),
+    ("pants_length", "Dài quần"),
+];
+
+/// Free-text labels (diacritics folded) recognised for each field
+const LABELS: [(&str, &str); 33] = [
+    ("vong bap tay", "arm_circumference"),
+    ("bap tay", "arm_circumference"),
+    ("arm", "arm_circumference"),
+    ("dai tay", "sleeve_length"),
+    ("tay", "sleeve_length"),
+    ("sleeve", "sleeve_length"),
+    ("dai ao", "ao_dai_length"),
+    ("ao dai", "ao_dai_length"),
+    ("ao", "ao_dai_length"),
+    ("dai quan", "pants_length"),
+    ("quan", "pants_length"),
+    ("pants", "pants_length"),
+    ("vong nguc", "bust"),
+    ("nguc", "bust"),
+    ("bust", "bust"),
+    ("vong eo", "waist"),
+    ("eo", "waist"),
+    ("waist", "waist"),
+    ("vong mong", "hip"),
+    ("mong", "hip"),
+    ("hong", "hip"),
+    ("hip", "hip"),
+    ("vai", "shoulder"),
+    ("shoulder", "shoulder"),
+    ("vong co", "neck"),
+    ("co", "neck"),
+    ("neck", "neck"),
+    ("chieu cao", "height"),
+    ("cao", "height"),
+    ("height", "height"),
+    ("can nang", "weight"),
+    ("nang", "weight"),
+    ("weight", "weight"),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementUnit {
+    #[default]
+    Cm,
+    Inch,
+}
+
+/// Body measurements for a made-to-measure garment.
+///
+/// Lengths and circumferences are in `unit`; height is too, while weight is
+/// always in kilograms. `notes` holds per-field remarks keyed by field name
+/// (e.g. `"waist": "đo sát"`), and `raw` keeps the original free text of
+/// measurements entered before the structured form existed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Measurements {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bust: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waist: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hip: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shoulder: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleeve_length: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ao_dai_length: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pants_length: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub neck: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arm_circumference: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[serde(default)]
+    pub unit: MeasurementUnit,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+/// Measurements as accepted from clients: the structured object, or the
+/// free text older storefront versions send
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MeasurementsInput {
+    Structured(Box<Measurements>),
+    Text(String),
+}
+
+impl MeasurementsInput {
+    /// Structured input must pass `validate`; free text is parsed leniently,
+    /// keeping only plausible values next to the original text
+    pub fn into_measurements(self) -> Result<Measurements, String> {
+        match self {
+            MeasurementsInput::Structured(m) => {
+                m.validate()?;
+                Ok(*m)
+            }
+            MeasurementsInput::Text(text) => Ok(Measurements::parse_text(&text)),
+        }
+    }
+}
+
+impl Measurements {
+    pub fn field(&self, name: &str) -> Option<f64> {
+        match name {
+            "bust" => self.bust,
+            "waist" => self.waist,
+            "hip" => self.hip,
+            "shoulder" => self.shoulder,
+            "sleeve_length" => self.sleeve_length,
+            "ao_dai_length" => self.ao_dai_length,
+            "pants_length" => self.pants_length,
+            "neck" => self.neck,
+            "arm_circumference" => self.arm_circumference,
+            "height" => self.height,
+            "weight" => self.weight,
+            _ => None,
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut Option<f64>> {
+        match name {
+            "bust" => Some(&mut self.bust),
+            "waist" => Some(&mut self.waist),
+            "hip" => Some(&mut self.hip),
+            "shoulder" => Some(&mut self.shoulder),
+            "sleeve_length" => Some(&mut self.sleeve_length),
+            "ao_dai_length" => Some(&mut self.ao_dai_length),
+            "pants_length" => Some(&mut self.pants_length),
+            "neck" => Some(&mut self.neck),
+            "arm_circumference" => Some(&mut self.arm_circumference),
+            "height" => Some(&mut self.height),
+            "weight" => Some(&mut self.weight),
+            _ => None,
+        }
+    }
+
+    /// Value of a field in centimetres (kilograms for weight)
+    pub fn field_cm(&self, name: &str) -> Option<f64> {
+        let value = self.field(name)?;
+        if name != "weight" && self.unit == MeasurementUnit::Inch {
+            Some(value * CM_PER_INCH)
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Unit a field's value is in, as printed after it
+    pub fn unit_label(&self, name: &str) -> &'static str {
+        match (name, self.unit) {
+            ("weight", _) => "kg",
+            (_, MeasurementUnit::Inch) => "in",
+            (_, MeasurementUnit::Cm) => "cm",
+        }
+    }
+
+    /// A field as printed, e.g. "84 cm" or "48.5 kg"
+    pub fn display(&self, name: &str) -> Option<String> {
+        let value = self.field(name)?;
+        let number = if value.fract() == 0.0 { format!("{}", value as i64) } else { format!("{:.1}", value) };
+        Some(format!("{} {}", number, self.unit_label(name)))
+    }
+
+    /// Every present field with its label, e.g. "Vòng ngực 84 cm; Vòng eo 66 cm"
+    pub fn summary(&self) -> String {
+        FIELD_LABELS
+            .iter()
+            .filter_map(|(name, label)| Some(format!("{} {}", label, self.display(name)?)))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        RANGES.iter().all(|(name, _, _)| self.field(name).is_none()) && self.notes.is_empty() && self.raw.is_none()
+    }
+
+    /// Check every present value against its plausible range
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = vec![];
+        for (name, min, max) in RANGES {
+            if let Some(value) = self.field_cm(name) {
+                if !(min..=max).contains(&value) {
+                    let unit = if name == "weight" { "kg" } else { "cm" };
+                    errors.push(format!("{} must be between {} and {} {}", name, min, max, unit));
+                }
+            }
+        }
+        for key in self.notes.keys() {
+            if !RANGES.iter().any(|(name, _, _)| name == key) {
+                errors.push(format!("Unknown measurement in notes: {}", key));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Read a stored value: structured JSON, or legacy free text
+    pub fn from_stored(value: &str) -> Measurements {
+        let value = value.trim();
+        if value.is_empty() {
+            return Measurements::default();
+        }
+        if value.starts_with('{') {
+            if let Ok(m) = serde_json::from_str::<Measurements>(value) {
+                return m;
+            }
+        }
+        Measurements::parse_text(value)
+    }
+
+    pub fn to_json(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Best-effort parse of free text such as
+    /// "Ngực 84, eo 66, mông 90, dài áo 135, cao 1m58, nặng 48kg" or "84-66-90".
+    ///
+    /// Values outside the plausible range are dropped; the original text is
+    /// always kept in `raw`.
+    pub fn parse_text(input: &str) -> Measurements {
+        let mut m = Measurements {
+            raw: Some(input.trim().to_string()).filter(|s| !s.is_empty()),
+            ..Default::default()
+        };
+        let folded = text::fold(input);
+        if folded.contains("inch") || input.contains('"') {
+            m.unit = MeasurementUnit::Inch;
+        }
+
+        let numbers = scan_numbers(&folded);
+        let mut consumed = vec![false; numbers.len()];
+
+        // "84-66-90" / "84/66/90" is bust-waist-hip
+        for i in 0..numbers.len().saturating_sub(2) {
+            let joined = (0..2).all(|k| {
+                let between = folded[numbers[i + k].end..numbers[i + k + 1].start].trim();
+                between == "-" || between == "/"
+            });
+            if joined && !consumed[i] {
+                m.bust = Some(numbers[i].value);
+                m.waist = Some(numbers[i + 1].value);
+                m.hip = Some(numbers[i + 2].value);
+                consumed[i..i + 3].iter_mut().for_each(|c| *c = true);
+            }
+        }
+
+        let mut previous_end = 0;
+        for (number, used) in numbers.iter().zip(&consumed) {
+            let label = &folded[previous_end..number.start];
+            previous_end = number.end;
+            if *used {
+                continue;
+            }
+            if let Some(field) = match_label(label) {
+                if let Some(slot) = m.field_mut(field) {
+                    slot.get_or_insert(number.value);
+                }
+            }
+        }
+
+        // Drop implausible values rather than rejecting legacy text
+        for (name, min, max) in RANGES {
+            if m.field_cm(name).is_some_and(|v| !(min..=max).contains(&v)) {
+                if let Some(slot) = m.field_mut(name) {
+                    *slot = None;
+                }
+            }
+        }
+        m
+    }
+}
+
+struct Number {
+    start: usize,
+    end: usize,
+    value: f64,
+}
+
+/// Find decimal numbers in folded text, reading "1m58" / "1.58m" as 158 cm
+fn scan_numbers(folded: &str) -> Vec<Number> {
+    let bytes = folded.as_bytes();
+    let mut numbers = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.' || bytes[i] == b',') {
+            // A comma or dot only counts as decimal separator between digits
+            if (bytes[i] == b'.' || bytes[i] == b',') && !bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit()) {
+                break;
+            }
+            i += 1;
+        }
+        let mut value: f64 = folded[start..i].replace(',', ".").parse().unwrap_or(0.0);
+        let mut end = i;
+
+        if bytes.get(i) == Some(&b'm') && value < 3.0 {
+            // "1m58" -> 158, "1.58m" -> 158
+            let digits_start = i + 1;
+            let mut j = digits_start;
+            while j < bytes.len() && bytes[j].is_ascii_digit() {
+                j += 1;
+            }
+            if j > digits_start && value.fract() == 0.0 {
+                let rest: f64 = folded[digits_start..j].parse().unwrap_or(0.0);
+                value = value * 100.0 + rest;
+                end = j;
+            } else if !bytes.get(i + 1).is_some_and(|b| b.is_ascii_alphabetic()) {
+                value *= 100.0;
+                end = i + 1;
+            }
+        }
+
+        numbers.push(Number { start, end, value });
+        i = end;
+    }
+    numbers
+}
+
+/// Field whose label appears closest to the end of `label` (longest wins on ties)
+fn match_label(label: &str) -> Option<&'static str> {
+    let mut best: Option<(usize, usize, &'static str)> = None;
+    for (keyword, field) in LABELS {
+        let Some(position) = rfind_word(label, keyword) else {
+            continue;
+        };
+        let end = position + keyword.len();
+        let better = match best {
+            None => true,
+            Some((best_end, best_len, _)) => end > best_end || (end == best_end && keyword.len() > best_len),
+        };
+        if better {
+            best = Some((end, keyword.len(), field));
+        }
+    }
+    best.map(|(_, _, field)| field)
+}
+
+/// Last occurrence of `word` in `haystack` that isn't part of a longer word
+fn rfind_word(haystack: &str, word: &str) -> Option<usize> {
+    haystack.rmatch_indices(word).map(|(i, _)| i).find(|&i| {
+        let before = haystack[..i].chars().next_back();
+        let after = haystack[i + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_labelled_text() {
+        let m = Measurements::parse_text("Ngực 84, eo 66, mông 90, dài áo 135, dài tay 58, cao 1m58, nặng 48kg");
+        assert_eq!(m.bust, Some(84.0));
+        assert_eq!(m.waist, Some(66.0));
+        assert_eq!(m.hip, Some(90.0));
+        assert_eq!(m.ao_dai_length, Some(135.0));
+        assert_eq!(m.sleeve_length, Some(58.0));
+        assert_eq!(m.height, Some(158.0));
+        assert_eq!(m.weight, Some(48.0));
+        assert_eq!(m.unit, MeasurementUnit::Cm);
+        assert_eq!(m.raw.as_deref(), Some("Ngực 84, eo 66, mông 90, dài áo 135, dài tay 58, cao 1m58, nặng 48kg"));
+    }
+
+    #[test]
+    fn longest_label_nearest_the_number_wins() {
+        assert_eq!(match_label("vong bap tay "), Some("arm_circumference"));
+        assert_eq!(match_label(", dai ao "), Some("ao_dai_length"));
+        assert_eq!(match_label("eo 66, dai tay "), Some("sleeve_length"));
+        assert_eq!(match_label("khong ro "), None);
+        // Labels must be whole words: "co" is not read inside "cocktail"
+        assert_eq!(match_label("cocktail "), None);
+    }
+
+    #[test]
+    fn scans_heights_and_decimals() {
+        let values: Vec<f64> = scan_numbers("cao 1.58m, eo 66,5, hip 90.").iter().map(|n| n.value).collect();
+        assert_eq!(values, vec![158.0, 66.5, 90.0]);
+    }
+
+    #[test]
+    fn reads_unlabelled_bust_waist_hip() {
+        for input in ["84-66-90", "84 / 66 / 90"] {
+            let m = Measurements::parse_text(input);
+            assert_eq!((m.bust, m.waist, m.hip), (Some(84.0), Some(66.0), Some(90.0)), "{}", input);
+        }
+    }
+
+    #[test]
+    fn reads_inches() {
+        let m = Measurements::parse_text("bust 33\", waist 26\", hip 35\"");
+        assert_eq!(m.unit, MeasurementUnit::Inch);
+        assert_eq!((m.bust, m.waist, m.hip), (Some(33.0), Some(26.0), Some(35.0)));
+        assert!(m.validate().is_ok());
+    }
+
+    #[test]
+    fn drops_out_of_range_values() {
+        let m = Measurements::parse_text("ngực 840, eo 66, cao 3m");
+        assert_eq!(m.bust, None);
+        assert_eq!(m.waist, Some(66.0));
+        assert_eq!(m.height, None);
+        assert!(m.validate().is_ok());
+
+        // 66 inches is far too wide a waist
+        let m = Measurements::parse_text("waist 66 inch");
+        assert_eq!(m.waist, None);
+        assert_eq!(m.raw.as_deref(), Some("waist 66 inch"));
+    }
+}
@@ -1,115 +1,1425 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-/// Product model
+use crate::measurements::{Measurements, MeasurementsInput};
+
+/// Product model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Product {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub price: i64,
+    #[serde(default = "default_images")]
+    pub images: Vec<String>,
+    #[serde(default = "default_category")]
+    pub category: String,
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default)]
+    pub sort_order: i32,
+    /// Original ("compare-at") price shown struck through on the storefront
+    #[serde(default)]
+    pub compare_at_price: Option<i64>,
+    /// Scheduled sale price, only applied between `sale_starts_at` and `sale_ends_at`
+    #[serde(default)]
+    pub sale_price: Option<i64>,
+    #[serde(default)]
+    pub sale_starts_at: Option<String>,
+    #[serde(default)]
+    pub sale_ends_at: Option<String>,
+    /// Price the customer pays right now (computed, see `pricing::apply_pricing`)
+    #[serde(default)]
+    pub effective_price: i64,
+    /// Discount of `effective_price` against the compare-at price, in percent
+    #[serde(default)]
+    pub discount_percent: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_name: Option<String>,
+    /// `single` or `bundle`
+    #[serde(default = "default_product_type")]
+    pub product_type: String,
+    /// Bundle price as a discount off the components' combined list price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_discount_percent: Option<i64>,
+    /// Units on hand; `None` means made to order (not tracked). Derived from components for bundles.
+    #[serde(default)]
+    pub stock: Option<i64>,
+    #[serde(default = "default_true")]
+    pub in_stock: bool,
+    /// Price per day when the product can be rented; `None` when it is sale-only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rental_price_per_day: Option<i64>,
+    #[serde(default)]
+    pub rental_deposit: i64,
+    /// Days a returned piece spends being cleaned before it can go out again
+    #[serde(default)]
+    pub rental_buffer_days: i64,
+    /// Pieces for rent per variant; `""` is the product without a variant
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rental_units: BTreeMap<String, i64>,
+    /// Bundle contents, only expanded on single-product responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<BundleComponent>>,
+    /// Customization choices (fabric, embroidery...), only expanded on single-product responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub option_groups: Option<Vec<ProductOptionGroup>>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+fn default_images() -> Vec<String> {
+    vec![]
+}
+
+fn default_product_type() -> String {
+    "single".to_string()
+}
+
+fn default_category() -> String {
+    "ao-dai".to_string()
+}
+
+fn default_status() -> String {
+    "active".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProduct {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub price: i64,
+    #[serde(default)]
+    pub images: Vec<String>,
+    #[serde(default = "default_category")]
+    pub category: String,
+    #[serde(default)]
+    pub sort_order: i32,
+    pub compare_at_price: Option<i64>,
+    pub sale_price: Option<i64>,
+    pub sale_starts_at: Option<String>,
+    pub sale_ends_at: Option<String>,
+    #[serde(default = "default_product_type")]
+    pub product_type: String,
+    pub bundle_discount_percent: Option<i64>,
+    pub stock: Option<i64>,
+    #[serde(default)]
+    pub components: Vec<BundleItemInput>,
+    pub rental_price_per_day: Option<i64>,
+    pub rental_deposit: Option<i64>,
+    pub rental_buffer_days: Option<i64>,
+    pub rental_units: Option<BTreeMap<String, i64>>,
+}
+
+/// `sale_price`/`compare_at_price`/`bundle_discount_percent`/`rental_price_per_day`
+/// of 0, a negative `stock` and empty sale dates clear the stored value
+#[derive(Debug, Deserialize)]
+pub struct UpdateProduct {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<i64>,
+    pub images: Option<Vec<String>>,
+    pub category: Option<String>,
+    pub status: Option<String>,
+    pub sort_order: Option<i32>,
+    pub compare_at_price: Option<i64>,
+    pub sale_price: Option<i64>,
+    pub sale_starts_at: Option<String>,
+    pub sale_ends_at: Option<String>,
+    pub bundle_discount_percent: Option<i64>,
+    pub stock: Option<i64>,
+    /// Replaces the bundle contents when present
+    pub components: Option<Vec<BundleItemInput>>,
+    pub rental_price_per_day: Option<i64>,
+    pub rental_deposit: Option<i64>,
+    pub rental_buffer_days: Option<i64>,
+    /// Replaces the rental pieces when present
+    pub rental_units: Option<BTreeMap<String, i64>>,
+}
+
+/// One component of a bundle as stored
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleItemInput {
+    pub product_id: String,
+    /// Free-form variant label such as a size ("M") or colour
+    #[serde(default)]
+    pub variant: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i64,
+}
+
+/// Expanded bundle component with the component's current pricing and stock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleComponent {
+    pub product_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    pub category: String,
+    #[serde(default)]
+    pub variant: String,
+    pub quantity: i64,
+    pub price: i64,
+    pub effective_price: i64,
+    #[serde(default)]
+    pub stock: Option<i64>,
+}
+
+/// Group of customization choices on a product, e.g. "Chất liệu" or "May gấp"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductOptionGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+    /// Maximum number of choices a customer may pick in this group
+    #[serde(default = "default_max_select")]
+    pub max_select: i64,
+    pub choices: Vec<ProductOptionChoice>,
+}
+
+fn default_max_select() -> i64 {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductOptionChoice {
+    pub id: String,
+    pub name: String,
+    /// Amount added to (or, if negative, taken off) the product price, in VND
+    #[serde(default)]
+    pub price_delta: i64,
+}
+
+/// Option group as submitted by the admin; ids are kept when given so
+/// storefront selections stay valid across edits
+#[derive(Debug, Deserialize)]
+pub struct OptionGroupInput {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default = "default_max_select")]
+    pub max_select: i64,
+    #[serde(default)]
+    pub choices: Vec<OptionChoiceInput>,
+    /// Name of a JSON-array setting (e.g. `materials`) whose entries are added as extra choices
+    pub choices_from_setting: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptionChoiceInput {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub price_delta: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProductOptions {
+    pub groups: Vec<OptionGroupInput>,
+}
+
+/// Customer's pick in one option group
+#[derive(Debug, Clone, Deserialize)]
+pub struct SelectedOptionInput {
+    pub group_id: String,
+    #[serde(default)]
+    pub choice_ids: Vec<String>,
+}
+
+/// Snapshot of a chosen option stored on the order, so later catalog edits
+/// don't change what the customer ordered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectedOption {
+    pub group_id: String,
+    pub group_name: String,
+    pub choice_id: String,
+    pub choice_name: String,
+    pub price_delta: i64,
+}
+
+/// Product suggested alongside another, with the signals that ranked it
+#[derive(Debug, Serialize)]
+pub struct RelatedProduct {
+    #[serde(flatten)]
+    pub product: Product,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RelatedParams {
+    pub limit: Option<usize>,
+}
+
+/// Replaces a product's curated "pairs well with" list, in display order
+#[derive(Debug, Deserialize)]
+pub struct UpdatePairings {
+    pub product_ids: Vec<String>,
+}
+
+/// Percentage promotion applied to one category, or the whole catalog when `category` is empty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promotion {
+    pub id: String,
+    pub name: String,
+    pub discount_percent: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePromotion {
+    pub name: String,
+    pub discount_percent: i64,
+    pub category: Option<String>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+}
+
+/// Empty `category`/`starts_at`/`ends_at` strings clear the stored value
+#[derive(Debug, Deserialize)]
+pub struct UpdatePromotion {
+    pub name: Option<String>,
+    pub discount_percent: Option<i64>,
+    pub category: Option<String>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub active: Option<bool>,
+}
+
+/// Order model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub id: String,
+    /// Sequential number staff read out over the phone, e.g. "HLV-2026-00042"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<String>,
+    /// Reference for the bank-transfer memo (the order number without dashes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_memo: Option<String>,
+    /// Customer record matched by phone number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<String>,
+    pub customer_name: String,
+    pub customer_phone: String,
+    #[serde(default)]
+    pub customer_email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    #[serde(default)]
+    pub product_name: String,
+    #[serde(default)]
+    pub measurements: Measurements,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default = "default_order_status")]
+    pub status: String,
+    /// Order value before discounts, in VND
+    #[serde(default)]
+    pub subtotal: i64,
+    #[serde(default)]
+    pub discount_amount: i64,
+    #[serde(default)]
+    pub total: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coupon_code: Option<String>,
+    /// Received so far, net of refunds
+    #[serde(default)]
+    pub paid_amount: i64,
+    /// `total - paid_amount`; negative when the customer is owed money
+    #[serde(default)]
+    pub balance_due: i64,
+    /// unpaid, deposit, paid or refunded
+    #[serde(default = "default_payment_status")]
+    pub payment_status: String,
+    /// Payment ledger, only included on single-order responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payments: Option<Vec<Payment>>,
+    #[serde(default)]
+    pub items: Vec<OrderItem>,
+    /// Date promised for handing over the garments (YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_date: Option<String>,
+    /// Expected fitting appointment (YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fitting_date: Option<String>,
+    /// Unguessable code the customer uses to follow the order on the storefront
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracking_code: Option<String>,
+    /// Username of the staff member in charge of the order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    /// People a group order is sewn for; empty for ordinary orders
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wearers: Vec<OrderWearer>,
+    /// Garment progress of a group order, e.g. 5 of 8 ready
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<GroupProgress>,
+    /// Full transition log, only included on single-order responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_history: Option<Vec<StatusChange>>,
+    /// Statuses this order may move to next, only included on single-order responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_statuses: Option<Vec<String>>,
+    /// Storefront order that tripped a spam check; hidden from the order list by default
+    #[serde(default)]
+    pub suspected_spam: bool,
+    /// Which checks it tripped, e.g. "honeypot", "submitted_too_fast"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spam_reasons: Vec<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+fn default_order_status() -> String {
+    "pending".to_string()
+}
+
+fn default_payment_status() -> String {
+    "unpaid".to_string()
+}
+
+/// Money received for an order, or paid back when `amount` is negative
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payment {
+    pub id: String,
+    pub order_id: String,
+    /// VND; refunds are negative
+    pub amount: i64,
+    /// cash, bank_transfer or e_wallet
+    pub method: String,
+    pub method_label: String,
+    /// Bank or wallet transaction id
+    #[serde(default)]
+    pub reference: String,
+    /// Staff member who recorded it
+    pub received_by: String,
+    #[serde(default)]
+    pub note: String,
+    pub paid_at: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePayment {
+    /// Negative to record a refund
+    pub amount: i64,
+    pub method: String,
+    #[serde(default)]
+    pub reference: String,
+    #[serde(default)]
+    pub note: String,
+    /// When the money changed hands (RFC 3339 or YYYY-MM-DD), now by default
+    pub paid_at: Option<String>,
+}
+
+/// An uploaded bank statement and how far its transfers have been reconciled
+#[derive(Debug, Clone, Serialize)]
+pub struct BankImport {
+    pub id: String,
+    pub filename: String,
+    pub imported_by: String,
+    /// Incoming transfers read from the file
+    pub row_count: i64,
+    pub proposed_count: i64,
+    pub unmatched_count: i64,
+    pub duplicate_count: i64,
+    pub confirmed_count: i64,
+    pub ignored_count: i64,
+    pub created_at: String,
+    /// Only included on single-import responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<BankTransaction>>,
+}
+
+/// An incoming transfer from a statement with its proposed or confirmed order
+#[derive(Debug, Clone, Serialize)]
+pub struct BankTransaction {
+    pub id: String,
+    pub import_id: String,
+    /// Line in the uploaded file, 1-based
+    pub row_number: i64,
+    /// YYYY-MM-DD
+    pub transaction_date: String,
+    /// VND received
+    pub amount: i64,
+    pub memo: String,
+    pub reference: String,
+    pub order_id: Option<String>,
+    pub order_number: Option<String>,
+    pub customer_name: Option<String>,
+    /// 0-100: how sure the match is
+    pub confidence: i64,
+    pub match_reason: String,
+    /// What the order still owed when matched (the deposit for a deposit transfer)
+    pub expected_amount: Option<i64>,
+    /// exact, deposit, underpaid or overpaid; empty when unmatched
+    pub amount_status: String,
+    /// proposed, unmatched, duplicate, confirmed or ignored
+    pub status: String,
+    /// Ledger entry recorded on confirmation
+    pub payment_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReconcileBankImport {
+    /// Transfers to record as payments
+    #[serde(default)]
+    pub confirm: Vec<ConfirmBankMatch>,
+    /// Transaction ids to set aside, e.g. transfers that aren't order payments
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmBankMatch {
+    pub transaction_id: String,
+    /// Order to credit; the proposed match when omitted
+    pub order_id: Option<String>,
+}
+
+/// Tailor or other workshop staff member production stages are assigned to
+#[derive(Debug, Clone, Serialize)]
+pub struct Tailor {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub phone: String,
+    /// Stages they take on (cutting, sewing, finishing); empty means any
+    pub stages: Vec<String>,
+    /// Working hours in a day, to turn queued hours into days of work
+    pub hours_per_day: f64,
+    pub active: bool,
+    #[serde(default)]
+    pub notes: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTailor {
+    pub name: String,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub stages: Vec<String>,
+    #[serde(default = "default_hours_per_day")]
+    pub hours_per_day: f64,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn default_hours_per_day() -> f64 {
+    8.0
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTailor {
+    pub name: Option<String>,
+    pub phone: Option<String>,
+    pub stages: Option<Vec<String>>,
+    pub hours_per_day: Option<f64>,
+    pub active: Option<bool>,
+    pub notes: Option<String>,
+}
+
+/// A production stage of an order, or of one of its items, given to a tailor
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkAssignment {
+    pub id: String,
+    pub order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<String>,
+    pub customer_name: String,
+    /// The item this is for; absent when it covers the whole order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_item_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_name: Option<String>,
+    /// cutting, sewing or finishing
+    pub stage: String,
+    pub stage_label: String,
+    pub tailor_id: String,
+    pub tailor_name: String,
+    pub estimated_hours: f64,
+    /// YYYY-MM-DD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    /// todo, in_progress or done
+    pub status: String,
+    pub status_label: String,
+    /// Due before today and not done
+    pub overdue: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_by: Option<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWorkAssignment {
+    /// One item of the order; the whole order when omitted
+    pub order_item_id: Option<String>,
+    pub stage: String,
+    pub tailor_id: String,
+    #[serde(default)]
+    pub estimated_hours: f64,
+    /// YYYY-MM-DD; the order's delivery date when omitted
+    pub due_date: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWorkAssignment {
+    pub tailor_id: Option<String>,
+    pub estimated_hours: Option<f64>,
+    /// Empty string clears it
+    pub due_date: Option<String>,
+    pub status: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Open work queued for one tailor
+#[derive(Debug, Serialize)]
+pub struct TailorWorkload {
+    pub tailor: Tailor,
+    /// Assignments not done yet, soonest due first
+    pub assignments: Vec<WorkAssignment>,
+    pub open_hours: f64,
+    pub overdue: i64,
+    /// `open_hours` in working days at the tailor's `hours_per_day`
+    pub days_of_work: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkshopParams {
+    /// Only this tailor's work
+    pub tailor_id: Option<String>,
+}
+
+/// Garments in production grouped by the stage they are at
+#[derive(Debug, Serialize)]
+pub struct BoardColumn {
+    /// cutting, sewing, finishing, or ready once every stage is done
+    pub stage: String,
+    pub label: String,
+    pub cards: Vec<BoardCard>,
+}
+
+/// One garment line of an order in production, or the whole order when it has no items
+#[derive(Debug, Serialize)]
+pub struct BoardCard {
+    pub order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<String>,
+    pub customer_name: String,
+    pub order_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_item_id: Option<String>,
+    pub product_name: String,
+    #[serde(default)]
+    pub variant: String,
+    pub quantity: i64,
+    /// Who has the current stage; absent while nobody is assigned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignment: Option<WorkAssignment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrder {
+    pub customer_name: String,
+    pub customer_phone: String,
+    #[serde(default)]
+    pub customer_email: String,
+    pub product_id: Option<String>,
+    #[serde(default)]
+    pub product_name: String,
+    /// Structured measurements, or legacy free text
+    pub measurements: Option<MeasurementsInput>,
+    #[serde(default)]
+    pub notes: String,
+    pub coupon_code: Option<String>,
+    /// Options for the single `product_id` when `items` is empty
+    #[serde(default)]
+    pub selected_options: Vec<SelectedOptionInput>,
+    /// Reuse a saved measurement profile of this phone's customer when `measurements` is absent
+    pub measurement_profile_id: Option<String>,
+    /// Save the order's measurements as a profile under this name
+    pub save_measurements_as: Option<String>,
+    /// Order lines; older clients send a single `product_id` instead
+    #[serde(default)]
+    pub items: Vec<OrderItemInput>,
+    /// Wedding party or family members, each with their own garment
+    #[serde(default)]
+    pub wearers: Vec<WearerInput>,
+    pub delivery_date: Option<String>,
+    /// Signed token from `GET /api/orders/form-token`
+    pub form_token: Option<String>,
+    /// Honeypot: hidden on the storefront form, so only bots fill it in
+    #[serde(default)]
+    pub website: String,
+}
+
+/// Signed token the storefront order form sends back with the order
+#[derive(Debug, Serialize)]
+pub struct FormToken {
+    pub token: String,
+    pub expires_at: String,
+}
+
+/// Someone a group order is sewn for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderWearer {
+    pub id: String,
+    pub name: String,
+    /// Bride, groom, mother of the bride...
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub measurements: Measurements,
+    /// See `wearers::FITTING_STATUSES`
+    pub fitting_status: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WearerInput {
+    pub name: String,
+    #[serde(default)]
+    pub role: String,
+    pub measurements: Option<MeasurementsInput>,
+    /// Saved profile of the ordering customer, used when `measurements` is absent
+    pub measurement_profile_id: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    pub product_id: String,
+    #[serde(default)]
+    pub variant: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i64,
+    #[serde(default)]
+    pub selected_options: Vec<SelectedOptionInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWearer {
+    pub name: Option<String>,
+    pub role: Option<String>,
+    pub measurements: Option<MeasurementsInput>,
+    pub fitting_status: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Garment counts of a group order, by the fitting status of their wearer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupProgress {
+    pub garments: i64,
+    pub ready: i64,
+    pub delivered: i64,
+    pub by_status: BTreeMap<String, i64>,
+}
+
+/// One line of an order, priced when the order was placed (VND)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderItem {
+    pub id: String,
+    pub product_id: Option<String>,
+    pub product_name: String,
+    #[serde(default)]
+    pub variant: String,
+    pub quantity: i64,
+    /// Product price per unit at order time
+    pub unit_price: i64,
+    /// Sum of the selected options' price deltas, per unit
+    #[serde(default)]
+    pub options_price: i64,
+    /// (unit_price + options_price) * quantity, before discount
+    pub line_total: i64,
+    /// Share of the order's coupon discount applied to this line
+    #[serde(default)]
+    pub discount_amount: i64,
+    #[serde(default)]
+    pub selected_options: Vec<SelectedOption>,
+    #[serde(default)]
+    pub measurements: Measurements,
+    /// Group-order wearer this garment is for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wearer_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderItemInput {
+    pub product_id: String,
+    /// Size or colour, free text
+    #[serde(default)]
+    pub variant: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i64,
+    #[serde(default)]
+    pub selected_options: Vec<SelectedOptionInput>,
+    /// Measurements of whoever wears this item, when not the orderer's
+    pub measurements: Option<MeasurementsInput>,
+}
+
+fn default_quantity() -> i64 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrder {
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    pub customer_email: Option<String>,
+    /// Rejected: an order's products can't be changed once placed
+    pub product_id: Option<String>,
+    /// Rejected, like `product_id`
+    pub product_name: Option<String>,
+    pub measurements: Option<MeasurementsInput>,
+    pub notes: Option<String>,
+    pub status: Option<String>,
+    /// Empty string clears it
+    pub delivery_date: Option<String>,
+    /// Empty string clears it
+    pub fitting_date: Option<String>,
+    /// Mark as spam, or clear a wrong suspicion
+    pub suspected_spam: Option<bool>,
+    /// Staff username in charge of the order; empty string clears it
+    pub assigned_to: Option<String>,
+}
+
+/// What a customer sees when tracking an order: no contact details,
+/// measurements, prices of other lines or staff notes
+#[derive(Debug, Serialize)]
+pub struct OrderTracking {
+    pub order_number: String,
+    pub transfer_memo: String,
+    pub status: String,
+    pub status_label: String,
+    pub placed_at: String,
+    pub history: Vec<TrackingEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fitting_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_date: Option<String>,
+    pub items: Vec<TrackingItem>,
+    pub total: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<GroupProgress>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackingEvent {
+    pub status: String,
+    pub label: String,
+    pub at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackingItem {
+    pub product_name: String,
+    pub variant: String,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrackingLookup {
+    pub phone: String,
+    pub order_number: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentQrParams {
+    /// "deposit" or "balance"; the deposit while nothing is paid, the balance after
+    pub purpose: Option<String>,
+    /// "json" (default), "png" or "svg"
+    pub format: Option<String>,
+}
+
+/// Bank transfer details for an order, with the VietQR payload to render
+#[derive(Debug, Serialize)]
+pub struct PaymentQr {
+    pub purpose: String,
+    pub amount: i64,
+    /// Transfer memo: the order number without dashes
+    pub memo: String,
+    pub bank_bin: String,
+    pub account_number: String,
+    pub account_name: String,
+    /// EMVCo string any VietQR-capable banking app can scan
+    pub payload: String,
+}
+
+/// Place customers come to for measuring and fittings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Showroom {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub phone: String,
+    /// Opening ranges per weekday, e.g. `{"mon": ["09:00-12:00", "13:30-18:00"]}`; missing days are closed
+    pub working_hours: BTreeMap<String, Vec<String>>,
+    /// Length of one appointment
+    pub slot_minutes: i64,
+    /// Appointments that can share a slot (fitting rooms, staff on duty)
+    pub capacity: i64,
+    pub active: bool,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShowroom {
+    pub name: String,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub phone: String,
+    /// Defaults to Mon-Sat 9:00-18:00 with a lunch break
+    pub working_hours: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(default = "default_slot_minutes")]
+    pub slot_minutes: i64,
+    #[serde(default = "default_capacity")]
+    pub capacity: i64,
+}
+
+fn default_slot_minutes() -> i64 {
+    30
+}
+
+fn default_capacity() -> i64 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateShowroom {
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub working_hours: Option<BTreeMap<String, Vec<String>>>,
+    pub slot_minutes: Option<i64>,
+    pub capacity: Option<i64>,
+    pub active: Option<bool>,
+}
+
+/// Day a showroom is closed; without a showroom it applies to all of them
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Product {
+pub struct Holiday {
     pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showroom_id: Option<String>,
+    pub date: String,
+    #[serde(default)]
     pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateHoliday {
+    pub showroom_id: Option<String>,
+    pub date: String,
     #[serde(default)]
-    pub description: String,
-    pub price: i64,
-    #[serde(default = "default_images")]
-    pub images: Vec<String>,
-    #[serde(default = "default_category")]
-    pub category: String,
-    #[serde(default = "default_status")]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HolidayListParams {
+    /// Only holidays on or after this date (YYYY-MM-DD)
+    pub from: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityParams {
+    /// First day (YYYY-MM-DD), today by default
+    pub from: Option<String>,
+    #[serde(default = "default_availability_days")]
+    pub days: i64,
+}
+
+fn default_availability_days() -> i64 {
+    7
+}
+
+/// Bookable slots of one day
+#[derive(Debug, Serialize)]
+pub struct DaySlots {
+    pub date: String,
+    /// Holiday name or "Đóng cửa" when the showroom is closed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_reason: Option<String>,
+    pub slots: Vec<Slot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Slot {
+    pub starts_at: String,
+    pub ends_at: String,
+    /// Bookings the slot can still take; 0 when full
+    pub remaining: i64,
+}
+
+/// Measuring session, fitting or pickup at a showroom
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Appointment {
+    pub id: String,
+    pub showroom_id: String,
+    pub showroom_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<String>,
+    pub customer_name: String,
+    pub customer_phone: String,
+    /// measuring, fitting or pickup
+    pub kind: String,
+    pub kind_label: String,
+    /// Shop time, RFC 3339
+    pub starts_at: String,
+    pub ends_at: String,
+    /// booked, completed, cancelled or no_show
     pub status: String,
     #[serde(default)]
-    pub sort_order: i32,
+    pub notes: String,
+    /// Lets the customer reschedule or cancel without an account
+    pub manage_code: String,
     pub created_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<String>,
 }
 
-fn default_images() -> Vec<String> {
-    vec![]
+#[derive(Debug, Deserialize)]
+pub struct CreateAppointment {
+    pub showroom_id: String,
+    /// Shop time "YYYY-MM-DDTHH:MM" or RFC 3339
+    pub starts_at: String,
+    #[serde(default = "default_appointment_kind")]
+    pub kind: String,
+    pub customer_name: String,
+    pub customer_phone: String,
+    #[serde(default)]
+    pub notes: String,
+    /// Links the visit to the customer's order; `order_id` works for staff only
+    pub tracking_code: Option<String>,
+    pub order_id: Option<String>,
 }
 
-fn default_category() -> String {
-    "ao-dai".to_string()
+fn default_appointment_kind() -> String {
+    "measuring".to_string()
 }
 
-fn default_status() -> String {
-    "active".to_string()
+/// Staff changes to an appointment
+#[derive(Debug, Deserialize)]
+pub struct UpdateAppointment {
+    pub showroom_id: Option<String>,
+    pub starts_at: Option<String>,
+    pub kind: Option<String>,
+    pub status: Option<String>,
+    pub notes: Option<String>,
+    /// Empty string unlinks the order
+    pub order_id: Option<String>,
 }
 
+/// Customer moving their own appointment
 #[derive(Debug, Deserialize)]
-pub struct CreateProduct {
-    pub name: String,
+pub struct RescheduleAppointment {
+    pub starts_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarParams {
+    /// Any day in the period (YYYY-MM-DD), today by default
+    pub date: Option<String>,
+    /// "day" (default) or "week", Monday to Sunday
+    pub view: Option<String>,
+    pub showroom_id: Option<String>,
+    /// All appointments of one order, whatever their date
+    pub order_id: Option<String>,
+    /// Include cancelled appointments
     #[serde(default)]
-    pub description: String,
-    pub price: i64,
+    pub include_cancelled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarDay {
+    pub date: String,
+    pub appointments: Vec<Appointment>,
+}
+
+/// One piece of a product variant rented out for a date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rental {
+    pub id: String,
+    pub product_id: String,
+    pub product_name: String,
     #[serde(default)]
-    pub images: Vec<String>,
-    #[serde(default = "default_category")]
-    pub category: String,
+    pub variant: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<String>,
+    pub customer_name: String,
+    pub customer_phone: String,
+    /// Pickup day (YYYY-MM-DD)
+    pub start_date: String,
+    /// Last day with the customer; the piece is due back the day after
+    pub end_date: String,
+    pub days: i64,
+    pub price_per_day: i64,
+    pub rental_total: i64,
+    pub deposit: i64,
+    /// Last day the piece is unavailable, cleaning included
+    pub blocked_until: String,
+    /// reserved, picked_up, returned or cancelled
+    pub status: String,
+    pub status_label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picked_up_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returned_at: Option<String>,
+    /// Day the piece actually came back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returned_on: Option<String>,
+    /// Days kept past `end_date`, charged at the daily price
+    pub late_fee: i64,
     #[serde(default)]
-    pub sort_order: i32,
+    pub damage_notes: String,
+    pub damage_fee: i64,
+    /// Deposit handed back after late and damage fees; set on return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit_refund: Option<i64>,
+    #[serde(default)]
+    pub notes: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct UpdateProduct {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub price: Option<i64>,
-    pub images: Option<Vec<String>>,
-    pub category: Option<String>,
+pub struct CreateRental {
+    pub product_id: String,
+    #[serde(default)]
+    pub variant: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub customer_name: String,
+    pub customer_phone: String,
+    pub order_id: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Dates and variant can only change while the rental is still reserved
+#[derive(Debug, Deserialize)]
+pub struct UpdateRental {
+    pub variant: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub notes: Option<String>,
+    /// Empty string unlinks the order
+    pub order_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReturnRental {
+    /// Day the piece came back (YYYY-MM-DD), today by default
+    pub returned_on: Option<String>,
+    #[serde(default)]
+    pub damage_notes: String,
+    #[serde(default)]
+    pub damage_fee: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RentalListParams {
     pub status: Option<String>,
-    pub sort_order: Option<i32>,
+    pub product_id: Option<String>,
+    /// Rentals overlapping `from`..`to` (YYYY-MM-DD)
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub phone: Option<String>,
 }
 
-/// Order model
+#[derive(Debug, Deserialize)]
+pub struct RentalAvailabilityParams {
+    /// First day (YYYY-MM-DD), today by default
+    pub from: Option<String>,
+    #[serde(default = "default_rental_availability_days")]
+    pub days: i64,
+}
+
+fn default_rental_availability_days() -> i64 {
+    30
+}
+
+/// Free pieces of a rentable product, day by day
+#[derive(Debug, Serialize)]
+pub struct RentalCalendar {
+    pub product_id: String,
+    pub rental_price_per_day: i64,
+    pub rental_deposit: i64,
+    pub rental_buffer_days: i64,
+    pub days: Vec<RentalDay>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RentalDay {
+    pub date: String,
+    /// Pieces not out or being cleaned that day, per variant
+    pub available: BTreeMap<String, i64>,
+}
+
+/// Customer, identified by normalized phone number
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Order {
+pub struct Customer {
     pub id: String,
-    pub customer_name: String,
-    pub customer_phone: String,
+    pub phone: String,
+    pub name: String,
     #[serde(default)]
-    pub customer_email: String,
+    pub email: String,
+    #[serde(default)]
+    pub notes: String,
+    /// Orders placed, excluding cancelled ones
+    pub order_count: i64,
+    /// Sum of those orders' totals, in VND
+    pub lifetime_value: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub product_id: Option<String>,
+    pub last_order_at: Option<String>,
+    /// Only included on single-customer responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orders: Option<Vec<Order>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub measurement_profiles: Option<Vec<MeasurementProfile>>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCustomer {
+    pub phone: String,
     #[serde(default)]
-    pub product_name: String,
+    pub name: String,
     #[serde(default)]
-    pub measurements: String,
+    pub email: String,
     #[serde(default)]
     pub notes: String,
-    #[serde(default = "default_order_status")]
-    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCustomer {
+    pub phone: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CustomerListParams {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    /// Matches name, phone or email
+    pub q: Option<String>,
+}
+
+/// Named set of measurements saved on a customer ("Chị Lan", "Mẹ")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasurementProfile {
+    pub id: String,
+    pub name: String,
+    pub measurements: Measurements,
     pub created_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<String>,
 }
 
-fn default_order_status() -> String {
-    "pending".to_string()
+#[derive(Debug, Deserialize)]
+pub struct SaveMeasurementProfile {
+    pub name: Option<String>,
+    pub measurements: Option<MeasurementsInput>,
+}
+
+/// Discount code. `discount_value` is a percentage for `percent` coupons and
+/// a VND amount for `fixed` ones; empty `product_ids`/`categories` mean no restriction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coupon {
+    pub id: String,
+    pub code: String,
+    #[serde(default)]
+    pub description: String,
+    pub discount_type: String,
+    pub discount_value: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_discount: Option<i64>,
+    #[serde(default)]
+    pub min_order_value: i64,
+    #[serde(default)]
+    pub product_ids: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_phone_limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    #[serde(default)]
+    pub times_used: i64,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct CreateOrder {
-    pub customer_name: String,
-    pub customer_phone: String,
+pub struct CreateCoupon {
+    pub code: String,
     #[serde(default)]
-    pub customer_email: String,
-    pub product_id: Option<String>,
+    pub description: String,
+    pub discount_type: String,
+    pub discount_value: i64,
+    pub max_discount: Option<i64>,
     #[serde(default)]
-    pub product_name: String,
+    pub min_order_value: i64,
     #[serde(default)]
-    pub measurements: String,
+    pub product_ids: Vec<String>,
     #[serde(default)]
-    pub notes: String,
+    pub categories: Vec<String>,
+    pub usage_limit: Option<i64>,
+    pub per_phone_limit: Option<i64>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    #[serde(default = "default_true")]
+    pub active: bool,
 }
 
+/// Limits/caps of 0 and empty dates clear the stored value
 #[derive(Debug, Deserialize)]
-pub struct UpdateOrder {
-    pub customer_name: Option<String>,
-    pub customer_phone: Option<String>,
-    pub customer_email: Option<String>,
+pub struct UpdateCoupon {
+    pub code: Option<String>,
+    pub description: Option<String>,
+    pub discount_type: Option<String>,
+    pub discount_value: Option<i64>,
+    pub max_discount: Option<i64>,
+    pub min_order_value: Option<i64>,
+    pub product_ids: Option<Vec<String>>,
+    pub categories: Option<Vec<String>>,
+    pub usage_limit: Option<i64>,
+    pub per_phone_limit: Option<i64>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateCoupon {
+    pub code: String,
+    #[serde(default)]
+    pub customer_phone: String,
     pub product_id: Option<String>,
-    pub product_name: Option<String>,
-    pub measurements: Option<String>,
-    pub notes: Option<String>,
-    pub status: Option<String>,
+    #[serde(default)]
+    pub selected_options: Vec<SelectedOptionInput>,
+    /// Whole cart; takes precedence over `product_id`
+    #[serde(default)]
+    pub items: Vec<OrderItemInput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CouponValidation {
+    pub code: String,
+    pub description: String,
+    pub subtotal: i64,
+    pub discount_amount: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CouponRedemption {
+    pub id: String,
+    pub coupon_id: String,
+    pub order_id: String,
+    pub customer_name: String,
+    pub customer_phone: String,
+    pub discount_amount: i64,
+    pub created_at: String,
+}
+
+/// One entry of an order's status history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub actor: String,
+    #[serde(default)]
+    pub note: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderTransition {
+    pub status: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderStatusInfo {
+    pub status: String,
+    pub label: String,
+    pub next: Vec<String>,
 }
 
 /// Setting model
@@ -249,6 +1559,35 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// Best-fitting size from the `size_chart` setting
+#[derive(Debug, Serialize)]
+pub struct SizeRecommendation {
+    pub size: String,
+    /// 0-1, how sure the match is
+    pub confidence: f64,
+    /// Measurements outside the recommended size's range
+    pub out_of_range: Vec<SizeDeviation>,
+    /// Every comparable size, best first
+    pub ranking: Vec<SizeScore>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizeDeviation {
+    pub field: String,
+    pub value: f64,
+    pub expected: String,
+    /// "above" or "below" the size's range
+    pub direction: String,
+    /// True when no size in the chart covers the value
+    pub outside_chart: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizeScore {
+    pub size: String,
+    pub score: f64,
+}
+
 /// Stats for dashboard
 #[derive(Debug, Serialize)]
 pub struct DashboardStats {
@@ -259,6 +1598,47 @@ pub struct DashboardStats {
     pub recent_orders: Vec<Order>,
 }
 
+/// Order list filters and pagination
+#[derive(Debug, Deserialize)]
+pub struct OrderListParams {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    pub status: Option<String>,
+    /// Free text: order number ("HLV-2026-00042", "00042"), customer name
+    /// with or without accents, email or phone
+    pub q: Option<String>,
+    /// Suspected spam is hidden unless asked for: `true` lists only those orders
+    pub spam: Option<bool>,
+    /// unpaid, deposit, paid or refunded
+    pub payment_status: Option<String>,
+    /// Placed on or after (RFC 3339 or YYYY-MM-DD)
+    pub from: Option<String>,
+    /// Placed on or before (RFC 3339 or YYYY-MM-DD, the whole day)
+    pub to: Option<String>,
+    /// Part of the customer's name (accents optional) or phone number
+    pub customer: Option<String>,
+    /// Orders with at least one line of this product
+    pub product_id: Option<String>,
+    /// Username of the staff member in charge, or "none" for unassigned orders
+    pub assigned_to: Option<String>,
+    /// Only orders past their delivery date and not yet delivered
+    pub overdue: Option<bool>,
+    /// Orders with a production stage assigned to this tailor
+    pub tailor_id: Option<String>,
+    /// created_at (default), delivery_date, total, balance_due, customer_name or order_number
+    pub sort: Option<String>,
+    /// asc or desc (default)
+    pub direction: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderExportParams {
+    /// csv (default) or xlsx
+    pub format: Option<String>,
+}
+
 /// Pagination params
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
@@ -0,0 +1,185 @@
+use rusqlite::{params, Connection};
+use uuid::Uuid;
+
+use crate::db::parse_json_array;
+use crate::models::{OptionGroupInput, ProductOptionChoice, ProductOptionGroup, SelectedOption, SelectedOptionInput};
+
+/// Option groups of a product with their choices, in display order
+pub fn load_groups(conn: &Connection, product_id: &str) -> Vec<ProductOptionGroup> {
+    let mut stmt = match conn.prepare(
+        "SELECT id, name, description, required, max_select FROM product_option_groups
+         WHERE product_id = ?1 ORDER BY sort_order ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+    let mut groups: Vec<ProductOptionGroup> = stmt
+        .query_map(params![product_id], |row| {
+            Ok(ProductOptionGroup {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                required: row.get(3)?,
+                max_select: row.get(4)?,
+                choices: vec![],
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default();
+
+    let mut stmt = match conn.prepare(
+        "SELECT id, name, price_delta FROM product_option_choices WHERE group_id = ?1 ORDER BY sort_order ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return groups,
+    };
+    for group in &mut groups {
+        group.choices = stmt
+            .query_map(params![group.id], |row| {
+                Ok(ProductOptionChoice {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    price_delta: row.get(2)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default();
+    }
+    groups
+}
+
+/// Replace a product's option groups (caller handles the transaction).
+///
+/// Choices listed in a `choices_from_setting` JSON array are appended after
+/// the explicit ones with no price delta, skipping names already present.
+/// Ids sent back keep a group or choice the product already has; new ones
+/// get an id here.
+pub fn save_groups(conn: &Connection, product_id: &str, groups: &[OptionGroupInput]) -> Result<(), String> {
+    let existing = load_groups(conn, product_id);
+    let known_group = |id: &str| existing.iter().any(|g| g.id == id);
+    let known_choice = |id: &str| existing.iter().flat_map(|g| &g.choices).any(|c| c.id == id);
+    conn.execute("DELETE FROM product_option_groups WHERE product_id = ?1", params![product_id])
+        .map_err(|e| e.to_string())?;
+
+    for (position, group) in groups.iter().enumerate() {
+        let name = group.name.trim();
+        if name.is_empty() {
+            return Err("Option group name is required".to_string());
+        }
+        if group.max_select < 1 {
+            return Err(format!("max_select must be at least 1 for \"{}\"", name));
+        }
+        if let Some(id) = group.id.as_deref().filter(|id| !known_group(id)) {
+            return Err(format!("Unknown option group: {}", id));
+        }
+        if let Some(choice) = group.choices.iter().find(|c| c.price_delta < 0) {
+            return Err(format!("price_delta cannot be negative for \"{}\"", choice.name.trim()));
+        }
+        if let Some(id) = group.choices.iter().filter_map(|c| c.id.as_deref()).find(|id| !known_choice(id)) {
+            return Err(format!("Unknown option choice: {}", id));
+        }
+
+        let mut choices: Vec<(Option<String>, String, i64)> = group
+            .choices
+            .iter()
+            .map(|c| (c.id.clone(), c.name.trim().to_string(), c.price_delta))
+            .collect();
+        if let Some(ref key) = group.choices_from_setting {
+            let value: String = conn
+                .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+                .map_err(|_| format!("Setting not found: {}", key))?;
+            for entry in parse_json_array(&value) {
+                if !choices.iter().any(|(_, n, _)| *n == entry) {
+                    choices.push((None, entry, 0));
+                }
+            }
+        }
+        if choices.is_empty() || choices.iter().any(|(_, n, _)| n.is_empty()) {
+            return Err(format!("Option group \"{}\" needs named choices", name));
+        }
+
+        let group_id = group.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+        conn.execute(
+            "INSERT INTO product_option_groups (id, product_id, name, description, required, max_select, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![group_id, product_id, name, group.description, group.required, group.max_select, position as i64],
+        )
+        .map_err(|e| e.to_string())?;
+
+        for (choice_position, (id, choice_name, price_delta)) in choices.into_iter().enumerate() {
+            conn.execute(
+                "INSERT INTO product_option_choices (id, group_id, name, price_delta, sort_order)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+                    group_id,
+                    choice_name,
+                    price_delta,
+                    choice_position as i64
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Validate a customer's selection against the product's option groups and
+/// snapshot the chosen choices with their price deltas
+pub fn resolve_selection(
+    groups: &[ProductOptionGroup],
+    selected: &[SelectedOptionInput],
+) -> Result<Vec<SelectedOption>, String> {
+    for input in selected {
+        if !groups.iter().any(|g| g.id == input.group_id) {
+            return Err(format!("Unknown option group: {}", input.group_id));
+        }
+    }
+
+    let mut resolved = vec![];
+    for group in groups {
+        let mut choice_ids: Vec<&String> = selected
+            .iter()
+            .filter(|s| s.group_id == group.id)
+            .flat_map(|s| s.choice_ids.iter())
+            .collect();
+        choice_ids.sort();
+        choice_ids.dedup();
+
+        if group.required && choice_ids.is_empty() {
+            return Err(format!("Please choose an option for \"{}\"", group.name));
+        }
+        if choice_ids.len() as i64 > group.max_select {
+            return Err(format!("At most {} choice(s) allowed for \"{}\"", group.max_select, group.name));
+        }
+
+        // Keep the group's own choice order rather than the submission order
+        for choice in &group.choices {
+            if choice_ids.contains(&&choice.id) {
+                resolved.push(SelectedOption {
+                    group_id: group.id.clone(),
+                    group_name: group.name.clone(),
+                    choice_id: choice.id.clone(),
+                    choice_name: choice.name.clone(),
+                    price_delta: choice.price_delta,
+                });
+            }
+        }
+        if let Some(unknown) = choice_ids.iter().find(|id| !group.choices.iter().any(|c| c.id == ***id)) {
+            return Err(format!("Unknown choice {} for \"{}\"", unknown, group.name));
+        }
+    }
+    Ok(resolved)
+}
+
+pub fn options_total(selected: &[SelectedOption]) -> i64 {
+    selected.iter().map(|o| o.price_delta).sum()
+}
+
+pub fn to_json(selected: &[SelectedOption]) -> String {
+    serde_json::to_string(selected).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn from_json(json: &str) -> Vec<SelectedOption> {
+    serde_json::from_str(json).unwrap_or_default()
+}
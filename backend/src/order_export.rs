@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+use crate::handlers::orders::{load_lines, order_from_row, ORDER_COLUMNS};
+use crate::models::Order;
+use crate::order_filters::OrderFilter;
+use crate::order_status;
+use crate::payments;
+use crate::pricing;
+
+/// Orders read per database round trip; the lock is released in between
+pub const BATCH_SIZE: usize = 500;
+
+pub const FORMATS: [&str; 2] = ["csv", "xlsx"];
+
+/// An order with the last time it entered each status
+pub struct ExportRow {
+    order: Order,
+    status_dates: HashMap<String, String>,
+}
+
+impl ExportRow {
+    /// Keyset position to continue the export after this row
+    pub fn cursor(&self) -> (String, String) {
+        (self.order.created_at.clone(), self.order.id.clone())
+    }
+}
+
+enum Cell {
+    Text(String),
+    Number(i64),
+    Money(i64),
+    /// Stored timestamp, written in shop-local time
+    DateTime(Option<String>),
+    /// YYYY-MM-DD
+    Date(Option<String>),
+}
+
+const COLUMNS: [&str; 19] = [
+    "Số đơn",
+    "Ngày đặt",
+    "Trạng thái",
+    "Khách hàng",
+    "Điện thoại",
+    "Email",
+    "Sản phẩm",
+    "Số lượng",
+    "Số đo",
+    "Tạm tính",
+    "Giảm giá",
+    "Mã giảm giá",
+    "Tổng cộng",
+    "Đã thanh toán",
+    "Còn lại",
+    "Thanh toán",
+    "Ngày thử",
+    "Ngày giao",
+    "Ghi chú",
+];
+
+/// Fixed columns, then when the order last entered each status after "pending"
+fn headers() -> Vec<String> {
+    COLUMNS
+        .iter()
+        .map(|c| c.to_string())
+        .chain(order_status::STATUSES.iter().skip(1).map(|(_, label)| format!("Ngày {}", label.to_lowercase())))
+        .collect()
+}
+
+fn items_summary(order: &Order) -> String {
+    if order.items.is_empty() {
+        return order.product_name.clone();
+    }
+    order
+        .items
+        .iter()
+        .map(|item| {
+            let mut line = item.product_name.clone();
+            if !item.variant.is_empty() {
+                line = format!("{} ({})", line, item.variant);
+            }
+            line = format!("{} × {}", line, item.quantity);
+            if !item.selected_options.is_empty() {
+                let options: Vec<String> =
+                    item.selected_options.iter().map(|o| format!("{}: {}", o.group_name, o.choice_name)).collect();
+                line = format!("{} [{}]", line, options.join(", "));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// The order's measurements, or each garment's when they were taken per item
+fn measurements_summary(order: &Order) -> String {
+    let own = order.measurements.summary();
+    if !own.is_empty() {
+        return own;
+    }
+    let per_item: Vec<String> = order
+        .items
+        .iter()
+        .filter_map(|item| {
+            let summary = item.measurements.summary();
+            (!summary.is_empty()).then(|| format!("{}: {}", item.product_name, summary))
+        })
+        .collect();
+    if per_item.is_empty() {
+        order.measurements.raw.clone().unwrap_or_default()
+    } else {
+        per_item.join(" | ")
+    }
+}
+
+fn cells(row: &ExportRow) -> Vec<Cell> {
+    let order = &row.order;
+    let mut cells = vec![
+        Cell::Text(order.order_number.clone().unwrap_or_default()),
+        Cell::DateTime(Some(order.created_at.clone())),
+        Cell::Text(order_status::label(&order.status)),
+        Cell::Text(order.customer_name.clone()),
+        Cell::Text(order.customer_phone.clone()),
+        Cell::Text(order.customer_email.clone()),
+        Cell::Text(items_summary(order)),
+        Cell::Number(if order.items.is_empty() { 1 } else { order.items.iter().map(|i| i.quantity).sum() }),
+        Cell::Text(measurements_summary(order)),
+        Cell::Money(order.subtotal),
+        Cell::Money(order.discount_amount),
+        Cell::Text(order.coupon_code.clone().unwrap_or_default()),
+        Cell::Money(order.total),
+        Cell::Money(order.paid_amount),
+        Cell::Money(order.balance_due),
+        Cell::Text(payments::status_label(&order.payment_status).to_string()),
+        Cell::Date(order.fitting_date.clone()),
+        Cell::Date(order.delivery_date.clone()),
+        Cell::Text(order.notes.clone()),
+    ];
+    for (status, _) in order_status::STATUSES.iter().skip(1) {
+        cells.push(Cell::DateTime(row.status_dates.get(*status).cloned()));
+    }
+    cells
+}
+
+/// "YYYY-MM-DD HH:MM" in shop-local time, which spreadsheets read as a date
+fn local_datetime(value: &str) -> Option<String> {
+    pricing::parse_datetime(value, false)
+        .map(|dt| dt.with_timezone(&pricing::shop_offset()).format("%Y-%m-%d %H:%M").to_string())
+}
+
+/// Next `BATCH_SIZE` orders matching `filter`, newest first, after `cursor`
+pub fn fetch_batch(
+    conn: &Connection,
+    filter: &OrderFilter,
+    cursor: Option<&(String, String)>,
+) -> rusqlite::Result<Vec<ExportRow>> {
+    let mut params = filter.params.clone();
+    let mut clause = filter.clause.clone();
+    if let Some((created_at, id)) = cursor {
+        let (at, after) = (filter.next_placeholder(), format!("?{}", params.len() + 2));
+        clause.push_str(&format!(" AND (created_at < {at} OR (created_at = {at} AND id < {after}))"));
+        params.push(Value::Text(created_at.clone()));
+        params.push(Value::Text(id.clone()));
+    }
+    let sql = format!(
+        "SELECT {} FROM orders {} ORDER BY created_at DESC, id DESC LIMIT {}",
+        ORDER_COLUMNS, clause, BATCH_SIZE
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut orders: Vec<Order> = stmt.query_map(params_from_iter(&params), order_from_row)?.filter_map(|r| r.ok()).collect();
+    for order in &mut orders {
+        load_lines(conn, order);
+    }
+
+    let ids = serde_json::to_string(&orders.iter().map(|o| o.id.as_str()).collect::<Vec<_>>()).unwrap_or_default();
+    let mut stmt = conn.prepare(
+        "SELECT order_id, to_status, MAX(created_at) FROM order_status_history
+         WHERE order_id IN (SELECT value FROM json_each(?1))
+         GROUP BY order_id, to_status",
+    )?;
+    let mut status_dates: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for row in stmt.query_map([ids], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))? {
+        let (order_id, status, at) = row?;
+        status_dates.entry(order_id).or_default().insert(status, at);
+    }
+
+    Ok(orders
+        .into_iter()
+        .map(|order| ExportRow {
+            status_dates: status_dates.remove(&order.id).unwrap_or_default(),
+            order,
+        })
+        .collect())
+}
+
+/// Text typed into the storefront order form ends up in these cells; one
+/// starting like a formula is prefixed with `'` so spreadsheets show it as text
+fn neutralize_formula(text: String) -> String {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text
+    }
+}
+
+/// CSV text of `rows`, preceded by a byte order mark and the header row on
+/// the first chunk so Excel reads the Vietnamese text as UTF-8
+pub fn csv_chunk(rows: &[ExportRow], first: bool) -> Result<Vec<u8>, String> {
+    let mut out = if first { "\u{feff}".as_bytes().to_vec() } else { vec![] };
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(&mut out);
+    if first {
+        writer.write_record(headers()).map_err(|e| e.to_string())?;
+    }
+    for row in rows {
+        let record: Vec<String> = cells(row)
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Text(text) => neutralize_formula(text),
+                Cell::Number(n) | Cell::Money(n) => n.to_string(),
+                Cell::DateTime(at) => at.as_deref().and_then(local_datetime).unwrap_or_default(),
+                Cell::Date(date) => date.unwrap_or_default(),
+            })
+            .collect();
+        writer.write_record(record).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    drop(writer);
+    Ok(out)
+}
+
+/// Workbook written row by row: rows are flushed to a temporary file as they
+/// are added rather than kept in memory
+pub struct XlsxExport {
+    workbook: Workbook,
+    next_row: u32,
+    header: Format,
+    money: Format,
+    datetime: Format,
+    date: Format,
+}
+
+impl XlsxExport {
+    pub fn new() -> Result<XlsxExport, String> {
+        let mut export = XlsxExport {
+            workbook: Workbook::new(),
+            next_row: 1,
+            header: Format::new().set_bold().set_background_color("#F5EBCC"),
+            money: Format::new().set_num_format("#,##0"),
+            datetime: Format::new().set_num_format("dd/mm/yyyy hh:mm"),
+            date: Format::new().set_num_format("dd/mm/yyyy"),
+        };
+        let sheet = export.workbook.add_worksheet_with_constant_memory();
+        sheet.set_name("Đơn hàng").map_err(|e| e.to_string())?;
+        for (col, title) in headers().iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, title, &export.header).map_err(|e| e.to_string())?;
+            sheet.set_column_width(col as u16, if col == 6 || col == 8 { 48 } else { 16 }).map_err(|e| e.to_string())?;
+        }
+        sheet.set_freeze_panes(1, 1).map_err(|e| e.to_string())?;
+        Ok(export)
+    }
+
+    pub fn append(&mut self, rows: &[ExportRow]) -> Result<(), String> {
+        let sheet = self.workbook.worksheet_from_index(0).map_err(|e| e.to_string())?;
+        for row in rows {
+            for (col, cell) in cells(row).into_iter().enumerate() {
+                let (r, c) = (self.next_row, col as u16);
+                let written = match cell {
+                    Cell::Text(text) if text.is_empty() => continue,
+                    Cell::Text(text) => sheet.write_string(r, c, text).map(|_| ()),
+                    Cell::Number(n) => sheet.write_number(r, c, n as f64).map(|_| ()),
+                    Cell::Money(n) => sheet.write_number_with_format(r, c, n as f64, &self.money).map(|_| ()),
+                    Cell::DateTime(at) => match at.as_deref().and_then(local_datetime) {
+                        Some(at) => ExcelDateTime::parse_from_str(&at)
+                            .and_then(|dt| sheet.write_datetime_with_format(r, c, &dt, &self.datetime).map(|_| ())),
+                        None => continue,
+                    },
+                    Cell::Date(date) => match date.filter(|d| !d.is_empty()) {
+                        Some(date) => ExcelDateTime::parse_from_str(&date)
+                            .and_then(|dt| sheet.write_datetime_with_format(r, c, &dt, &self.date).map(|_| ())),
+                        None => continue,
+                    },
+                };
+                written.map_err(|e| e.to_string())?;
+            }
+            self.next_row += 1;
+        }
+        Ok(())
+    }
+
+    /// Save to an anonymous temporary file, rewound for reading
+    pub fn finish(mut self) -> Result<File, String> {
+        let mut file = tempfile::tempfile().map_err(|e| e.to_string())?;
+        self.workbook.save_to_writer(&mut file).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+        Ok(file)
+    }
+}
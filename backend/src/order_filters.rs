@@ -0,0 +1,183 @@
+use chrono::Utc;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+
+use crate::models::OrderListParams;
+use crate::order_status;
+use crate::payments;
+use crate::pricing;
+use crate::text;
+
+/// Columns the order list can be sorted by, with the expression each sorts on.
+/// Customer names sort on their folded form so "Ánh" comes next to "Anh".
+pub const SORTS: [(&str, &str); 6] = [
+    ("created_at", "created_at"),
+    ("delivery_date", "delivery_date"),
+    ("total", "total"),
+    ("balance_due", "(total - paid_amount)"),
+    ("customer_name", "customer_name_folded"),
+    ("order_number", "order_number"),
+];
+
+/// WHERE clause over `orders` with its bound parameters, shared by the
+/// order list and the export so both always select the same orders.
+/// Parameters are numbered, so callers can append their own after `params`.
+pub struct OrderFilter {
+    pub clause: String,
+    pub params: Vec<Value>,
+}
+
+impl OrderFilter {
+    fn push(&mut self, condition: &str, values: Vec<Value>) {
+        // Each "?#" in `condition` stands for the next of `values`
+        let mut condition = condition.to_string();
+        for value in values {
+            self.params.push(value);
+            condition = condition.replacen("?#", &format!("?{}", self.params.len()), 1);
+        }
+        self.clause.push_str(" AND ");
+        self.clause.push_str(&condition);
+    }
+
+    /// Placeholder for the next parameter a caller appends
+    pub fn next_placeholder(&self) -> String {
+        format!("?{}", self.params.len() + 1)
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// FTS5 query matching every word of `input` at the start of an indexed word
+/// ("nguy anh" finds "Nguyễn Thị Ánh"). A phone number typed with spaces or
+/// dots is one word, so "0912 345 678" finds 0912345678. Words are folded
+/// and reduced to letters and digits, which leaves nothing to escape.
+fn match_terms(input: &str) -> Option<String> {
+    let folded = text::fold(input);
+    let words: Vec<String> = if folded.chars().all(|c| c.is_ascii_digit() || " +.-()".contains(c)) {
+        vec![folded.chars().filter(|c| c.is_ascii_digit()).collect()]
+    } else {
+        folded.split(|c: char| !c.is_alphanumeric()).map(String::from).collect()
+    };
+    let terms: Vec<String> = words.into_iter().filter(|w| !w.is_empty()).map(|w| format!("\"{}\"*", w)).collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+pub fn build(params: &OrderListParams) -> Result<OrderFilter, String> {
+    let mut filter = OrderFilter {
+        clause: "WHERE 1 = 1".to_string(),
+        params: vec![],
+    };
+
+    // Conditions compare bare columns so SQLite can use their indexes;
+    // text search goes through the order_search full-text index
+    let spam = params.spam.unwrap_or(false);
+    filter.push("suspected_spam = ?#", vec![Value::Integer(spam as i64)]);
+
+    if let Some(status) = non_empty(&params.status) {
+        if !order_status::is_valid(status) {
+            return Err(format!("Unknown status: {}", status));
+        }
+        filter.push("status = ?#", vec![Value::Text(status.to_string())]);
+    }
+
+    // Free text: an order number with or without dashes ("hlv202600042",
+    // "00042"), the customer's name with or without accents, email or phone
+    if let Some(terms) = non_empty(&params.q).and_then(match_terms) {
+        filter.push("rowid IN (SELECT rowid FROM order_search WHERE order_search MATCH ?#)", vec![Value::Text(terms)]);
+    }
+
+    if let Some(payment_status) = non_empty(&params.payment_status) {
+        if !payments::PAYMENT_STATUSES.contains(&payment_status) {
+            return Err("payment_status must be unpaid, deposit, paid or refunded".to_string());
+        }
+        filter.push("payment_status = ?#", vec![Value::Text(payment_status.to_string())]);
+    }
+
+    // Placed between `from` and `to`, whole shop-local days for plain dates
+    if let Some(from) = non_empty(&params.from) {
+        let from = pricing::parse_datetime(from, false).ok_or("from must be RFC 3339 or YYYY-MM-DD")?;
+        filter.push("created_at >= ?#", vec![Value::Text(from.to_rfc3339())]);
+    }
+    if let Some(to) = non_empty(&params.to) {
+        let to = pricing::parse_datetime(to, true).ok_or("to must be RFC 3339 or YYYY-MM-DD")?;
+        filter.push("created_at <= ?#", vec![Value::Text(to.to_rfc3339())]);
+    }
+
+    // "nguyen" finds "Nguyễn"
+    if let Some(terms) = non_empty(&params.customer).and_then(match_terms) {
+        filter.push(
+            "rowid IN (SELECT rowid FROM order_search WHERE order_search MATCH ?#)",
+            vec![Value::Text(format!("{{name phone}} : ({})", terms))],
+        );
+    }
+
+    // Legacy single-product orders keep the product on the order itself
+    if let Some(product_id) = non_empty(&params.product_id) {
+        filter.push(
+            "(product_id = ?# OR EXISTS (SELECT 1 FROM order_items i WHERE i.order_id = orders.id AND i.product_id = ?#))",
+            vec![Value::Text(product_id.to_string()), Value::Text(product_id.to_string())],
+        );
+    }
+
+    // A username, or "none" for orders nobody has taken on yet
+    match non_empty(&params.assigned_to) {
+        Some("none") => filter.push("assigned_to IS NULL", vec![]),
+        Some(username) => filter.push("assigned_to = ?#", vec![Value::Text(username.to_string())]),
+        None => {}
+    }
+
+    if let Some(tailor_id) = non_empty(&params.tailor_id) {
+        filter.push(
+            "EXISTS (SELECT 1 FROM work_assignments w WHERE w.order_id = orders.id AND w.tailor_id = ?#)",
+            vec![Value::Text(tailor_id.to_string())],
+        );
+    }
+
+    // Promised for a day already past and not yet handed over
+    if params.overdue.unwrap_or(false) {
+        let today = Utc::now().with_timezone(&pricing::shop_offset()).format("%Y-%m-%d").to_string();
+        filter.push(
+            &format!("delivery_date < ?# AND status NOT IN ('{}')", order_status::CLOSED_STATUSES.join("', '")),
+            vec![Value::Text(today)],
+        );
+    }
+
+    Ok(filter)
+}
+
+/// ORDER BY for the list's `sort` and `direction`, newest first by default.
+/// Ties are broken by id so pages never overlap.
+pub fn order_by(params: &OrderListParams) -> Result<String, String> {
+    let (name, column) = match non_empty(&params.sort) {
+        Some(sort) => SORTS.iter().find(|(name, _)| *name == sort).copied().ok_or_else(|| {
+            format!("sort must be one of {}", SORTS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", "))
+        })?,
+        None => SORTS[0],
+    };
+    let direction = match non_empty(&params.direction) {
+        None | Some("desc") => "DESC",
+        Some("asc") => "ASC",
+        Some(_) => return Err("direction must be asc or desc".to_string()),
+    };
+    // Orders without a delivery date (or number) come last whichever way
+    let nulls = if name == "delivery_date" || name == "order_number" { " NULLS LAST" } else { "" };
+    Ok(format!("ORDER BY {} {}{}, id {}", column, direction, nulls, direction))
+}
+
+/// Diacritic-free, lower-case customer name kept beside the original so
+/// names sort regardless of accents
+pub fn fold_name(name: &str) -> String {
+    text::fold(name.trim())
+}
+
+/// Fold the names of orders stored before `customer_name_folded` existed
+pub fn fold_missing_names(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT id, customer_name FROM orders WHERE customer_name_folded IS NULL")?;
+    let orders: Vec<(String, String)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.filter_map(|r| r.ok()).collect();
+    for (id, name) in orders {
+        conn.execute("UPDATE orders SET customer_name_folded = ?1 WHERE id = ?2", params![fold_name(&name), id])?;
+    }
+    Ok(())
+}
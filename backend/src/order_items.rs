@@ -0,0 +1,154 @@
+use rusqlite::{params, Connection};
+use uuid::Uuid;
+
+use crate::coupons::CouponLine;
+use crate::measurements::Measurements;
+use crate::models::{OrderItem, OrderItemInput};
+use crate::options;
+use crate::products::load_product;
+
+pub const MAX_QUANTITY: i64 = 99;
+
+/// An order line priced against the current catalog, before it is saved
+#[derive(Debug, Clone)]
+pub struct PricedLine {
+    pub item: OrderItem,
+    pub category: String,
+}
+
+/// Price each requested line from the product's current effective price and
+/// the chosen options. Errors are messages suitable for the storefront.
+pub fn price_items(conn: &Connection, inputs: &[OrderItemInput]) -> Result<Vec<PricedLine>, String> {
+    if inputs.is_empty() {
+        return Err("An order needs at least one item".to_string());
+    }
+
+    let mut lines = vec![];
+    for input in inputs {
+        if !(1..=MAX_QUANTITY).contains(&input.quantity) {
+            return Err(format!("Quantity must be between 1 and {}", MAX_QUANTITY));
+        }
+        let product = load_product(conn, &input.product_id)
+            .map_err(|_| format!("Product not found: {}", input.product_id))?;
+        // Only what the storefront sells can be ordered: rent-only pieces are
+        // booked as rentals, and bundles are out once a component is
+        if product.status != "active" {
+            return Err(format!("{} is no longer available", product.name));
+        }
+        if !product.in_stock {
+            return Err(format!("{} is out of stock", product.name));
+        }
+        if product.rental_price_per_day.is_some() && product.price <= 0 {
+            return Err(format!("{} is only available for rent", product.name));
+        }
+        let groups = product.option_groups.clone().unwrap_or_default();
+        let selected_options = options::resolve_selection(&groups, &input.selected_options)?;
+        let measurements = match input.measurements.clone() {
+            Some(m) => m.into_measurements()?,
+            None => Measurements::default(),
+        };
+
+        let options_price = options::options_total(&selected_options);
+        lines.push(PricedLine {
+            item: OrderItem {
+                id: Uuid::new_v4().to_string(),
+                product_id: Some(product.id),
+                product_name: product.name,
+                variant: input.variant.trim().to_string(),
+                quantity: input.quantity,
+                unit_price: product.effective_price,
+                options_price,
+                line_total: (product.effective_price + options_price) * input.quantity,
+                discount_amount: 0,
+                selected_options,
+                measurements,
+                wearer_id: None,
+            },
+            category: product.category,
+        });
+    }
+    Ok(lines)
+}
+
+pub fn coupon_lines(lines: &[PricedLine]) -> Vec<CouponLine> {
+    lines
+        .iter()
+        .map(|line| CouponLine {
+            product_id: line.item.product_id.clone(),
+            category: Some(line.category.clone()),
+            amount: line.item.line_total,
+        })
+        .collect()
+}
+
+/// Product names for the order's summary `product_name`, e.g. "Áo dài lụa x2, Khăn vấn"
+pub fn summary_name(lines: &[PricedLine]) -> String {
+    lines
+        .iter()
+        .map(|line| match line.item.quantity {
+            1 => line.item.product_name.clone(),
+            quantity => format!("{} x{}", line.item.product_name, quantity),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Insert the lines of a new order (caller handles the transaction)
+pub fn save_items(conn: &Connection, order_id: &str, lines: &[PricedLine]) -> rusqlite::Result<()> {
+    for (position, line) in lines.iter().enumerate() {
+        let item = &line.item;
+        conn.execute(
+            "INSERT INTO order_items (id, order_id, product_id, product_name, variant, quantity, unit_price, options_price,
+                line_total, discount_amount, selected_options, measurements, wearer_id, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                item.id,
+                order_id,
+                item.product_id,
+                item.product_name,
+                item.variant,
+                item.quantity,
+                item.unit_price,
+                item.options_price,
+                item.line_total,
+                item.discount_amount,
+                options::to_json(&item.selected_options),
+                item.measurements.to_json(),
+                item.wearer_id,
+                position as i64
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Lines of an order, in the order they were placed
+pub fn load_items(conn: &Connection, order_id: &str) -> Vec<OrderItem> {
+    let mut stmt = match conn.prepare(
+        "SELECT id, product_id, product_name, variant, quantity, unit_price, options_price, line_total, discount_amount,
+            selected_options, measurements, wearer_id
+         FROM order_items WHERE order_id = ?1 ORDER BY sort_order ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+
+    stmt.query_map(params![order_id], |row| {
+        Ok(OrderItem {
+            id: row.get(0)?,
+            product_id: row.get(1)?,
+            product_name: row.get(2)?,
+            variant: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            quantity: row.get(4)?,
+            unit_price: row.get(5)?,
+            options_price: row.get(6)?,
+            line_total: row.get(7)?,
+            discount_amount: row.get(8)?,
+            selected_options: options::from_json(&row.get::<_, Option<String>>(9)?.unwrap_or_default()),
+            measurements: Measurements::from_stored(&row.get::<_, Option<String>>(10)?.unwrap_or_default()),
+            wearer_id: row.get(11)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
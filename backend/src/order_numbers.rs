@@ -0,0 +1,79 @@
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::pricing::{parse_datetime, shop_offset};
+
+pub const DEFAULT_PREFIX: &str = "HLV";
+
+/// Prefixes are kept to letters and digits so an order number stays a
+/// usable bank-transfer memo once the dashes are dropped
+pub fn validate_prefix(prefix: &str) -> Result<(), String> {
+    if (2..=8).contains(&prefix.len()) && prefix.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err("order_number_prefix must be 2-8 uppercase letters or digits".to_string())
+    }
+}
+
+fn prefix(conn: &Connection) -> rusqlite::Result<String> {
+    let prefix: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = 'order_number_prefix'", [], |row| row.get(0))
+        .optional()?;
+    Ok(prefix.filter(|p| validate_prefix(p).is_ok()).unwrap_or_else(|| DEFAULT_PREFIX.to_string()))
+}
+
+/// Allocate the next number of the shop-local year, e.g. "HLV-2026-00042".
+///
+/// The counter is bumped and read in one statement, so two orders can never
+/// share a number; call it inside the order's transaction so a failed order
+/// doesn't burn one.
+pub fn next(conn: &Connection, placed_at: DateTime<Utc>) -> rusqlite::Result<String> {
+    let year = placed_at.with_timezone(&shop_offset()).year();
+    let sequence: i64 = conn.query_row(
+        "INSERT INTO order_sequences (year, last_value) VALUES (?1, 1)
+         ON CONFLICT(year) DO UPDATE SET last_value = last_value + 1
+         RETURNING last_value",
+        params![year],
+        |row| row.get(0),
+    )?;
+    Ok(format!("{}-{}-{:05}", prefix(conn)?, year, sequence))
+}
+
+/// Reference customers put in a bank transfer: the order number without
+/// dashes, since many banking apps strip punctuation from the memo
+pub fn transfer_memo(order_number: &str) -> String {
+    compact(order_number)
+}
+
+/// Uppercase letters and digits only, for comparing numbers however they were typed
+pub fn compact(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Number orders placed before order numbers existed, oldest first, in the
+/// year each was placed
+pub fn assign_missing(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT id, created_at FROM orders WHERE order_number IS NULL ORDER BY created_at ASC")?;
+    let orders: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (id, created_at) in orders {
+        let placed_at = parse_datetime(&created_at, false)
+            .or_else(|| {
+                // Seeded rows use SQLite's "YYYY-MM-DD HH:MM:SS" (UTC)
+                NaiveDateTime::parse_from_str(&created_at, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|dt| dt.and_utc())
+            })
+            .unwrap_or_else(Utc::now);
+        let number = next(conn, placed_at)?;
+        conn.execute("UPDATE orders SET order_number = ?1 WHERE id = ?2", params![number, id])?;
+    }
+    Ok(())
+}
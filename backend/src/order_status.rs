@@ -0,0 +1,150 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::models::{OrderStatusInfo, StatusChange};
+use crate::text;
+
+/// Tailoring-shop order lifecycle, in production order
+pub const STATUSES: [(&str, &str); 9] = [
+    ("pending", "Chờ xác nhận"),
+    ("confirmed", "Đã xác nhận"),
+    ("measuring", "Đang lấy số đo"),
+    ("cutting", "Đang cắt vải"),
+    ("sewing", "Đang may"),
+    ("fitting", "Thử đồ"),
+    ("completed", "Hoàn thành"),
+    ("delivered", "Đã giao"),
+    ("cancelled", "Đã hủy"),
+];
+
+/// Orders handed over or called off: they can no longer run late or be worked on
+pub const CLOSED_STATUSES: [&str; 2] = ["delivered", "cancelled"];
+
+/// Statuses an order may move to from `from`.
+///
+/// Ready-made orders may skip measuring, a fitting can send the garment back
+/// for alterations, and anything not yet handed over can be cancelled.
+/// Statuses written before the lifecycle existed can move anywhere so staff
+/// can bring those orders back on track.
+pub fn allowed_transitions(from: &str) -> Vec<&'static str> {
+    match from {
+        "pending" => vec!["confirmed", "cancelled"],
+        "confirmed" => vec!["measuring", "cutting", "cancelled"],
+        "measuring" => vec!["cutting", "cancelled"],
+        "cutting" => vec!["sewing", "cancelled"],
+        "sewing" => vec!["fitting", "completed", "cancelled"],
+        "fitting" => vec!["sewing", "completed", "delivered", "cancelled"],
+        "completed" => vec!["delivered"],
+        "delivered" | "cancelled" => vec![],
+        _ => STATUSES.iter().map(|(s, _)| *s).collect(),
+    }
+}
+
+/// Vietnamese label of a status, or the status itself for legacy values
+pub fn label(status: &str) -> String {
+    text::label(&STATUSES, status)
+}
+
+pub fn is_valid(status: &str) -> bool {
+    STATUSES.iter().any(|(s, _)| *s == status)
+}
+
+/// Every status with its label and the statuses reachable from it
+pub fn lifecycle() -> Vec<OrderStatusInfo> {
+    STATUSES
+        .iter()
+        .map(|(status, label)| OrderStatusInfo {
+            status: status.to_string(),
+            label: label.to_string(),
+            next: allowed_transitions(status).into_iter().map(String::from).collect(),
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum TransitionError {
+    NotFound,
+    Invalid(String),
+    Database(rusqlite::Error),
+}
+
+impl From<rusqlite::Error> for TransitionError {
+    fn from(e: rusqlite::Error) -> Self {
+        TransitionError::Database(e)
+    }
+}
+
+/// Move an order to `to`, recording who did it (caller handles the transaction)
+pub fn transition(
+    conn: &Connection,
+    order_id: &str,
+    to: &str,
+    actor: &str,
+    note: &str,
+) -> Result<(), TransitionError> {
+    if !is_valid(to) {
+        return Err(TransitionError::Invalid(format!("Unknown order status: {}", to)));
+    }
+
+    let from: String = conn
+        .query_row("SELECT status FROM orders WHERE id = ?1", params![order_id], |row| row.get(0))
+        .optional()?
+        .ok_or(TransitionError::NotFound)?;
+
+    if from == to {
+        return Err(TransitionError::Invalid(format!("Order is already {}", to)));
+    }
+    if !allowed_transitions(&from).contains(&to) {
+        return Err(TransitionError::Invalid(format!("Cannot change order status from {} to {}", from, to)));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE orders SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        params![to, now, order_id],
+    )?;
+    record(conn, order_id, Some(&from), to, actor, note)?;
+    Ok(())
+}
+
+/// Append a history entry without touching the order itself
+pub fn record(
+    conn: &Connection,
+    order_id: &str,
+    from: Option<&str>,
+    to: &str,
+    actor: &str,
+    note: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO order_status_history (id, order_id, from_status, to_status, actor, note, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![Uuid::new_v4().to_string(), order_id, from, to, actor, note, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Status history of an order, oldest first
+pub fn history(conn: &Connection, order_id: &str) -> Vec<StatusChange> {
+    let mut stmt = match conn.prepare(
+        "SELECT id, from_status, to_status, actor, note, created_at FROM order_status_history
+         WHERE order_id = ?1 ORDER BY created_at ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+
+    stmt.query_map(params![order_id], |row| {
+        Ok(StatusChange {
+            id: row.get(0)?,
+            from_status: row.get(1)?,
+            to_status: row.get(2)?,
+            actor: row.get(3)?,
+            note: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
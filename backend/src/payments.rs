@@ -0,0 +1,97 @@
+use rusqlite::{params, Connection};
+
+use crate::models::Payment;
+use crate::text;
+
+/// How money changes hands, with the labels shown to staff
+pub const METHODS: [(&str, &str); 3] = [
+    ("cash", "Tiền mặt"),
+    ("bank_transfer", "Chuyển khoản"),
+    ("e_wallet", "Ví điện tử"),
+];
+
+pub const PAYMENT_STATUSES: [&str; 4] = ["unpaid", "deposit", "paid", "refunded"];
+
+/// Payment status as printed on invoices and exports
+pub fn status_label(status: &str) -> &'static str {
+    match status {
+        "paid" => "Đã thanh toán",
+        "deposit" => "Đã đặt cọc",
+        "refunded" => "Đã hoàn tiền",
+        _ => "Chưa thanh toán",
+    }
+}
+
+pub fn method_label(method: &str) -> String {
+    text::label(&METHODS, method)
+}
+
+pub fn is_valid_method(method: &str) -> bool {
+    METHODS.iter().any(|(m, _)| *m == method)
+}
+
+/// Where an order stands given what was received net of refunds.
+/// Negative entries also correct mistakes, so an order only counts as
+/// refunded once they have given back everything received.
+pub fn payment_status(total: i64, paid: i64, has_refunds: bool) -> &'static str {
+    if paid > 0 && paid >= total {
+        "paid"
+    } else if paid > 0 {
+        "deposit"
+    } else if has_refunds {
+        "refunded"
+    } else {
+        "unpaid"
+    }
+}
+
+pub fn load_payments(conn: &Connection, order_id: &str) -> Vec<Payment> {
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT id, order_id, amount, method, reference, received_by, note, paid_at, created_at
+         FROM payments WHERE order_id = ?1 ORDER BY paid_at ASC, created_at ASC",
+    ) else {
+        return vec![];
+    };
+    stmt.query_map(params![order_id], |row| {
+        let method: String = row.get(3)?;
+        Ok(Payment {
+            id: row.get(0)?,
+            order_id: row.get(1)?,
+            amount: row.get(2)?,
+            method_label: method_label(&method),
+            method,
+            reference: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            received_by: row.get(5)?,
+            note: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+            paid_at: row.get(7)?,
+            created_at: row.get(8)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// Net amount received for an order so far
+pub fn paid_amount(conn: &Connection, order_id: &str) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM payments WHERE order_id = ?1",
+        params![order_id],
+        |row| row.get(0),
+    )
+}
+
+/// Recompute the order's `paid_amount` and `payment_status` from its payments
+pub fn refresh(conn: &Connection, order_id: &str) -> rusqlite::Result<()> {
+    let (total, paid, has_refunds): (i64, i64, bool) = conn.query_row(
+        "SELECT COALESCE(o.total, 0), COALESCE(SUM(p.amount), 0), COALESCE(MIN(p.amount), 0) < 0
+         FROM orders o LEFT JOIN payments p ON p.order_id = o.id
+         WHERE o.id = ?1",
+        params![order_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    conn.execute(
+        "UPDATE orders SET paid_amount = ?1, payment_status = ?2 WHERE id = ?3",
+        params![paid, payment_status(total, paid, has_refunds), order_id],
+    )?;
+    Ok(())
+}
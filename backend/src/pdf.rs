@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use qrcode::{Color, EcLevel, QrCode};
+use sha2::{Digest, Sha256};
+use unicode_normalization::UnicodeNormalization;
+
+/// Page sizes in points, portrait
+pub const A4: (f32, f32) = (595.0, 842.0);
+pub const A5: (f32, f32) = (420.0, 595.0);
+
+/// The shop's brand face, with Vietnamese glyphs; embedded as a subset of
+/// the glyphs actually drawn
+const FONT_DATA: &[u8] = include_bytes!("../fonts/SVN-Magellin.otf");
+const FONT_NAME: &str = "SVN-Magellin-Regular";
+const FONT_RESOURCE: Name = Name(b"F1");
+
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// A document laid out top-down. Coordinates are in points from the top-left
+/// corner; `y` is the running cursor that content helpers advance.
+pub struct Document {
+    face: ttf_parser::Face<'static>,
+    title: String,
+    page_size: (f32, f32),
+    pub margin: f32,
+    pub y: f32,
+    pages: Vec<Content>,
+    /// Glyphs drawn so far, with the character each stands for
+    glyphs: BTreeMap<u16, char>,
+}
+
+impl Document {
+    pub fn new(title: &str, page_size: (f32, f32), margin: f32) -> Document {
+        let face = ttf_parser::Face::parse(FONT_DATA, 0).expect("bundled font is valid");
+        Document {
+            face,
+            title: title.to_string(),
+            page_size,
+            margin,
+            y: margin,
+            pages: vec![Content::new()],
+            glyphs: BTreeMap::new(),
+        }
+    }
+
+    /// Right edge of the printable area
+    pub fn right(&self) -> f32 {
+        self.page_size.0 - self.margin
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(Content::new());
+        self.y = self.margin;
+    }
+
+    /// Start a new page unless `height` more points fit above the bottom margin
+    pub fn ensure_space(&mut self, height: f32) {
+        if self.y + height > self.page_size.1 - self.margin {
+            self.new_page();
+        }
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("a document always has a page")
+    }
+
+    fn glyph(&self, c: char) -> u16 {
+        self.face
+            .glyph_index(c)
+            .or_else(|| self.face.glyph_index('?'))
+            .map(|g| g.0)
+            .unwrap_or(0)
+    }
+
+    fn advance(&self, glyph: u16) -> f32 {
+        let units = self.face.glyph_hor_advance(ttf_parser::GlyphId(glyph)).unwrap_or(0);
+        units as f32 * 1000.0 / self.face.units_per_em() as f32
+    }
+
+    /// Width of `text` set at `size` points
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        text.nfc().map(|c| self.advance(self.glyph(c))).sum::<f32>() * size / 1000.0
+    }
+
+    /// Draw `text` with its baseline at `y`. The font only has precomposed
+    /// Vietnamese letters, so decomposed input is composed first.
+    pub fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        let mut encoded = Vec::with_capacity(text.len() * 2);
+        for c in text.nfc() {
+            let glyph = self.glyph(c);
+            self.glyphs.entry(glyph).or_insert(c);
+            encoded.extend_from_slice(&glyph.to_be_bytes());
+        }
+        let baseline = self.page_size.1 - y;
+        let page = self.page();
+        page.begin_text();
+        page.set_font(FONT_RESOURCE, size);
+        page.next_line(x, baseline);
+        page.show(Str(&encoded));
+        page.end_text();
+    }
+
+    /// Draw `text` ending at `right`
+    pub fn text_right(&mut self, right: f32, y: f32, size: f32, text: &str) {
+        let x = right - self.text_width(text, size);
+        self.text(x, y, size, text);
+    }
+
+    pub fn text_centered(&mut self, y: f32, size: f32, text: &str) {
+        let x = (self.page_size.0 - self.text_width(text, size)) / 2.0;
+        self.text(x, y, size, text);
+    }
+
+    /// Break `text` into lines no wider than `max_width`, at spaces where possible
+    pub fn wrap(&self, text: &str, size: f32, max_width: f32) -> Vec<String> {
+        let mut lines = vec![];
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+                if self.text_width(&candidate, size) <= max_width {
+                    line = candidate;
+                    continue;
+                }
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                // A single word wider than the column is split anywhere
+                for c in word.chars() {
+                    if !line.is_empty() && self.text_width(&format!("{}{}", line, c), size) > max_width {
+                        lines.push(std::mem::take(&mut line));
+                    }
+                    line.push(c);
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Write wrapped `text` at the cursor, moving it down one `leading` per line
+    pub fn paragraph(&mut self, x: f32, size: f32, leading: f32, max_width: f32, text: &str) {
+        for line in self.wrap(text, size, max_width) {
+            self.ensure_space(leading);
+            self.y += leading;
+            let y = self.y;
+            self.text(x, y, size, &line);
+        }
+    }
+
+    /// Horizontal rule across the printable area at `y`
+    pub fn rule(&mut self, y: f32, width: f32) {
+        let (left, right, top) = (self.margin, self.right(), self.page_size.1 - y);
+        let page = self.page();
+        page.set_line_width(width);
+        page.move_to(left, top);
+        page.line_to(right, top);
+        page.stroke();
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, rgb: (f32, f32, f32)) {
+        let top = self.page_size.1 - y - height;
+        let page = self.page();
+        page.save_state();
+        page.set_fill_rgb(rgb.0, rgb.1, rgb.2);
+        page.rect(x, top, width, height);
+        page.fill_nonzero();
+        page.restore_state();
+    }
+
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32, line_width: f32) {
+        let top = self.page_size.1 - y - height;
+        let page = self.page();
+        page.set_line_width(line_width);
+        page.rect(x, top, width, height);
+        page.stroke();
+    }
+
+    /// Vector QR code of `data`, `size` points wide with its top-left corner at (x, y)
+    pub fn qr(&mut self, x: f32, y: f32, size: f32, data: &str) -> Result<(), String> {
+        let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M).map_err(|e| e.to_string())?;
+        let modules = code.width();
+        let colors = code.to_colors();
+        let cell = size / modules as f32;
+        let page_height = self.page_size.1;
+        let page = self.page();
+        page.save_state();
+        page.set_fill_rgb(0.0, 0.0, 0.0);
+        for row in 0..modules {
+            // One rectangle per run of dark modules keeps the stream small
+            let mut col = 0;
+            while col < modules {
+                if colors[row * modules + col] != Color::Dark {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < modules && colors[row * modules + col] == Color::Dark {
+                    col += 1;
+                }
+                let top = page_height - y - (row + 1) as f32 * cell;
+                page.rect(x + start as f32 * cell, top, (col - start) as f32 * cell, cell);
+            }
+        }
+        page.fill_nonzero();
+        page.restore_state();
+        Ok(())
+    }
+
+    /// Serialize the document, embedding the subset of the font it uses
+    pub fn finish(mut self) -> Result<Vec<u8>, String> {
+        let pages = std::mem::take(&mut self.pages);
+        let mut next = Ref::new(1);
+        let mut alloc = || next.bump();
+        let catalog_id = alloc();
+        let page_tree_id = alloc();
+        let info_id = alloc();
+        let font_id = alloc();
+        let cid_font_id = alloc();
+        let descriptor_id = alloc();
+        let cmap_id = alloc();
+        let font_file_id = alloc();
+        let page_ids: Vec<(Ref, Ref)> = pages.iter().map(|_| (alloc(), alloc())).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().map(|(page, _)| *page))
+            .count(page_ids.len() as i32);
+        pdf.document_info(info_id).title(TextStr(&self.title));
+
+        let (width, height) = self.page_size;
+        for (content, (page_id, content_id)) in pages.into_iter().zip(&page_ids) {
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, width, height));
+            page.parent(page_tree_id);
+            page.contents(*content_id);
+            page.resources().fonts().pair(FONT_RESOURCE, font_id);
+            page.finish();
+            let data = compress_to_vec_zlib(&content.finish(), 6);
+            pdf.stream(*content_id, &data).filter(Filter::FlateDecode);
+        }
+
+        // Subsets are named with a tag derived from their glyphs (PDF 32000 9.6.4)
+        let glyph_ids: Vec<u16> = self.glyphs.keys().copied().collect();
+        let digest = Sha256::digest(glyph_ids.iter().flat_map(|g| g.to_be_bytes()).collect::<Vec<u8>>());
+        let tag: String = digest.iter().take(6).map(|b| (b'A' + b % 26) as char).collect();
+        let base_font = format!("{}+{}", tag, FONT_NAME);
+
+        pdf.type0_font(font_id)
+            .base_font(Name(base_font.as_bytes()))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font_id)
+            .to_unicode(cmap_id);
+
+        let mut cid_font = pdf.cid_font(cid_font_id);
+        cid_font.subtype(CidFontType::Type0);
+        cid_font.base_font(Name(base_font.as_bytes()));
+        cid_font.system_info(SYSTEM_INFO);
+        cid_font.font_descriptor(descriptor_id);
+        cid_font.default_width(0.0);
+        let mut widths = cid_font.widths();
+        for glyph in &glyph_ids {
+            widths.consecutive(*glyph, [self.advance(*glyph)]);
+        }
+        widths.finish();
+        cid_font.finish();
+
+        let scale = |units: i16| units as f32 * 1000.0 / self.face.units_per_em() as f32;
+        let bbox = self.face.global_bounding_box();
+        pdf.font_descriptor(descriptor_id)
+            .name(Name(base_font.as_bytes()))
+            .flags(FontFlags::NON_SYMBOLIC)
+            .bbox(Rect::new(scale(bbox.x_min), scale(bbox.y_min), scale(bbox.x_max), scale(bbox.y_max)))
+            .italic_angle(self.face.italic_angle().unwrap_or(0.0))
+            .ascent(scale(self.face.ascender()))
+            .descent(scale(self.face.descender()))
+            .cap_height(scale(self.face.capital_height().unwrap_or(self.face.ascender())))
+            .stem_v(80.0)
+            .font_file3(font_file_id);
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+        for (glyph, c) in &self.glyphs {
+            cmap.pair(*glyph, *c);
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+
+        // Glyph ids survive subsetting, so the CIDs written above stay valid
+        let subset = subsetter::subset(FONT_DATA, 0, subsetter::Profile::pdf(&glyph_ids))
+            .map_err(|e| format!("Cannot subset font: {:?}", e))?;
+        let font_data = compress_to_vec_zlib(&subset, 6);
+        pdf.stream(font_file_id, &font_data)
+            .filter(Filter::FlateDecode)
+            .pair(Name(b"Subtype"), Name(b"OpenType"));
+
+        Ok(pdf.finish())
+    }
+}
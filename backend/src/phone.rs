@@ -0,0 +1,143 @@
+/// 11-digit mobile prefixes renumbered to 10 digits in 2018 (old -> new)
+const LEGACY_PREFIXES: [(&str, &str); 21] = [
+    // Viettel
+    ("0162", "032"),
+    ("0163", "033"),
+    ("0164", "034"),
+    ("0165", "035"),
+    ("0166", "036"),
+    ("0167", "037"),
+    ("0168", "038"),
+    ("0169", "039"),
+    // MobiFone
+    ("0120", "070"),
+    ("0121", "079"),
+    ("0122", "077"),
+    ("0126", "076"),
+    ("0128", "078"),
+    // VinaPhone
+    ("0123", "083"),
+    ("0124", "084"),
+    ("0125", "085"),
+    ("0127", "081"),
+    ("0129", "082"),
+    // Vietnamobile
+    ("0186", "056"),
+    ("0188", "058"),
+    // Gmobile
+    ("0199", "059"),
+];
+
+/// Current 10-digit mobile prefixes
+const MOBILE_PREFIXES: [&str; 36] = [
+    "032", "033", "034", "035", "036", "037", "038", "039", // Viettel
+    "052", "056", "058", // Vietnamobile
+    "055", // Reddi
+    "059", // Gmobile
+    "070", "076", "077", "078", "079", "089", "090", "093", // MobiFone
+    "081", "082", "083", "084", "085", "088", "091", "094", // VinaPhone
+    "086", "096", "097", "098", // Viettel
+    "087", // iTel
+    "092", // Vietnamobile
+    "099", // Gmobile
+];
+
+/// Normalize a Vietnamese phone number to E.164 ("+84912503456").
+///
+/// Accepts national ("0912 503 456"), international ("+84 912 503 456",
+/// "0084...") and bare ("912503456") forms with spaces, dots, dashes or
+/// brackets, and remaps pre-2018 11-digit mobile numbers to their current
+/// prefix. Returns `None` unless the result is a current mobile number or a
+/// landline (0 + 2xx area code, 11 digits).
+pub fn normalize(phone: &str) -> Option<String> {
+    let phone = phone.trim();
+    if phone.is_empty() || !phone.chars().all(|c| c.is_ascii_digit() || " .-()+".contains(c)) {
+        return None;
+    }
+    if phone.rfind('+').is_some_and(|i| i > 0) {
+        return None;
+    }
+
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    let national = if phone.starts_with('+') {
+        format!("0{}", digits.strip_prefix("84")?)
+    } else if let Some(rest) = digits.strip_prefix("0084") {
+        format!("0{}", rest)
+    } else if digits.starts_with("84") && (digits.len() == 11 || digits.len() == 12) {
+        format!("0{}", &digits[2..])
+    } else if digits.starts_with('0') {
+        digits
+    } else {
+        format!("0{}", digits)
+    };
+
+    let national = remap_legacy(&national).unwrap_or(national);
+    let is_mobile = national.len() == 10 && MOBILE_PREFIXES.contains(&&national[..3]);
+    let is_landline = national.len() == 11 && national.starts_with("02");
+
+    (is_mobile || is_landline).then(|| format!("+84{}", &national[1..]))
+}
+
+fn remap_legacy(national: &str) -> Option<String> {
+    if national.len() != 11 || !national.starts_with("01") {
+        return None;
+    }
+    LEGACY_PREFIXES
+        .iter()
+        .find(|(old, _)| national.starts_with(old))
+        .map(|(old, new)| format!("{}{}", new, &national[old.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_mobile_forms() {
+        for input in [
+            "0912 503 456",
+            "0912.503.456",
+            "(091) 250-3456",
+            "+84 912 503 456",
+            "0084 912 503 456",
+            "84912503456",
+            "912503456",
+        ] {
+            assert_eq!(normalize(input).as_deref(), Some("+84912503456"), "{}", input);
+        }
+        // A bare 84 is a country code only when the length says so
+        assert_eq!(normalize("845123456").as_deref(), Some("+84845123456"));
+    }
+
+    #[test]
+    fn remaps_legacy_mobile_prefixes() {
+        assert_eq!(normalize("01662 345 678").as_deref(), Some("+84362345678"));
+        assert_eq!(normalize("+84 1662 345 678").as_deref(), Some("+84362345678"));
+        assert_eq!(normalize("0129 345 6789").as_deref(), Some("+84823456789"));
+    }
+
+    #[test]
+    fn normalizes_landlines() {
+        assert_eq!(normalize("024 3825 1234").as_deref(), Some("+842438251234"));
+        assert_eq!(normalize("+84 24 3825 1234").as_deref(), Some("+842438251234"));
+        assert_eq!(normalize("842438251234").as_deref(), Some("+842438251234"));
+    }
+
+    #[test]
+    fn rejects_junk() {
+        for input in [
+            "",
+            "   ",
+            "abc",
+            "0912 503 45x",
+            "091+2503456",
+            "+1 212 555 0100",
+            "0912 503 45",
+            "0912 503 4567",
+            "0112 345 678",
+            "0131 234 5678",
+        ] {
+            assert_eq!(normalize(input), None, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use rusqlite::Connection;
+
+use crate::models::{Product, Promotion};
+
+/// Shop-local offset (Vietnam, UTC+7) used for date-only sale boundaries
+pub fn shop_offset() -> FixedOffset {
+    FixedOffset::east_opt(7 * 3600).unwrap()
+}
+
+/// Parse an RFC 3339 timestamp or a plain `YYYY-MM-DD` date.
+///
+/// Plain dates are interpreted in shop-local time: as the start of the day,
+/// or the very end of it when `end_of_day` is set, so an `ends_at` of
+/// `2026-03-08` still includes the whole of March 8th.
+pub fn parse_datetime(value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+    } else {
+        NaiveTime::MIN
+    };
+    shop_offset()
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Check that an optional start/end pair is parseable and correctly ordered
+pub fn validate_window(starts_at: Option<&str>, ends_at: Option<&str>) -> Result<(), String> {
+    let starts = non_empty(starts_at)
+        .map(|s| parse_datetime(s, false).ok_or_else(|| format!("Invalid start date: {}", s)))
+        .transpose()?;
+    let ends = non_empty(ends_at)
+        .map(|s| parse_datetime(s, true).ok_or_else(|| format!("Invalid end date: {}", s)))
+        .transpose()?;
+    if let (Some(starts), Some(ends)) = (starts, ends) {
+        if starts >= ends {
+            return Err("Start date must be before end date".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Whether `now` falls inside an optional window; missing bounds are open-ended
+pub fn is_within_window(starts_at: Option<&str>, ends_at: Option<&str>, now: DateTime<Utc>) -> bool {
+    let started = match non_empty(starts_at) {
+        Some(s) => parse_datetime(s, false).is_some_and(|starts| starts <= now),
+        None => true,
+    };
+    let not_ended = match non_empty(ends_at) {
+        Some(s) => parse_datetime(s, true).is_some_and(|ends| now <= ends),
+        None => true,
+    };
+    started && not_ended
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|s| !s.is_empty())
+}
+
+pub const PROMOTION_COLUMNS: &str =
+    "id, name, discount_percent, category, starts_at, ends_at, active, created_at, updated_at";
+
+/// Map a row selected with `PROMOTION_COLUMNS`
+pub fn promotion_from_row(row: &rusqlite::Row) -> rusqlite::Result<Promotion> {
+    Ok(Promotion {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        discount_percent: row.get(2)?,
+        category: row.get(3)?,
+        starts_at: row.get(4)?,
+        ends_at: row.get(5)?,
+        active: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+/// Load promotions that are switched on and currently inside their window
+pub fn load_active_promotions(conn: &Connection, now: DateTime<Utc>) -> Vec<Promotion> {
+    let mut stmt = match conn.prepare(&format!("SELECT {} FROM promotions WHERE active = 1", PROMOTION_COLUMNS)) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+
+    let promotions = stmt
+        .query_map([], promotion_from_row)
+        .map(|rows| rows.filter_map(|r| r.ok()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    promotions
+        .into_iter()
+        .filter(|p| is_within_window(p.starts_at.as_deref(), p.ends_at.as_deref(), now))
+        .collect()
+}
+
+/// Fill in `effective_price`, `discount_percent` and `promotion_name`.
+///
+/// The customer gets the single best price among the list price, a running
+/// scheduled sale and any matching promotion; discounts never stack. The
+/// discount percentage is measured against the compare-at price when one is
+/// set above the list price, otherwise against the list price itself.
+pub fn apply_pricing(product: &mut Product, promotions: &[Promotion], now: DateTime<Utc>) {
+    let mut effective = product.price;
+    let mut promotion_name = None;
+
+    if let Some(sale_price) = product.sale_price.filter(|p| *p > 0) {
+        if sale_price < effective
+            && is_within_window(product.sale_starts_at.as_deref(), product.sale_ends_at.as_deref(), now)
+        {
+            effective = sale_price;
+        }
+    }
+
+    for promotion in promotions {
+        let applies = match promotion.category.as_deref().map(str::trim) {
+            None | Some("") => true,
+            Some(category) => category == product.category,
+        };
+        if !applies {
+            continue;
+        }
+        let discounted = discounted_price(product.price, promotion.discount_percent);
+        if discounted < effective {
+            effective = discounted;
+            promotion_name = Some(promotion.name.clone());
+        }
+    }
+
+    let reference = product.compare_at_price.unwrap_or(0).max(product.price);
+    product.effective_price = effective;
+    product.discount_percent = discount_percent(reference, effective);
+    product.promotion_name = promotion_name;
+}
+
+/// Apply a percentage discount, rounding the discount to the nearest đồng
+pub fn discounted_price(price: i64, percent: i64) -> i64 {
+    let percent = percent.clamp(0, 100);
+    price - (price * percent + 50) / 100
+}
+
+/// Rounded percentage saved going from `reference` down to `effective`
+pub fn discount_percent(reference: i64, effective: i64) -> i64 {
+    if reference <= 0 || effective >= reference {
+        return 0;
+    }
+    ((reference - effective) * 100 + reference / 2) / reference
+}
@@ -0,0 +1,63 @@
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::bundles;
+use crate::db::parse_json_array;
+use crate::models::Product;
+use crate::options;
+use crate::pricing;
+
+pub const PRODUCT_COLUMNS: &str = "id, name, description, price, images, category, status, sort_order, \
+     compare_at_price, sale_price, sale_starts_at, sale_ends_at, product_type, bundle_discount_percent, stock, \
+     created_at, updated_at, rental_price_per_day, rental_deposit, rental_buffer_days, rental_units";
+
+/// Map a row selected with `PRODUCT_COLUMNS`; computed fields are filled by
+/// `bundles::resolve_bundles` and `pricing::apply_pricing`
+pub fn product_from_row(row: &rusqlite::Row) -> rusqlite::Result<Product> {
+    let price: i64 = row.get(3)?;
+    Ok(Product {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        price,
+        images: parse_json_array(&row.get::<_, String>(4)?),
+        category: row.get(5)?,
+        status: row.get(6)?,
+        sort_order: row.get(7)?,
+        compare_at_price: row.get(8)?,
+        sale_price: row.get(9)?,
+        sale_starts_at: row.get(10)?,
+        sale_ends_at: row.get(11)?,
+        effective_price: price,
+        discount_percent: 0,
+        promotion_name: None,
+        product_type: row.get::<_, Option<String>>(12)?.unwrap_or_else(|| "single".to_string()),
+        bundle_discount_percent: row.get(13)?,
+        stock: row.get(14)?,
+        in_stock: row.get::<_, Option<i64>>(14)?.is_none_or(|s| s > 0),
+        rental_price_per_day: row.get(17)?,
+        rental_deposit: row.get::<_, Option<i64>>(18)?.unwrap_or(0),
+        rental_buffer_days: row.get::<_, Option<i64>>(19)?.unwrap_or(0),
+        rental_units: serde_json::from_str(&row.get::<_, Option<String>>(20)?.unwrap_or_default()).unwrap_or_default(),
+        components: None,
+        option_groups: None,
+        created_at: row.get(15)?,
+        updated_at: row.get(16).ok(),
+    })
+}
+
+/// Load one product with its current effective price applied
+pub fn load_product(conn: &Connection, id: &str) -> rusqlite::Result<Product> {
+    let mut product = conn.query_row(
+        &format!("SELECT {} FROM products WHERE id = ?1", PRODUCT_COLUMNS),
+        params![id],
+        product_from_row,
+    )?;
+
+    bundles::resolve_bundles(conn, std::slice::from_mut(&mut product), true);
+    product.option_groups = Some(options::load_groups(conn, &product.id));
+    let now = Utc::now();
+    let promotions = pricing::load_active_promotions(conn, now);
+    pricing::apply_pricing(&mut product, &promotions, now);
+    Ok(product)
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::models::{Product, RelatedProduct};
+
+pub const DEFAULT_RELATED_LIMIT: usize = 8;
+pub const MAX_RELATED_LIMIT: usize = 24;
+
+const PAIRING_WEIGHT: f64 = 100.0;
+const CO_ORDER_WEIGHT: f64 = 40.0;
+const CATEGORY_WEIGHT: f64 = 30.0;
+const PRICE_WEIGHT: f64 = 20.0;
+
+/// Signals gathered for one target product
+#[derive(Debug, Default)]
+pub struct RelatedSignals {
+    /// Admin-curated "pairs well with" links, mapped to their curated position
+    pub pairings: HashMap<String, usize>,
+    /// Number of customers who ordered both the target and the candidate
+    pub co_orders: HashMap<String, i64>,
+}
+
+/// Score every candidate against `target` and return the best `limit` of them.
+///
+/// Curated pairings always come first, in their curated order; among the
+/// rest, products bought by the same customers weigh most, then a shared
+/// category, then how close the current price is. Ties fall back to the
+/// catalog sort order.
+pub fn rank(target: &Product, candidates: Vec<Product>, signals: &RelatedSignals, limit: usize) -> Vec<RelatedProduct> {
+    let max_co_orders = signals.co_orders.values().copied().max().unwrap_or(0);
+
+    let mut scored: Vec<RelatedProduct> = candidates
+        .into_iter()
+        .filter(|p| p.id != target.id)
+        .map(|product| {
+            let mut score = 0.0;
+            let mut reasons = vec![];
+
+            if let Some(position) = signals.pairings.get(&product.id) {
+                score += PAIRING_WEIGHT - (*position as f64).min(PAIRING_WEIGHT / 2.0);
+                reasons.push("pairs_well_with".to_string());
+            }
+
+            if let Some(count) = signals.co_orders.get(&product.id).filter(|c| **c > 0) {
+                score += CO_ORDER_WEIGHT * (*count as f64) / (max_co_orders as f64);
+                reasons.push("bought_together".to_string());
+            }
+
+            if product.category == target.category {
+                score += CATEGORY_WEIGHT;
+                reasons.push("same_category".to_string());
+            }
+
+            let proximity = price_proximity(target.effective_price, product.effective_price);
+            if proximity > 0.0 {
+                score += PRICE_WEIGHT * proximity;
+                if proximity >= 0.75 {
+                    reasons.push("similar_price".to_string());
+                }
+            }
+
+            RelatedProduct { product, score: (score * 100.0).round() / 100.0, reasons }
+        })
+        .collect();
+
+    // Curated pairings come first in the admin's order, whatever the organic signals score
+    let position = |related: &RelatedProduct| signals.pairings.get(&related.product.id).copied().unwrap_or(usize::MAX);
+    scored.sort_by(|a, b| {
+        position(a)
+            .cmp(&position(b))
+            .then(b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal))
+            .then(a.product.sort_order.cmp(&b.product.sort_order))
+    });
+    scored.truncate(limit);
+    scored
+}
+
+/// 1.0 for identical prices, falling linearly to 0.0 at a 100% difference
+fn price_proximity(target: i64, candidate: i64) -> f64 {
+    if target <= 0 || candidate <= 0 {
+        return 0.0;
+    }
+    let diff = (target - candidate).abs() as f64 / target as f64;
+    (1.0 - diff).max(0.0)
+}
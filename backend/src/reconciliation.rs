@@ -0,0 +1,349 @@
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use crate::order_numbers::compact;
+use crate::text::fold;
+use crate::vietqr;
+
+/// Largest statement accepted, in bytes
+pub const MAX_FILE_SIZE: usize = 5 * 1024 * 1024;
+
+/// Header wording per column, most specific first, compared after `text::fold`.
+/// Bank exports differ in both language and naming.
+const DATE_HEADERS: [&str; 7] = [
+    "ngay giao dich", "ngay hieu luc", "transaction date", "posting date", "value date", "ngay", "date",
+];
+const CREDIT_HEADERS: [&str; 6] = ["so tien ghi co", "ghi co", "phat sinh co", "tien vao", "credit", "deposit"];
+const AMOUNT_HEADERS: [&str; 3] = ["so tien", "amount", "gia tri"];
+const DEBIT_WORDS: [&str; 4] = ["ghi no", "phat sinh no", "tien ra", "debit"];
+const MEMO_HEADERS: [&str; 8] = [
+    "noi dung", "dien giai", "mo ta", "description", "remark", "details", "narrative", "memo",
+];
+const REFERENCE_HEADERS: [&str; 6] = [
+    "so tham chieu", "ma giao dich", "so giao dich", "so but toan", "reference", "transaction id",
+];
+
+/// An incoming transfer read from a statement
+pub struct StatementRow {
+    /// Line in the file, 1-based
+    pub row_number: i64,
+    /// YYYY-MM-DD
+    pub date: String,
+    pub amount: i64,
+    pub memo: String,
+    pub reference: String,
+}
+
+impl StatementRow {
+    /// Identifies the same transfer when a statement period is imported twice
+    pub fn fingerprint(&self) -> String {
+        let key = if self.reference.is_empty() {
+            format!("{}|{}|{}", self.date, self.amount, compact(&self.memo))
+        } else {
+            format!("ref|{}", compact(&self.reference))
+        };
+        hex::encode(Sha256::digest(key.as_bytes()))
+    }
+}
+
+struct Columns {
+    date: usize,
+    amount: usize,
+    memo: usize,
+    reference: Option<usize>,
+}
+
+fn find_column(headers: &[String], candidates: &[&str], skip: &[&str]) -> Option<usize> {
+    candidates.iter().find_map(|candidate| {
+        headers
+            .iter()
+            .position(|h| h.contains(candidate) && !skip.iter().any(|s| h.contains(s)))
+    })
+}
+
+/// Columns of a header row, if it names a date, an amount and a memo
+fn header_columns(record: &csv::StringRecord) -> Option<Columns> {
+    let headers: Vec<String> = record.iter().map(|h| fold(h.trim_start_matches('\u{feff}')).trim().to_string()).collect();
+    Some(Columns {
+        date: find_column(&headers, &DATE_HEADERS, &[])?,
+        amount: find_column(&headers, &CREDIT_HEADERS, &[])
+            .or_else(|| find_column(&headers, &AMOUNT_HEADERS, &DEBIT_WORDS))?,
+        memo: find_column(&headers, &MEMO_HEADERS, &[])?,
+        reference: find_column(&headers, &REFERENCE_HEADERS, &[]),
+    })
+}
+
+/// The delimiter used most on the first lines: comma, semicolon or tab
+fn detect_delimiter(data: &[u8]) -> u8 {
+    let head: Vec<u8> = data.split(|b| *b == b'\n').take(10).flatten().copied().collect();
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|d| head.iter().filter(|b| *b == d).count())
+        .unwrap_or(b',')
+}
+
+/// "1.050.000", "1,050,000.00", "500000 VND" or "+2.000.000đ" as whole VND.
+/// Negative amounts (outgoing money) come back negative.
+pub fn parse_amount(value: &str) -> Option<i64> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let value = value.trim_end_matches(|c: char| c.is_alphabetic());
+    let negative = value.starts_with('-') || (value.starts_with('(') && value.ends_with(')'));
+    // Drop decimals ("1,050,000.00"); three digits after the last
+    // separator are a thousands group ("1.050.000")
+    let whole = match value.rfind(['.', ',']) {
+        Some(pos) if (1..=2).contains(&value[pos + 1..].chars().filter(|c| c.is_ascii_digit()).count()) => &value[..pos],
+        _ => value,
+    };
+    let digits: String = whole.chars().filter(|c| c.is_ascii_digit()).collect();
+    let amount: i64 = digits.parse().ok()?;
+    Some(if negative { -amount } else { amount })
+}
+
+/// "31/12/2026", "31/12/2026 14:05:00", "2026-12-31" or "31-12-2026" as YYYY-MM-DD
+pub fn parse_date(value: &str) -> Option<String> {
+    let day = value.trim().split([' ', 'T']).next()?;
+    ["%d/%m/%Y", "%Y-%m-%d", "%d-%m-%Y", "%Y/%m/%d", "%d/%m/%y", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(day, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// Incoming transfers of a CSV statement export. Bank preambles (account
+/// details, period) before the header row are skipped, as are outgoing
+/// transfers and summary lines without a date.
+pub fn parse_statement(data: &[u8]) -> Result<Vec<StatementRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(data))
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(data);
+
+    let mut columns: Option<Columns> = None;
+    let mut rows = vec![];
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Cannot read the statement: {}", e))?;
+        let Some(ref cols) = columns else {
+            columns = header_columns(&record);
+            continue;
+        };
+        let field = |i: usize| record.get(i).unwrap_or("").trim().to_string();
+        let (Some(date), Some(amount)) = (parse_date(&field(cols.date)), parse_amount(&field(cols.amount))) else {
+            continue;
+        };
+        if amount <= 0 {
+            continue;
+        }
+        rows.push(StatementRow {
+            // Blank lines yield no record, so count lines rather than records
+            row_number: record.position().map_or(0, |p| p.line() as i64),
+            date,
+            amount,
+            memo: field(cols.memo),
+            reference: cols.reference.map(field).unwrap_or_default(),
+        });
+    }
+
+    if columns.is_none() {
+        return Err("No header row with date, amount and description columns was found".to_string());
+    }
+    Ok(rows)
+}
+
+/// An open order a transfer may pay for
+pub struct Candidate {
+    pub order_id: String,
+    order_number: String,
+    tracking_code: String,
+    /// National ("0912503456") and international ("84912503456") digits
+    phones: [String; 2],
+    total: i64,
+    paid: i64,
+}
+
+/// Orders still expecting money, i.e. not cancelled, flagged as spam or paid in full
+pub fn load_candidates(conn: &Connection) -> rusqlite::Result<Vec<Candidate>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, COALESCE(order_number, ''), COALESCE(tracking_code, ''), customer_phone,
+                COALESCE(total, 0), COALESCE(paid_amount, 0)
+         FROM orders
+         WHERE status != 'cancelled' AND COALESCE(suspected_spam, 0) = 0
+           AND COALESCE(paid_amount, 0) < COALESCE(total, 0)
+         ORDER BY created_at ASC",
+    )?;
+    let candidates = stmt
+        .query_map([], |row| {
+            let phone: String = row.get(3)?;
+            let international = phone.trim_start_matches('+').to_string();
+            let national = international.strip_prefix("84").map(|n| format!("0{}", n)).unwrap_or_default();
+            Ok(Candidate {
+                order_id: row.get(0)?,
+                order_number: compact(&row.get::<_, String>(1)?),
+                tracking_code: compact(&row.get::<_, String>(2)?),
+                phones: [national, international],
+                total: row.get(4)?,
+                paid: row.get(5)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(candidates)
+}
+
+pub struct Proposal {
+    pub order_id: String,
+    pub confidence: i64,
+    pub reason: String,
+    pub expected_amount: i64,
+    pub amount_status: &'static str,
+}
+
+/// How `amount` compares with what the order owes, and the amount it was compared with
+fn amount_status(candidate: &Candidate, amount: i64, deposit_percent: i64) -> (&'static str, i64) {
+    let balance = candidate.total - candidate.paid;
+    let deposit = vietqr::deposit_due(candidate.total, candidate.paid, deposit_percent);
+    if amount == balance {
+        ("exact", balance)
+    } else if deposit > 0 && amount == deposit {
+        ("deposit", deposit)
+    } else if amount < balance {
+        ("underpaid", balance)
+    } else {
+        ("overpaid", balance)
+    }
+}
+
+/// Best order for a transfer. An order number or tracking code in the memo
+/// is a strong match; the customer's phone number alone is a weak one.
+/// Either is stronger when the amount is what the order owes.
+pub fn propose(row: &StatementRow, candidates: &[Candidate], deposit_percent: i64) -> Option<Proposal> {
+    let memo = compact(&row.memo);
+    let memo_digits: String = row.memo.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    let by_code: Vec<(&Candidate, &str)> = candidates
+        .iter()
+        .filter_map(|c| {
+            if !c.order_number.is_empty() && memo.contains(&c.order_number) {
+                Some((c, "order number"))
+            } else if !c.tracking_code.is_empty() && memo.contains(&c.tracking_code) {
+                Some((c, "tracking code"))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let (matches, strong) = if by_code.is_empty() {
+        let by_phone: Vec<(&Candidate, &str)> = candidates
+            .iter()
+            .filter(|c| c.phones.iter().any(|p| p.len() >= 9 && memo_digits.contains(p.as_str())))
+            .map(|c| (c, "phone number"))
+            .collect();
+        (by_phone, false)
+    } else {
+        (by_code, true)
+    };
+
+    // Among several orders, prefer one the amount settles, else the oldest
+    let scored: Vec<(&Candidate, &str, &'static str, i64)> = matches
+        .iter()
+        .map(|(c, reason)| {
+            let (status, expected) = amount_status(c, row.amount, deposit_percent);
+            (*c, *reason, status, expected)
+        })
+        .collect();
+    let (candidate, reason, status, expected) = scored
+        .iter()
+        .find(|(_, _, status, _)| matches!(*status, "exact" | "deposit"))
+        .or_else(|| scored.first())
+        .copied()?;
+
+    let amount_fits = matches!(status, "exact" | "deposit");
+    let mut confidence = match (strong, amount_fits) {
+        (true, true) => 95,
+        (true, false) => 70,
+        (false, true) => 60,
+        (false, false) => 30,
+    };
+    let mut reason = format!("{} in memo", reason);
+    if scored.len() > 1 {
+        confidence -= 20;
+        reason = format!("{}, {} orders match", reason, scored.len());
+    }
+    Some(Proposal {
+        order_id: candidate.order_id.clone(),
+        confidence,
+        reason,
+        expected_amount: expected,
+        amount_status: status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(order_id: &str, order_number: &str, phone: &str, total: i64) -> Candidate {
+        Candidate {
+            order_id: order_id.to_string(),
+            order_number: compact(order_number),
+            tracking_code: String::new(),
+            phones: [format!("0{}", &phone[3..]), phone[1..].to_string()],
+            total,
+            paid: 0,
+        }
+    }
+
+    fn transfer(memo: &str, amount: i64) -> StatementRow {
+        StatementRow {
+            row_number: 1,
+            date: "2026-10-19".to_string(),
+            amount,
+            memo: memo.to_string(),
+            reference: String::new(),
+        }
+    }
+
+    #[test]
+    fn parses_bank_amount_formats() {
+        assert_eq!(parse_amount("1.050.000"), Some(1_050_000));
+        assert_eq!(parse_amount("1,050,000.00"), Some(1_050_000));
+        assert_eq!(parse_amount("(500000)"), Some(-500_000));
+        assert_eq!(parse_amount("500.000,00"), Some(500_000));
+    }
+
+    #[test]
+    fn skips_preamble_before_header_row() {
+        let statement = "Sao kê tài khoản,0071000123456\n\
+                         Từ ngày,01/10/2026,Đến ngày,31/10/2026\n\
+                         \n\
+                         Ngày giao dịch,Số tiền ghi có,Số tiền ghi nợ,Nội dung\n\
+                         05/10/2026,\"1.050.000\",,HLV202600015 thanh toan\n\
+                         06/10/2026,,\"200.000\",Phi dich vu\n\
+                         Tổng cộng,\"1.050.000\",\"200.000\",\n";
+        let rows = parse_statement(statement.as_bytes()).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row_number, 5);
+        assert_eq!(rows[0].date, "2026-10-05");
+        assert_eq!(rows[0].amount, 1_050_000);
+        assert_eq!(rows[0].memo, "HLV202600015 thanh toan");
+    }
+
+    #[test]
+    fn order_number_in_memo_outranks_phone() {
+        let candidates = [
+            candidate("a", "HLV-2026-00015", "+84912503456", 1_050_000),
+            candidate("b", "HLV-2026-00016", "+84987654321", 1_050_000),
+        ];
+
+        let by_number = propose(&transfer("0987654321 chuyen tien HLV-2026-00015", 1_050_000), &candidates, 30).unwrap();
+        assert_eq!(by_number.order_id, "a");
+        assert_eq!(by_number.reason, "order number in memo");
+        assert_eq!(by_number.amount_status, "exact");
+        assert_eq!(by_number.confidence, 95);
+
+        let by_phone = propose(&transfer("0987 654 321 chuyen tien ao dai", 1_050_000), &candidates, 30).unwrap();
+        assert_eq!(by_phone.order_id, "b");
+        assert_eq!(by_phone.reason, "phone number in memo");
+        assert_eq!(by_phone.confidence, 60);
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{Duration, NaiveDate};
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+
+use crate::models::{Product, Rental, RentalDay};
+use crate::text;
+
+/// Rental lifecycle, with the Vietnamese labels shown to staff
+pub const STATUSES: [(&str, &str); 4] = [
+    ("reserved", "Đã đặt"),
+    ("picked_up", "Đang thuê"),
+    ("returned", "Đã trả"),
+    ("cancelled", "Đã hủy"),
+];
+
+pub const DEFAULT_BUFFER_DAYS: i64 = 1;
+pub const MAX_BUFFER_DAYS: i64 = 14;
+/// Pieces of one variant a product may have for rent
+pub const MAX_UNITS: i64 = 100;
+/// Longest single rental
+pub const MAX_RENTAL_DAYS: i64 = 30;
+/// How far ahead a rental may start
+pub const MAX_DAYS_AHEAD: i64 = 365;
+
+pub const RENTAL_SELECT: &str = "SELECT r.id, r.product_id, r.product_name, r.variant, r.order_id, o.order_number,
+        r.customer_id, r.customer_name, r.customer_phone, r.start_date, r.end_date, r.price_per_day, r.rental_total,
+        r.deposit, r.blocked_until, r.status, r.picked_up_at, r.returned_at, r.returned_on, r.late_fee,
+        r.damage_notes, r.damage_fee, r.deposit_refund, r.notes, r.created_at, r.updated_at
+     FROM rentals r
+     LEFT JOIN orders o ON o.id = r.order_id";
+
+pub fn rental_from_row(row: &rusqlite::Row) -> rusqlite::Result<Rental> {
+    let start_date: String = row.get(9)?;
+    let end_date: String = row.get(10)?;
+    let status: String = row.get(15)?;
+    Ok(Rental {
+        id: row.get(0)?,
+        product_id: row.get(1)?,
+        product_name: row.get(2)?,
+        variant: row.get(3)?,
+        order_id: row.get(4)?,
+        order_number: row.get(5)?,
+        customer_id: row.get(6)?,
+        customer_name: row.get(7)?,
+        customer_phone: row.get(8)?,
+        days: match (parse_date(&start_date), parse_date(&end_date)) {
+            (Some(start), Some(end)) => rental_days(start, end),
+            _ => 0,
+        },
+        start_date,
+        end_date,
+        price_per_day: row.get(11)?,
+        rental_total: row.get(12)?,
+        deposit: row.get(13)?,
+        blocked_until: row.get(14)?,
+        status_label: status_label(&status),
+        status,
+        picked_up_at: row.get(16)?,
+        returned_at: row.get(17)?,
+        returned_on: row.get(18)?,
+        late_fee: row.get::<_, Option<i64>>(19)?.unwrap_or(0),
+        damage_notes: row.get::<_, Option<String>>(20)?.unwrap_or_default(),
+        damage_fee: row.get::<_, Option<i64>>(21)?.unwrap_or(0),
+        deposit_refund: row.get(22)?,
+        notes: row.get::<_, Option<String>>(23)?.unwrap_or_default(),
+        created_at: row.get(24)?,
+        updated_at: row.get(25)?,
+    })
+}
+
+pub fn load_rental(conn: &Connection, id: &str) -> rusqlite::Result<Rental> {
+    conn.query_row(&format!("{} WHERE r.id = ?1", RENTAL_SELECT), params![id], rental_from_row)
+}
+
+pub fn status_label(status: &str) -> String {
+    text::label(&STATUSES, status)
+}
+
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Days charged, counting both the pickup day and the last day
+pub fn rental_days(start: NaiveDate, end: NaiveDate) -> i64 {
+    (end - start).num_days() + 1
+}
+
+/// Last day a piece is unavailable: the rental itself, then cleaning
+pub fn blocked_until(end: NaiveDate, buffer_days: i64) -> NaiveDate {
+    end + Duration::days(buffer_days)
+}
+
+/// Pieces of `variant` the product has for rent, if it is rentable in that variant
+pub fn units(product: &Product, variant: &str) -> Option<i64> {
+    product.rental_price_per_day?;
+    product.rental_units.get(variant.trim()).copied().filter(|n| *n > 0)
+}
+
+/// Date ranges during which pieces of a variant are taken, between `from` and `to`.
+/// A piece picked up and not yet back stays taken until it could be cleaned
+/// if it came back `today`, however late that is; a returned one stays taken
+/// while it is being cleaned.
+fn taken(
+    conn: &Connection,
+    product_id: &str,
+    variant: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    except: Option<&str>,
+    today: NaiveDate,
+) -> rusqlite::Result<Vec<(NaiveDate, NaiveDate)>> {
+    let mut stmt = conn.prepare(
+        "SELECT start_date, blocked_until, status, buffer_days FROM rentals
+         WHERE product_id = ?1 AND variant = ?2 AND status IN ('reserved', 'picked_up', 'returned')
+           AND start_date <= ?3 AND (blocked_until >= ?4 OR status = 'picked_up')
+           AND (?5 IS NULL OR id != ?5)",
+    )?;
+    let rows: Vec<(String, String, String, i64)> = stmt
+        .query_map(params![product_id, variant, format_date(to), format_date(from), except], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(start, until, status, buffer_days)| {
+            let (start, mut until) = (parse_date(&start)?, parse_date(&until)?);
+            if status == "picked_up" {
+                until = until.max(blocked_until(today, buffer_days));
+            }
+            (until >= from).then_some((start, until))
+        })
+        .collect())
+}
+
+fn count_on(ranges: &[(NaiveDate, NaiveDate)], day: NaiveDate) -> i64 {
+    ranges.iter().filter(|(start, until)| *start <= day && day <= *until).count() as i64
+}
+
+/// Free pieces per variant for each of `days` days from `from`
+pub fn calendar(
+    conn: &Connection,
+    product: &Product,
+    from: NaiveDate,
+    days: i64,
+    today: NaiveDate,
+) -> rusqlite::Result<Vec<RentalDay>> {
+    let to = from + Duration::days(days - 1);
+    let mut taken_by_variant = BTreeMap::new();
+    for variant in product.rental_units.keys() {
+        taken_by_variant.insert(variant.as_str(), taken(conn, &product.id, variant, from, to, None, today)?);
+    }
+
+    Ok(from
+        .iter_days()
+        .take(days as usize)
+        .map(|day| RentalDay {
+            date: format_date(day),
+            available: product
+                .rental_units
+                .iter()
+                .map(|(variant, units)| {
+                    let taken = count_on(&taken_by_variant[variant.as_str()], day);
+                    (variant.clone(), (units - taken).max(0))
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Check a piece of `variant` is free every day from `start` to `until`.
+/// Returns the first day all pieces are taken. Run inside the transaction
+/// that saves the rental so two bookings can't both take the last piece.
+pub fn first_conflict(
+    conn: &Connection,
+    product: &Product,
+    variant: &str,
+    start: NaiveDate,
+    until: NaiveDate,
+    except: Option<&str>,
+    today: NaiveDate,
+) -> rusqlite::Result<Option<NaiveDate>> {
+    let units = units(product, variant).unwrap_or(0);
+    let ranges = taken(conn, &product.id, variant, start, until, except, today)?;
+    Ok(start.iter_days().take_while(|day| *day <= until).find(|day| count_on(&ranges, *day) >= units))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returned_piece_stays_taken_while_cleaned() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../schema.sql")).unwrap();
+        conn.execute("INSERT INTO products (id, name, price, created_at) VALUES ('p1', 'Áo dài', 1500000, '2026-10-01')", []).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let start = today - Duration::days(3);
+        conn.execute(
+            "INSERT INTO rentals (id, product_id, product_name, variant, customer_name, customer_phone,
+                start_date, end_date, price_per_day, rental_total, buffer_days, blocked_until, status,
+                returned_on, created_at)
+             VALUES ('r1', 'p1', 'Áo dài', 'M', 'Lan', '+84912503456', ?1, ?2, 100000, 400000, 2, ?3,
+                'returned', ?2, ?2)",
+            params![format_date(start), format_date(today), format_date(blocked_until(today, 2))],
+        )
+        .unwrap();
+
+        let tomorrow = today + Duration::days(1);
+        let ranges = taken(&conn, "p1", "M", tomorrow, tomorrow + Duration::days(3), None, today).unwrap();
+        assert_eq!(count_on(&ranges, tomorrow), 1);
+        assert_eq!(count_on(&ranges, today + Duration::days(2)), 1);
+        assert_eq!(count_on(&ranges, today + Duration::days(3)), 0);
+    }
+}
@@ -0,0 +1,252 @@
+use serde_json::Value;
+
+use crate::measurements::Measurements;
+use crate::models::{SizeDeviation, SizeRecommendation, SizeScore};
+use crate::text;
+
+/// Chart keys and the measurement each one describes. `bust` may also carry
+/// a "bust-waist-hip" triple.
+const CHART_KEYS: [(&str, &str); 9] = [
+    ("weight", "weight"),
+    ("height", "height"),
+    ("bust", "bust"),
+    ("waist", "waist"),
+    ("hip", "hip"),
+    ("shoulder", "shoulder"),
+    ("ao_dai", "ao_dai_length"),
+    ("tay", "sleeve_length"),
+    ("quan", "pants_length"),
+];
+
+/// (field, relative weight, tolerance, deviation at which the score reaches 0)
+const SCORING: [(&str, f64, f64, f64); 9] = [
+    ("bust", 3.0, 2.0, 8.0),
+    ("waist", 2.0, 2.0, 8.0),
+    ("hip", 2.0, 2.0, 8.0),
+    ("weight", 2.0, 1.0, 6.0),
+    ("height", 1.0, 2.0, 10.0),
+    ("shoulder", 1.0, 1.0, 5.0),
+    ("ao_dai_length", 1.0, 2.0, 8.0),
+    ("sleeve_length", 1.0, 2.0, 8.0),
+    ("pants_length", 1.0, 2.0, 8.0),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bound {
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Bound {
+    fn exact(value: f64) -> Bound {
+        Bound { min: Some(value), max: Some(value) }
+    }
+
+    /// How far `value` falls outside the bound (0 inside), negative when below
+    fn deviation(&self, value: f64) -> f64 {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => value - min,
+            (_, Some(max)) if value > max => value - max,
+            _ => 0.0,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => format!("{}", min),
+            (Some(min), Some(max)) => format!("{}-{}", min, max),
+            (None, Some(max)) => format!("<= {}", max),
+            (Some(min), None) => format!(">= {}", min),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SizeSpec {
+    name: String,
+    fields: Vec<(&'static str, Bound)>,
+}
+
+impl SizeSpec {
+    fn bound(&self, field: &str) -> Option<Bound> {
+        self.fields.iter().find(|(f, _)| *f == field).map(|(_, b)| *b)
+    }
+
+    /// Rough size of the garment, for ordering sizes small to large
+    fn magnitude(&self) -> f64 {
+        ["weight", "bust", "height"]
+            .iter()
+            .filter_map(|field| self.bound(field))
+            .chain(self.fields.iter().map(|(_, b)| *b))
+            .find_map(|b| b.max.or(b.min))
+            .unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SizeChart {
+    sizes: Vec<SizeSpec>,
+}
+
+impl SizeChart {
+    /// Lowest and highest value any size allows for `field`
+    fn span(&self, field: &str) -> Option<(f64, f64)> {
+        let bounds: Vec<Bound> = self.sizes.iter().filter_map(|s| s.bound(field)).collect();
+        let low = bounds.iter().map(|b| b.min.unwrap_or(f64::NEG_INFINITY)).reduce(f64::min)?;
+        let high = bounds.iter().map(|b| b.max.unwrap_or(f64::INFINITY)).reduce(f64::max)?;
+        Some((low, high))
+    }
+}
+
+/// Parse and validate the `size_chart` setting.
+///
+/// The chart maps size names to objects whose values are numbers or short
+/// strings: "134" (target length), "90-94" (range), "dưới 53kg" / "trên 72"
+/// (open range) and, for `bust`, "84-66-90" (bust, waist, hip). An open
+/// "dưới" bound starts where the next smaller size of the same field ends,
+/// so sizes don't overlap.
+pub fn parse_chart(json: &str) -> Result<SizeChart, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("size_chart is not valid JSON: {}", e))?;
+    let entries = value.as_object().ok_or("size_chart must be an object of sizes")?;
+    if entries.is_empty() {
+        return Err("size_chart needs at least one size".to_string());
+    }
+
+    let mut sizes = vec![];
+    for (name, spec) in entries {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Size names cannot be empty".to_string());
+        }
+        let spec = spec.as_object().ok_or_else(|| format!("Size {} must be an object", name))?;
+        let mut fields: Vec<(&'static str, Bound)> = vec![];
+        for (key, raw) in spec {
+            let field = CHART_KEYS
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, f)| *f)
+                .ok_or_else(|| format!("Unknown size chart field {} in size {}", key, name))?;
+            let (text, numbers) = match raw {
+                Value::Number(n) => (String::new(), n.as_f64().into_iter().collect()),
+                Value::String(s) => (text::fold(s), numbers_in(s)),
+                _ => return Err(format!("{}.{} must be a number or string", name, key)),
+            };
+
+            if field == "bust" && numbers.len() == 3 {
+                fields.push(("bust", Bound::exact(numbers[0])));
+                fields.push(("waist", Bound::exact(numbers[1])));
+                fields.push(("hip", Bound::exact(numbers[2])));
+                continue;
+            }
+            let bound = match numbers.as_slice() {
+                [value] if text.contains("duoi") || text.contains('<') => Bound { min: None, max: Some(*value) },
+                [value] if text.contains("tren") || text.contains('>') => Bound { min: Some(*value), max: None },
+                [value] => Bound::exact(*value),
+                [low, high] if low <= high => Bound { min: Some(*low), max: Some(*high) },
+                _ => return Err(format!("Cannot read {}.{}: {}", name, key, raw)),
+            };
+            fields.push((field, bound));
+        }
+        if fields.is_empty() {
+            return Err(format!("Size {} has no measurements", name));
+        }
+        sizes.push(SizeSpec { name: name.to_string(), fields });
+    }
+
+    // JSON objects come back sorted by key, so order sizes by their measurements
+    sizes.sort_by(|a, b| a.magnitude().total_cmp(&b.magnitude()));
+
+    // Close open "below" bounds at the previous size's upper bound
+    let snapshot = sizes.clone();
+    for size in &mut sizes {
+        for (field, bound) in &mut size.fields {
+            if let (None, Some(max)) = (bound.min, bound.max) {
+                bound.min = snapshot
+                    .iter()
+                    .filter_map(|other| other.bound(field).and_then(|b| b.max))
+                    .filter(|other_max| *other_max < max)
+                    .reduce(f64::max);
+            }
+        }
+    }
+
+    Ok(SizeChart { sizes })
+}
+
+fn numbers_in(value: &str) -> Vec<f64> {
+    value
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .filter(|s| s.chars().any(|c| c.is_ascii_digit()))
+        .filter_map(|s| s.trim_matches(|c| c == '.' || c == ',').replace(',', ".").parse().ok())
+        .collect()
+}
+
+/// Score every size against the customer's measurements and pick the best.
+///
+/// Each compared field scores 1 within tolerance of the chart value and
+/// falls linearly to 0; the size score is the weighted mean. Confidence
+/// scales that score down when only a few of the chart's fields could be
+/// compared, and when the runner-up is nearly as good.
+pub fn recommend(chart: &SizeChart, measurements: &Measurements) -> Result<SizeRecommendation, String> {
+    let mut scored: Vec<(usize, f64, f64)> = vec![];
+    for (index, size) in chart.sizes.iter().enumerate() {
+        let mut total_weight = 0.0;
+        let mut compared_weight = 0.0;
+        let mut score = 0.0;
+        for (field, weight, tolerance, scale) in SCORING {
+            let Some(bound) = size.bound(field) else {
+                continue;
+            };
+            total_weight += weight;
+            if let Some(value) = measurements.field_cm(field) {
+                let excess = (bound.deviation(value).abs() - tolerance).max(0.0);
+                score += weight * (1.0 - excess / scale).max(0.0);
+                compared_weight += weight;
+            }
+        }
+        if compared_weight > 0.0 {
+            scored.push((index, score / compared_weight, compared_weight / total_weight));
+        }
+    }
+    if scored.is_empty() {
+        return Err("Provide at least one measurement the size chart covers".to_string());
+    }
+
+    // Stable sort keeps chart order on ties, favouring the smaller size
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (best, best_score, coverage) = scored[0];
+    let margin = scored.get(1).map(|(_, s, _)| best_score - s).unwrap_or(1.0);
+    let confidence = best_score * (0.5 + 0.5 * coverage) * (0.75 + 0.25 * (margin * 4.0).min(1.0));
+
+    let size = &chart.sizes[best];
+    let out_of_range = SCORING
+        .iter()
+        .filter_map(|(field, _, tolerance, _)| {
+            let bound = size.bound(field)?;
+            let value = measurements.field_cm(field)?;
+            let deviation = bound.deviation(value);
+            (deviation.abs() > *tolerance).then(|| SizeDeviation {
+                field: field.to_string(),
+                value: round(value),
+                expected: bound.describe(),
+                direction: if deviation > 0.0 { "above" } else { "below" }.to_string(),
+                outside_chart: chart.span(field).is_some_and(|(low, high)| value < low - tolerance || value > high + tolerance),
+            })
+        })
+        .collect();
+
+    Ok(SizeRecommendation {
+        size: size.name.clone(),
+        confidence: round(confidence),
+        out_of_range,
+        ranking: scored
+            .iter()
+            .map(|(index, score, _)| SizeScore { size: chart.sizes[*index].name.clone(), score: round(*score) })
+            .collect(),
+    })
+}
+
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
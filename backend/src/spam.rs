@@ -0,0 +1,269 @@
+use axum::http::HeaderMap;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+use crate::handlers::auth::get_jwt_secret;
+use crate::models::{FormToken, OrderItemInput};
+
+/// Storefront orders accepted per client IP, and per phone number, within `WINDOW_MINUTES`
+pub const MAX_ORDERS_PER_IP: i64 = 5;
+pub const MAX_ORDERS_PER_PHONE: i64 = 3;
+pub const WINDOW_MINUTES: i64 = 10;
+
+/// Failed order lookups (phone plus order number) allowed per client IP within `WINDOW_MINUTES`
+pub const MAX_LOOKUP_MISSES_PER_IP: i64 = 10;
+
+/// A form token is good for this long after the page was loaded
+const TOKEN_TTL_HOURS: i64 = 2;
+/// People take longer than this to fill in the order form
+const MIN_FILL_SECONDS: i64 = 3;
+/// Submissions (and lookup misses) are only needed for rate limits, duplicate and token-reuse checks
+const KEEP_SUBMISSIONS_DAYS: i64 = 1;
+
+/// Outcome for a submission that may go ahead
+pub struct Verdict {
+    /// Borderline signals; any at all flag the order as suspected spam
+    pub reasons: Vec<String>,
+    pub token_nonce: Option<String>,
+}
+
+/// Why a submission was refused outright
+pub enum Rejection {
+    RateLimited,
+    /// Same phone and items as a recent order, with that order's number
+    Duplicate(String),
+    InvalidToken(&'static str),
+}
+
+/// A storefront order about to be created
+pub struct Submission<'a> {
+    pub ip: &'a str,
+    pub phone: &'a str,
+    pub fingerprint: String,
+    /// The hidden field real customers never see, so never fill in
+    pub honeypot: &'a str,
+    pub form_token: Option<&'a str>,
+    /// Whether orders without a valid form token are refused rather than flagged
+    pub require_token: bool,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac(payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(get_jwt_secret().as_bytes()).expect("HMAC accepts any key length");
+    mac.update(b"order-form:");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Issue a signed token the order form sends back with the order
+pub fn issue_token(now: DateTime<Utc>) -> FormToken {
+    let payload = format!("{}.{}", now.timestamp(), Uuid::new_v4().simple());
+    FormToken {
+        token: format!("{}.{}", payload, hex::encode(mac(&payload).finalize().into_bytes())),
+        expires_at: (now + Duration::hours(TOKEN_TTL_HOURS)).to_rfc3339(),
+    }
+}
+
+enum TokenCheck {
+    Valid { nonce: String, too_fast: bool },
+    Expired,
+    Invalid,
+}
+
+fn check_token(token: &str, now: DateTime<Utc>) -> TokenCheck {
+    let mut parts = token.trim().splitn(3, '.');
+    let (Some(issued), Some(nonce), Some(signature)) = (parts.next(), parts.next(), parts.next()) else {
+        return TokenCheck::Invalid;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return TokenCheck::Invalid;
+    };
+    if mac(&format!("{}.{}", issued, nonce)).verify_slice(&signature).is_err() {
+        return TokenCheck::Invalid;
+    }
+    let Some(issued) = issued.parse().ok().and_then(|t| DateTime::from_timestamp(t, 0)) else {
+        return TokenCheck::Invalid;
+    };
+    if now - issued > Duration::hours(TOKEN_TTL_HOURS) {
+        return TokenCheck::Expired;
+    }
+    TokenCheck::Valid {
+        nonce: nonce.to_string(),
+        too_fast: now - issued < Duration::seconds(MIN_FILL_SECONDS),
+    }
+}
+
+/// Client address for rate limiting. The API only sits behind Traefik, which
+/// overwrites `X-Real-Ip` with the address it saw, so the header can't be
+/// spoofed from outside; without it the peer address is used.
+pub fn client_ip(headers: &HeaderMap, peer: SocketAddr) -> String {
+    headers
+        .get("x-real-ip")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| peer.ip().to_string())
+}
+
+/// What the order is for, independent of option order and spacing, so a
+/// double-clicked submit or a resent form is recognised
+pub fn fingerprint(phone: &str, inputs: &[OrderItemInput], product_name: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(phone.as_bytes());
+    for input in inputs {
+        let mut choices: Vec<String> = input
+            .selected_options
+            .iter()
+            .map(|o| format!("{}={}", o.group_id, o.choice_ids.join("+")))
+            .collect();
+        choices.sort();
+        hasher.update(format!("\n{}|{}|{}|{}", input.product_id, input.variant.trim(), input.quantity, choices.join(",")));
+    }
+    if inputs.is_empty() {
+        hasher.update(format!("\n{}", product_name.trim().to_lowercase()));
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Decide whether a storefront order goes ahead. Clear abuse is refused;
+/// borderline signals come back as reasons to flag the order as suspected
+/// spam, so a real customer with an odd browser still gets through.
+///
+/// Run under the same lock as `record` so concurrent submissions can't both
+/// slip under a limit.
+pub fn check(conn: &Connection, submission: &Submission, now: DateTime<Utc>) -> Result<Verdict, Rejection> {
+    let since = (now - Duration::minutes(WINDOW_MINUTES)).to_rfc3339();
+    let count = |column: &str, value: &str| -> i64 {
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM order_submissions WHERE {} = ?1 AND created_at > ?2", column),
+            params![value, since],
+            |row| row.get(0),
+        )
+        .unwrap_or(0)
+    };
+    if count("ip", submission.ip) >= MAX_ORDERS_PER_IP || count("phone", submission.phone) >= MAX_ORDERS_PER_PHONE {
+        return Err(Rejection::RateLimited);
+    }
+
+    let duplicate: Option<String> = conn
+        .query_row(
+            "SELECT COALESCE(o.order_number, o.id) FROM order_submissions s JOIN orders o ON o.id = s.order_id
+             WHERE s.phone = ?1 AND s.fingerprint = ?2 AND s.created_at > ?3
+             ORDER BY s.created_at DESC LIMIT 1",
+            params![submission.phone, submission.fingerprint, since],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap_or(None);
+    if let Some(order_number) = duplicate {
+        return Err(Rejection::Duplicate(order_number));
+    }
+
+    let mut reasons = vec![];
+    if !submission.honeypot.trim().is_empty() {
+        reasons.push("honeypot".to_string());
+    }
+
+    let mut token_nonce = None;
+    match submission.form_token.map(str::trim).filter(|t| !t.is_empty()).map(|t| check_token(t, now)) {
+        Some(TokenCheck::Valid { nonce, too_fast }) => {
+            let reused: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM order_submissions WHERE token_nonce = ?1",
+                    params![nonce],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
+            if reused {
+                reasons.push("form_token_reused".to_string());
+            }
+            if too_fast {
+                reasons.push("submitted_too_fast".to_string());
+            }
+            token_nonce = Some(nonce);
+        }
+        Some(TokenCheck::Expired) if submission.require_token => {
+            return Err(Rejection::InvalidToken("The order form has expired, please reload the page"));
+        }
+        Some(TokenCheck::Expired) => reasons.push("form_token_expired".to_string()),
+        Some(TokenCheck::Invalid) if submission.require_token => {
+            return Err(Rejection::InvalidToken("Invalid form token"));
+        }
+        Some(TokenCheck::Invalid) => reasons.push("form_token_invalid".to_string()),
+        None if submission.require_token => {
+            return Err(Rejection::InvalidToken("Missing form token"));
+        }
+        None => {}
+    }
+
+    Ok(Verdict { reasons, token_nonce })
+}
+
+/// Remember an accepted submission (caller handles the transaction)
+pub fn record(
+    conn: &Connection,
+    submission: &Submission,
+    verdict: &Verdict,
+    order_id: &str,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM order_submissions WHERE created_at < ?1",
+        params![(now - Duration::days(KEEP_SUBMISSIONS_DAYS)).to_rfc3339()],
+    )?;
+    conn.execute(
+        "INSERT INTO order_submissions (id, order_id, ip, phone, fingerprint, token_nonce, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Uuid::new_v4().to_string(),
+            order_id,
+            submission.ip,
+            submission.phone,
+            submission.fingerprint,
+            verdict.token_nonce,
+            now.to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Whether a client may look up another order by phone and order number.
+/// Order numbers are sequential, so only misses are counted: they are what
+/// guessing a customer's other orders produces.
+pub fn lookup_allowed(conn: &Connection, ip: &str, now: DateTime<Utc>) -> bool {
+    let since = (now - Duration::minutes(WINDOW_MINUTES)).to_rfc3339();
+    let misses: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM tracking_lookup_misses WHERE ip = ?1 AND created_at > ?2",
+            params![ip, since],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+    misses < MAX_LOOKUP_MISSES_PER_IP
+}
+
+/// Remember a lookup that matched no order
+pub fn record_lookup_miss(conn: &Connection, ip: &str, now: DateTime<Utc>) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM tracking_lookup_misses WHERE created_at < ?1",
+        params![(now - Duration::days(KEEP_SUBMISSIONS_DAYS)).to_rfc3339()],
+    )?;
+    conn.execute(
+        "INSERT INTO tracking_lookup_misses (ip, created_at) VALUES (?1, ?2)",
+        params![ip, now.to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Whether the `require_form_token` setting is on
+pub fn token_required(conn: &Connection) -> bool {
+    conn.query_row("SELECT value FROM settings WHERE key = 'require_form_token'", [], |row| row.get::<_, String>(0))
+        .map(|v| v.trim() == "true")
+        .unwrap_or(false)
+}
@@ -0,0 +1,24 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Lower-case and strip Vietnamese diacritics ("Vòng Ngực" -> "vong nguc"),
+/// so text typed with or without accents compares equal
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+        .map(|c| match c {
+            'đ' | 'Đ' => 'd',
+            _ => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Label shown to staff for a stored code, from `(code, label)` pairs; codes
+/// without one (legacy values) are shown as they are
+pub fn label(labels: &[(&str, &str)], code: &str) -> String {
+    labels
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
@@ -0,0 +1,168 @@
+use image::Luma;
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use rusqlite::Connection;
+use std::io::Cursor;
+
+/// NAPAS identifier for VietQR in the merchant account information
+const NAPAS_GUID: &str = "A000000727";
+/// Transfer to a bank account (as opposed to a card)
+const SERVICE_TO_ACCOUNT: &str = "QRIBFTTA";
+const CURRENCY_VND: &str = "704";
+/// Banking apps cut transfer memos longer than this
+const MAX_MEMO_LEN: usize = 25;
+/// Side of the rendered QR image, quiet zone included
+const IMAGE_SIZE: u32 = 320;
+
+pub const DEFAULT_DEPOSIT_PERCENT: i64 = 50;
+
+/// The shop account customers transfer to, from the `bank_*` settings
+pub struct BankAccount {
+    pub bin: String,
+    pub account_number: String,
+    pub account_name: String,
+}
+
+/// Bank account from settings, if one is configured
+pub fn load_account(conn: &Connection) -> Option<BankAccount> {
+    let setting = |key: &str| -> String {
+        conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get::<_, String>(0))
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default()
+    };
+    let account = BankAccount {
+        bin: setting("bank_bin"),
+        account_number: setting("bank_account_number"),
+        account_name: setting("bank_account_name"),
+    };
+    (validate_bin(&account.bin).is_ok() && validate_account_number(&account.account_number).is_ok()).then_some(account)
+}
+
+/// Share of the total asked up front, from the `deposit_percent` setting
+pub fn deposit_percent(conn: &Connection) -> i64 {
+    conn.query_row("SELECT value FROM settings WHERE key = 'deposit_percent'", [], |row| row.get::<_, String>(0))
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|p| (0..=100).contains(p))
+        .unwrap_or(DEFAULT_DEPOSIT_PERCENT)
+}
+
+/// Deposit still owed: `percent` of the total, rounded up to 1,000 VND, less what was paid
+pub fn deposit_due(total: i64, paid: i64, percent: i64) -> i64 {
+    let deposit = (total * percent + 99) / 100;
+    let deposit = (deposit + 999) / 1000 * 1000;
+    (deposit.min(total) - paid).max(0)
+}
+
+/// The 6-digit NAPAS bank identification number, e.g. 970436 for Vietcombank
+pub fn validate_bin(bin: &str) -> Result<(), String> {
+    if bin.len() == 6 && bin.chars().all(|c| c.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err("bank_bin must be the bank's 6-digit NAPAS BIN".to_string())
+    }
+}
+
+pub fn validate_account_number(number: &str) -> Result<(), String> {
+    if (1..=19).contains(&number.len()) && number.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(())
+    } else {
+        Err("bank_account_number must be up to 19 letters and digits".to_string())
+    }
+}
+
+/// EMVCo data object: two-digit id, two-digit length, value
+fn tlv(id: &str, value: &str) -> String {
+    format!("{}{:02}{}", id, value.len(), value)
+}
+
+/// CRC-16/CCITT-FALSE, as EMVCo requires for field 63
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Memo as banks accept it: ASCII letters, digits and spaces
+fn clean_memo(memo: &str) -> String {
+    memo.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ')
+        .take(MAX_MEMO_LEN)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// VietQR payload for a transfer of `amount` VND with `memo` prefilled.
+/// An `amount` of 0 leaves it for the customer to type.
+pub fn payload(account: &BankAccount, amount: i64, memo: &str) -> String {
+    let beneficiary = tlv("00", &account.bin) + &tlv("01", &account.account_number);
+    let merchant = tlv("00", NAPAS_GUID) + &tlv("01", &beneficiary) + &tlv("02", SERVICE_TO_ACCOUNT);
+
+    let mut data = tlv("00", "01");
+    // 12: dynamic, for one payment; 11: static, reusable
+    data += &tlv("01", if amount > 0 { "12" } else { "11" });
+    data += &tlv("38", &merchant);
+    data += &tlv("53", CURRENCY_VND);
+    if amount > 0 {
+        data += &tlv("54", &amount.to_string());
+    }
+    data += &tlv("58", "VN");
+    let memo = clean_memo(memo);
+    if !memo.is_empty() {
+        data += &tlv("62", &tlv("08", &memo));
+    }
+    data += "6304";
+    let crc = crc16(data.as_bytes());
+    format!("{}{:04X}", data, crc)
+}
+
+fn qr_code(payload: &str) -> Result<QrCode, String> {
+    QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::M).map_err(|e| e.to_string())
+}
+
+pub fn png(payload: &str) -> Result<Vec<u8>, String> {
+    let image = qr_code(payload)?
+        .render::<Luma<u8>>()
+        .min_dimensions(IMAGE_SIZE, IMAGE_SIZE)
+        .build();
+    let mut buf = Cursor::new(Vec::new());
+    image.write_to(&mut buf, image::ImageFormat::Png).map_err(|e| e.to_string())?;
+    Ok(buf.into_inner())
+}
+
+pub fn svg(payload: &str) -> Result<String, String> {
+    Ok(qr_code(payload)?
+        .render::<svg::Color>()
+        .min_dimensions(IMAGE_SIZE, IMAGE_SIZE)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc16_is_ccitt_false() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn payload_for_dynamic_transfer() {
+        let account = BankAccount {
+            bin: "970436".to_string(),
+            account_number: "0011001234567".to_string(),
+            account_name: "HY LAC VIET".to_string(),
+        };
+        assert_eq!(
+            payload(&account, 150_000, "HLV-2026-00015"),
+            "00020101021238570010A00000072701270006970436011300110012345670208QRIBFTTA\
+             530370454061500005802VN62160812HLV20260001563049821"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use rusqlite::{params, Connection};
+
+use crate::measurements::Measurements;
+use crate::models::{GroupProgress, OrderItem, OrderWearer};
+
+/// Where each wearer's garment is, in production order
+pub const FITTING_STATUSES: [(&str, &str); 7] = [
+    ("pending", "Chờ lấy số đo"),
+    ("measured", "Đã lấy số đo"),
+    ("cutting", "Đang cắt vải"),
+    ("sewing", "Đang may"),
+    ("fitting", "Thử đồ"),
+    ("ready", "Đã xong"),
+    ("delivered", "Đã giao"),
+];
+
+pub fn is_valid_status(status: &str) -> bool {
+    FITTING_STATUSES.iter().any(|(s, _)| *s == status)
+}
+
+/// Insert the wearers of a new order (caller handles the transaction)
+pub fn save_wearers(conn: &Connection, order_id: &str, wearers: &[OrderWearer]) -> rusqlite::Result<()> {
+    for (position, wearer) in wearers.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO order_wearers (id, order_id, name, role, measurements, fitting_status, notes, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                wearer.id,
+                order_id,
+                wearer.name,
+                wearer.role,
+                wearer.measurements.to_json(),
+                wearer.fitting_status,
+                wearer.notes,
+                position as i64
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Wearers of an order, in the order they were entered
+pub fn load_wearers(conn: &Connection, order_id: &str) -> Vec<OrderWearer> {
+    let mut stmt = match conn.prepare(
+        "SELECT id, name, role, measurements, fitting_status, notes, updated_at FROM order_wearers
+         WHERE order_id = ?1 ORDER BY sort_order ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(_) => return vec![],
+    };
+
+    stmt.query_map(params![order_id], |row| {
+        Ok(OrderWearer {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            role: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            measurements: Measurements::from_stored(&row.get::<_, Option<String>>(3)?.unwrap_or_default()),
+            fitting_status: row.get::<_, Option<String>>(4)?.unwrap_or_else(|| "pending".to_string()),
+            notes: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            updated_at: row.get(6)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// Count garments (item quantities) by their wearer's fitting status.
+/// Items not assigned to a wearer, like shared accessories, aren't counted.
+pub fn progress(wearers: &[OrderWearer], items: &[OrderItem]) -> GroupProgress {
+    let mut by_status: BTreeMap<String, i64> = BTreeMap::new();
+    for item in items {
+        let Some(wearer) = item.wearer_id.as_ref().and_then(|id| wearers.iter().find(|w| w.id == *id)) else {
+            continue;
+        };
+        *by_status.entry(wearer.fitting_status.clone()).or_default() += item.quantity;
+    }
+
+    let count = |status: &str| by_status.get(status).copied().unwrap_or(0);
+    GroupProgress {
+        garments: by_status.values().sum(),
+        ready: count("ready") + count("delivered"),
+        delivered: count("delivered"),
+        by_status,
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+use crate::db::parse_json_array;
+use crate::models::{BoardCard, BoardColumn, Tailor, TailorWorkload, WorkAssignment};
+use crate::order_status;
+use crate::pricing::shop_offset;
+use crate::text;
+
+/// Production stages, in the order a garment goes through them
+pub const STAGES: [(&str, &str); 3] = [
+    ("cutting", "Cắt vải"),
+    ("sewing", "May"),
+    ("finishing", "Hoàn thiện"),
+];
+
+/// Board column for garments through every stage
+pub const READY: (&str, &str) = ("ready", "Đã xong");
+
+pub const STATUSES: [(&str, &str); 3] = [
+    ("todo", "Chưa làm"),
+    ("in_progress", "Đang làm"),
+    ("done", "Đã xong"),
+];
+
+/// Order statuses whose garments show on the workshop board
+const IN_PRODUCTION: [&str; 5] = ["confirmed", "measuring", "cutting", "sewing", "fitting"];
+
+pub const MAX_ESTIMATED_HOURS: f64 = 500.0;
+pub const MAX_HOURS_PER_DAY: f64 = 24.0;
+
+pub const TAILOR_SELECT: &str =
+    "SELECT id, name, phone, stages, hours_per_day, active, notes, created_at, updated_at FROM tailors";
+
+pub const ASSIGNMENT_SELECT: &str = "SELECT a.id, a.order_id, o.order_number, o.customer_name, a.order_item_id, i.product_name,
+        a.stage, a.tailor_id, t.name, a.estimated_hours, a.due_date, a.status, a.started_at, a.completed_at,
+        a.notes, a.assigned_by, a.created_at, a.updated_at
+     FROM work_assignments a
+     JOIN orders o ON o.id = a.order_id
+     JOIN tailors t ON t.id = a.tailor_id
+     LEFT JOIN order_items i ON i.id = a.order_item_id";
+
+/// Stages in production order, for ORDER BY
+const STAGE_ORDER: &str = "CASE a.stage WHEN 'cutting' THEN 0 WHEN 'sewing' THEN 1 ELSE 2 END";
+
+pub fn is_stage(stage: &str) -> bool {
+    STAGES.iter().any(|(s, _)| *s == stage)
+}
+
+pub fn is_status(status: &str) -> bool {
+    STATUSES.iter().any(|(s, _)| *s == status)
+}
+
+fn today() -> String {
+    Utc::now().with_timezone(&shop_offset()).format("%Y-%m-%d").to_string()
+}
+
+pub fn tailor_from_row(row: &rusqlite::Row) -> rusqlite::Result<Tailor> {
+    Ok(Tailor {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        phone: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+        stages: parse_json_array(&row.get::<_, String>(3)?),
+        hours_per_day: row.get(4)?,
+        active: row.get::<_, Option<bool>>(5)?.unwrap_or(true),
+        notes: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+pub fn load_tailor(conn: &Connection, id: &str) -> rusqlite::Result<Tailor> {
+    conn.query_row(&format!("{} WHERE id = ?1", TAILOR_SELECT), params![id], tailor_from_row)
+}
+
+/// Map a row selected with `ASSIGNMENT_SELECT`
+pub fn assignment_from_row(row: &rusqlite::Row) -> rusqlite::Result<WorkAssignment> {
+    let stage: String = row.get(6)?;
+    let status: String = row.get(11)?;
+    let due_date: Option<String> = row.get(10)?;
+    Ok(WorkAssignment {
+        id: row.get(0)?,
+        order_id: row.get(1)?,
+        order_number: row.get(2)?,
+        customer_name: row.get(3)?,
+        order_item_id: row.get(4)?,
+        product_name: row.get(5)?,
+        stage_label: text::label(&STAGES, &stage),
+        stage,
+        tailor_id: row.get(7)?,
+        tailor_name: row.get(8)?,
+        estimated_hours: row.get(9)?,
+        overdue: status != "done" && due_date.as_deref().is_some_and(|d| d < today().as_str()),
+        due_date,
+        status_label: text::label(&STATUSES, &status),
+        status,
+        started_at: row.get(12)?,
+        completed_at: row.get(13)?,
+        notes: row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+        assigned_by: row.get(15)?,
+        created_at: row.get(16)?,
+        updated_at: row.get(17)?,
+    })
+}
+
+pub fn load_assignment(conn: &Connection, id: &str) -> rusqlite::Result<WorkAssignment> {
+    conn.query_row(&format!("{} WHERE a.id = ?1", ASSIGNMENT_SELECT), params![id], assignment_from_row)
+}
+
+/// Every assignment of an order, whole-order ones first, then by item and stage
+pub fn order_assignments(conn: &Connection, order_id: &str) -> rusqlite::Result<Vec<WorkAssignment>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE a.order_id = ?1 ORDER BY a.order_item_id IS NOT NULL, i.sort_order, {}",
+        ASSIGNMENT_SELECT, STAGE_ORDER
+    ))?;
+    let assignments = stmt.query_map(params![order_id], assignment_from_row)?.filter_map(|r| r.ok()).collect();
+    Ok(assignments)
+}
+
+/// Work not done yet on orders still open, soonest due first
+pub fn workload(conn: &Connection, tailor: Tailor) -> rusqlite::Result<TailorWorkload> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE a.tailor_id = ?1 AND a.status != 'done' AND o.status NOT IN ('{}')
+         ORDER BY a.due_date IS NULL, a.due_date, a.created_at",
+        ASSIGNMENT_SELECT,
+        order_status::CLOSED_STATUSES.join("', '")
+    ))?;
+    let assignments: Vec<WorkAssignment> =
+        stmt.query_map(params![tailor.id], assignment_from_row)?.filter_map(|r| r.ok()).collect();
+
+    let open_hours = assignments.iter().fold(0.0, |hours, a| hours + a.estimated_hours);
+    let days_of_work = if tailor.hours_per_day > 0.0 {
+        (open_hours / tailor.hours_per_day * 10.0).round() / 10.0
+    } else {
+        0.0
+    };
+    Ok(TailorWorkload {
+        overdue: assignments.iter().filter(|a| a.overdue).count() as i64,
+        open_hours,
+        days_of_work,
+        assignments,
+        tailor,
+    })
+}
+
+/// Garments of orders in production under the first stage they haven't
+/// finished. An item's own assignment for a stage takes precedence over
+/// one covering the whole order; a stage nobody has taken yet still holds
+/// the garment, as an unassigned card.
+pub fn board(conn: &Connection, tailor_id: Option<&str>) -> rusqlite::Result<Vec<BoardColumn>> {
+    let in_production = IN_PRODUCTION.join("', '");
+    let mut assignments: HashMap<String, Vec<WorkAssignment>> = HashMap::new();
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE o.status IN ('{}') AND o.suspected_spam = 0",
+        ASSIGNMENT_SELECT, in_production
+    ))?;
+    for assignment in stmt.query_map([], assignment_from_row)?.filter_map(|r| r.ok()) {
+        assignments.entry(assignment.order_id.clone()).or_default().push(assignment);
+    }
+
+    let mut columns: Vec<BoardColumn> = STAGES
+        .iter()
+        .chain(std::iter::once(&READY))
+        .map(|(stage, label)| BoardColumn {
+            stage: stage.to_string(),
+            label: label.to_string(),
+            cards: vec![],
+        })
+        .collect();
+
+    // Soonest promised first within each column
+    let mut stmt = conn.prepare(&format!(
+        "SELECT o.id, o.order_number, o.customer_name, o.status, o.delivery_date, o.product_name,
+                i.id, i.product_name, i.variant, i.quantity
+         FROM orders o
+         LEFT JOIN order_items i ON i.order_id = o.id
+         WHERE o.status IN ('{}') AND o.suspected_spam = 0
+         ORDER BY o.delivery_date IS NULL, o.delivery_date, o.created_at, i.sort_order",
+        in_production
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        let item_id: Option<String> = row.get(6)?;
+        let card = BoardCard {
+            order_id: row.get(0)?,
+            order_number: row.get(1)?,
+            customer_name: row.get(2)?,
+            order_status: row.get(3)?,
+            delivery_date: row.get(4)?,
+            product_name: match item_id {
+                Some(_) => row.get(7)?,
+                None => row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            },
+            variant: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+            quantity: row.get::<_, Option<i64>>(9)?.unwrap_or(1),
+            order_item_id: item_id,
+            assignment: None,
+        };
+        Ok(card)
+    })?;
+
+    for mut card in rows.filter_map(|r| r.ok()) {
+        let order_assignments = assignments.get(&card.order_id).map(Vec::as_slice).unwrap_or_default();
+        let for_stage = |stage: &str| {
+            let mine = order_assignments
+                .iter()
+                .find(|a| a.stage == stage && a.order_item_id.is_some() && a.order_item_id == card.order_item_id);
+            mine.or_else(|| order_assignments.iter().find(|a| a.stage == stage && a.order_item_id.is_none()))
+        };
+        let current = STAGES
+            .iter()
+            .position(|(stage, _)| for_stage(stage).is_none_or(|a| a.status != "done"))
+            .unwrap_or(STAGES.len());
+        card.assignment = STAGES.get(current).and_then(|(stage, _)| for_stage(stage)).cloned();
+
+        if let Some(tailor_id) = tailor_id {
+            if card.assignment.as_ref().is_none_or(|a| a.tailor_id != tailor_id) {
+                continue;
+            }
+        }
+        columns[current].cards.push(card);
+    }
+
+    Ok(columns)
+}